@@ -0,0 +1,377 @@
+// audio as a shadertoy style 512x2 texture, with the spectrum in the first row and the waveform in the second
+
+use std::{collections::VecDeque, f32::consts::PI, fs::File, io::BufReader, path::Path, sync::{Arc, Mutex}, time::Duration};
+use cpal::{FromSample, Sample, SampleFormat, SizedSample, Stream, traits::{DeviceTrait, HostTrait, StreamTrait}};
+use rodio::{buffer::SamplesBuffer, Decoder, OutputStream, Sink, Source};
+use wgpu::{Device, Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d, Queue, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages};
+
+use crate::channel::Channel;
+
+// the same analysis shadertoy gets from the web audio api
+const FFT_SIZE: usize = 2048;
+const WIDTH: usize = 512;
+const SMOOTHING: f32 = 0.8;
+const MIN_DECIBELS: f32 = -100.;
+const MAX_DECIBELS: f32 = -30.;
+
+// how many frames of spectral flux a beat has to stand out from, and how many standard deviations above their average it has to be
+const FLUX_HISTORY: usize = 43;
+// how many frames have to be heard before anything counts as standing out
+const MIN_FLUX_HISTORY: usize = 8;
+const SENSITIVITY: f32 = 1.5;
+// beats closer together than this are the same beat, which caps the tempo at 240 bpm
+const MIN_INTERVAL: f32 = 0.25;
+// a gap longer than this starts counting the tempo over
+const MAX_INTERVAL: f32 = 2.;
+// how many of the latest beats the tempo is the median of
+const INTERVALS: usize = 8;
+// tempos are doubled or halved until they're in this range, since beats are easily detected at half or twice the tempo
+const MIN_BPM: f32 = 70.;
+const MAX_BPM: f32 = 180.;
+// how quickly the pulse fades after each beat, per second
+const PULSE_DECAY: f32 = 8.;
+
+// finds onsets in the spectrum by how much louder it gets from one frame to the next compared to the last second or so,
+// and the tempo from the time between them
+#[derive(Default)]
+struct BeatDetector {
+  // the last frame's magnitudes, compressed the way ears hear them
+  previous: Vec<f32>,
+  fluxes: VecDeque<f32>,
+  intervals: VecDeque<f32>,
+  last_beat: Option<f32>,
+  // when the spectrum was last analysed, so going back in time can start over
+  last_time: Option<f32>,
+}
+
+impl BeatDetector {
+  fn detect(&mut self, magnitudes: &[f32], time: f32) {
+    match self.last_time {
+      // seeking back or restarting loses track of where the beat was
+      Some(last_time) if time < last_time => *self = BeatDetector::default(),
+      // the same samples again while paused
+      Some(last_time) if time == last_time => return,
+      _ => {}
+    }
+    self.last_time = Some(time);
+    let compressed: Vec<f32> = magnitudes.iter().map(|magnitude| (1. + 1000. * magnitude).ln()).collect();
+    let flux: f32 = compressed.iter().zip(&self.previous).map(|(now, before)| (now - before).max(0.)).sum();
+    self.previous = compressed;
+
+    let count = self.fluxes.len() as f32;
+    let mean = self.fluxes.iter().sum::<f32>() / count.max(1.);
+    let deviation = (self.fluxes.iter().map(|flux| (flux - mean) * (flux - mean)).sum::<f32>() / count.max(1.)).sqrt();
+    let is_onset = self.fluxes.len() >= MIN_FLUX_HISTORY && flux > mean + SENSITIVITY * deviation && flux > f32::EPSILON;
+    if self.fluxes.len() == FLUX_HISTORY {
+      self.fluxes.pop_front();
+    }
+    self.fluxes.push_back(flux);
+    if !is_onset || self.last_beat.is_some_and(|last_beat| time - last_beat < MIN_INTERVAL) {
+      return;
+    }
+    match self.last_beat.map(|last_beat| time - last_beat) {
+      Some(interval) if interval <= MAX_INTERVAL => {
+        if self.intervals.len() == INTERVALS {
+          self.intervals.pop_front();
+        }
+        self.intervals.push_back(interval);
+      }
+      _ => self.intervals.clear(),
+    }
+    self.last_beat = Some(time);
+  }
+
+  // 1 on the frame of a beat, fading towards 0 until the next one
+  fn pulse(&self, time: f32) -> f32 {
+    self.last_beat.map_or(0., |last_beat| (-(time - last_beat).max(0.) * PULSE_DECAY).exp())
+  }
+
+  // beats per minute, or 0 until there have been a few beats in a row
+  fn bpm(&self) -> f32 {
+    if self.intervals.len() < 2 {
+      return 0.;
+    }
+    let mut intervals: Vec<f32> = self.intervals.iter().copied().collect();
+    intervals.sort_by(f32::total_cmp);
+    let mut bpm = 60. / intervals[intervals.len() / 2];
+    while bpm < MIN_BPM {
+      bpm *= 2.;
+    }
+    while bpm >= MAX_BPM {
+      bpm /= 2.;
+    }
+    bpm
+  }
+}
+
+pub struct AudioTexture {
+  texture: Texture,
+  // each frequency's magnitude, blended with the previous frames' so the spectrum doesn't flicker
+  smoothed: Vec<f32>,
+  beats: BeatDetector,
+  // when the samples were last analysed, which the pulse fades from
+  time: f32,
+}
+
+impl AudioTexture {
+  pub fn new(device: &Device) -> Self {
+    let texture = device.create_texture(&TextureDescriptor {
+      label: Some("audio"),
+      size: Extent3d { width: WIDTH as u32, height: 2, depth_or_array_layers: 1 },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D2,
+      format: TextureFormat::R8Unorm,
+      usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+    });
+    AudioTexture { texture, smoothed: vec![0.; WIDTH], beats: BeatDetector::default(), time: 0. }
+  }
+
+  pub fn channel(&self, device: &Device) -> Channel {
+    Channel::from_texture(device, &self.texture)
+  }
+
+  // analyse the most recent samples, oldest first, which lead up to `time` seconds, and upload the result
+  pub fn update(&mut self, queue: &Queue, samples: &[f32], time: f32) {
+    // pad the start with silence if there aren't enough samples yet
+    let samples = &samples[samples.len().saturating_sub(FFT_SIZE)..];
+    let samples: Vec<f32> = std::iter::repeat_n(0., FFT_SIZE - samples.len()).chain(samples.iter().copied()).collect();
+
+    // a blackman window keeps frequencies from leaking into their neighbours
+    let mut real: Vec<f32> = samples.iter().enumerate().map(|(index, sample)| {
+      let x = index as f32 / FFT_SIZE as f32;
+      sample * (0.42 - 0.5 * (2. * PI * x).cos() + 0.08 * (4. * PI * x).cos())
+    }).collect();
+    let mut imaginary = vec![0.; FFT_SIZE];
+    fft(&mut real, &mut imaginary);
+
+    let magnitudes: Vec<f32> = (0..WIDTH).map(|bin| (real[bin] * real[bin] + imaginary[bin] * imaginary[bin]).sqrt() / FFT_SIZE as f32).collect();
+    // beats are found before smoothing, which would blur their onsets
+    self.beats.detect(&magnitudes, time);
+    self.time = time;
+    let mut pixels = vec![0u8; WIDTH * 2];
+    for (bin, (smoothed, magnitude)) in self.smoothed.iter_mut().zip(magnitudes).enumerate() {
+      *smoothed = SMOOTHING * *smoothed + (1. - SMOOTHING) * magnitude;
+      let decibels = 20. * smoothed.max(f32::MIN_POSITIVE).log10();
+      pixels[bin] = ((decibels - MIN_DECIBELS) / (MAX_DECIBELS - MIN_DECIBELS) * 255.).clamp(0., 255.) as u8;
+    }
+    for (pixel, sample) in pixels[WIDTH..].iter_mut().zip(&samples[FFT_SIZE - WIDTH..]) {
+      *pixel = (128. + sample * 128.).clamp(0., 255.) as u8;
+    }
+
+    queue.write_texture(
+      ImageCopyTexture {
+        texture: &self.texture,
+        mip_level: 0,
+        origin: Origin3d::ZERO,
+        aspect: TextureAspect::All,
+      },
+      &pixels,
+      ImageDataLayout {
+        offset: 0,
+        bytes_per_row: std::num::NonZeroU32::new(WIDTH as u32),
+        rows_per_image: None,
+      },
+      Extent3d { width: WIDTH as u32, height: 2, depth_or_array_layers: 1 },
+    );
+  }
+
+  // the pulse of the latest beat and the tempo as of the last update
+  pub fn beat(&self) -> [f32; 2] {
+    [self.beats.pulse(self.time), self.beats.bpm()]
+  }
+}
+
+// the default input device, listened to for as long as this is alive
+pub struct Microphone {
+  _stream: Stream,
+  // the latest samples mixed down to mono
+  samples: Arc<Mutex<VecDeque<f32>>>,
+  texture: AudioTexture,
+}
+
+impl Microphone {
+  pub fn start(device: &Device) -> Result<Self, String> {
+    let input = cpal::default_host().default_input_device().ok_or("there is no microphone")?;
+    let config = input.default_input_config().map_err(|error| error.to_string())?;
+    let samples = Arc::new(Mutex::new(VecDeque::with_capacity(FFT_SIZE)));
+    let stream = match config.sample_format() {
+      SampleFormat::I8 => listen::<i8>(&input, &config.config(), samples.clone()),
+      SampleFormat::I16 => listen::<i16>(&input, &config.config(), samples.clone()),
+      SampleFormat::I32 => listen::<i32>(&input, &config.config(), samples.clone()),
+      SampleFormat::U8 => listen::<u8>(&input, &config.config(), samples.clone()),
+      SampleFormat::U16 => listen::<u16>(&input, &config.config(), samples.clone()),
+      SampleFormat::U32 => listen::<u32>(&input, &config.config(), samples.clone()),
+      SampleFormat::F32 => listen::<f32>(&input, &config.config(), samples.clone()),
+      SampleFormat::F64 => listen::<f64>(&input, &config.config(), samples.clone()),
+      format => return Err(format!("unable to read {} samples", format)),
+    }?;
+    stream.play().map_err(|error| error.to_string())?;
+    Ok(Microphone { _stream: stream, samples, texture: AudioTexture::new(device) })
+  }
+
+  pub fn channel(&self, device: &Device) -> Channel {
+    self.texture.channel(device)
+  }
+
+  // analyse whatever was heard last, as of `time` seconds in
+  pub fn update(&mut self, queue: &Queue, time: f32) {
+    let samples: Vec<f32> = self.samples.lock().map(|samples| samples.iter().copied().collect()).unwrap_or_default();
+    self.texture.update(queue, &samples, time);
+  }
+
+  pub fn beat(&self) -> [f32; 2] {
+    self.texture.beat()
+  }
+}
+
+// keep the last `FFT_SIZE` samples that come in from `input`
+fn listen<T: SizedSample>(input: &cpal::Device, config: &cpal::StreamConfig, samples: Arc<Mutex<VecDeque<f32>>>) -> Result<Stream, String> where f32: FromSample<T> {
+  let channels = config.channels as usize;
+  input.build_input_stream(
+    config,
+    move |data: &[T], _| {
+      let Ok(mut samples) = samples.lock() else {
+        return;
+      };
+      for frame in data.chunks(channels) {
+        let mono = frame.iter().map(|&sample| f32::from_sample(sample)).sum::<f32>() / channels as f32;
+        if samples.len() == FFT_SIZE {
+          samples.pop_front();
+        }
+        samples.push_back(mono);
+      }
+    },
+    |error| println!("[Horus] Microphone error: {}", error),
+    None,
+  ).map_err(|error| error.to_string())
+}
+
+// an in place radix 2 fast fourier transform, `real` and `imaginary` have to be the same power of two long
+fn fft(real: &mut [f32], imaginary: &mut [f32]) {
+  let length = real.len();
+  let mut j = 0;
+  for i in 1..length {
+    let mut bit = length >> 1;
+    while j & bit != 0 {
+      j ^= bit;
+      bit >>= 1;
+    }
+    j |= bit;
+    if i < j {
+      real.swap(i, j);
+      imaginary.swap(i, j);
+    }
+  }
+
+  let mut size = 2;
+  while size <= length {
+    let angle = -2. * PI / size as f32;
+    for start in (0..length).step_by(size) {
+      for k in 0..size / 2 {
+        let (sin, cos) = (angle * k as f32).sin_cos();
+        let (even, odd) = (start + k, start + k + size / 2);
+        let odd_real = real[odd] * cos - imaginary[odd] * sin;
+        let odd_imaginary = real[odd] * sin + imaginary[odd] * cos;
+        real[odd] = real[even] - odd_real;
+        imaginary[odd] = imaginary[even] - odd_imaginary;
+        real[even] += odd_real;
+        imaginary[even] += odd_imaginary;
+      }
+    }
+    size *= 2;
+  }
+}
+
+// a decoded audio file, played back in the window while its texture follows `time`
+pub struct AudioFile {
+  // interleaved samples for playback, and a mono mix of them for the texture
+  samples: Vec<f32>,
+  mono: Vec<f32>,
+  channels: u16,
+  pub sample_rate: u32,
+  texture: AudioTexture,
+  // the output stream has to outlive the sink that plays into it
+  playback: Option<(OutputStream, Sink)>,
+}
+
+impl AudioFile {
+  pub fn load(device: &Device, path: &Path) -> Result<Self, String> {
+    let file = File::open(path).map_err(|error| error.to_string())?;
+    let decoder = Decoder::new(BufReader::new(file)).map_err(|error| error.to_string())?;
+    let channels = decoder.channels();
+    let sample_rate = decoder.sample_rate();
+    Ok(AudioFile::from_samples(device, decoder.convert_samples().collect(), channels, sample_rate))
+  }
+
+  // audio that was made rather than decoded, like a sound shader's, interleaved if there's more than one channel
+  pub fn from_samples(device: &Device, samples: Vec<f32>, channels: u16, sample_rate: u32) -> Self {
+    let mut audio = AudioFile { samples: Vec::new(), mono: Vec::new(), channels, sample_rate, texture: AudioTexture::new(device), playback: None };
+    audio.set_samples(samples, channels, sample_rate);
+    audio
+  }
+
+  // replace what's played with `samples`, keeping the texture that channels read from, which has to be played again to be heard
+  pub fn set_samples(&mut self, samples: Vec<f32>, channels: u16, sample_rate: u32) {
+    self.mono = samples.chunks(channels as usize).map(|frame| frame.iter().sum::<f32>() / channels as f32).collect();
+    self.samples = samples;
+    self.channels = channels;
+    self.sample_rate = sample_rate;
+    self.playback = None;
+  }
+
+  pub fn channel(&self, device: &Device) -> Channel {
+    self.texture.channel(device)
+  }
+
+  // start playing from the beginning through the default output device
+  pub fn play(&mut self) -> Result<(), String> {
+    let (stream, handle) = OutputStream::try_default().map_err(|error| error.to_string())?;
+    let sink = Sink::try_new(&handle).map_err(|error| error.to_string())?;
+    sink.append(SamplesBuffer::new(self.channels, self.sample_rate, self.samples.clone()));
+    self.playback = Some((stream, sink));
+    Ok(())
+  }
+
+  pub fn set_paused(&self, paused: bool) {
+    if let Some((_, sink)) = &self.playback {
+      if paused { sink.pause() } else { sink.play() }
+    }
+  }
+
+  // play faster or slower, which changes the pitch too
+  pub fn set_speed(&self, speed: f32) {
+    if let Some((_, sink)) = &self.playback {
+      sink.set_speed(speed);
+    }
+  }
+
+  // jump to `time` seconds into the file, starting it again if it had already finished
+  pub fn seek(&self, time: f32) {
+    if let Some((_, sink)) = &self.playback {
+      if sink.empty() {
+        sink.append(SamplesBuffer::new(self.channels, self.sample_rate, self.samples.clone()));
+      }
+      if let Err(error) = sink.try_seek(Duration::from_secs_f32(time.max(0.))) {
+        println!("[Horus] Unable to seek the audio: {}", error);
+      }
+    }
+  }
+
+  // how far into the file playback has got, or `None` once it has finished or if it never started
+  pub fn position(&self) -> Option<f32> {
+    self.playback.as_ref().filter(|(_, sink)| !sink.empty()).map(|(_, sink)| sink.get_pos().as_secs_f32())
+  }
+
+  // analyse the samples leading up to `time` seconds into the file
+  pub fn update(&mut self, queue: &Queue, time: f32) {
+    // it's silent before the file starts and after it ends
+    let end = (time.max(0.) * self.sample_rate as f32) as usize;
+    let samples: Vec<f32> = (end.saturating_sub(FFT_SIZE)..end).map(|index| self.mono.get(index).copied().unwrap_or(0.)).collect();
+    self.texture.update(queue, &samples, time);
+  }
+
+  pub fn beat(&self) -> [f32; 2] {
+    self.texture.beat()
+  }
+}
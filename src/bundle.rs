@@ -0,0 +1,196 @@
+// `horus bundle` copies the horus executable with a project's files appended to the end of it, which it unpacks and runs by itself
+// whenever it's started, so a shader can be shared as a single file that runs without Horus or the project
+//
+// the files are followed by how many bytes they take up and MAGIC, so the executable can find them by reading its own end
+//
+// each run unpacks into a directory of its own, named so nothing else can have made it first, which is removed again when it closes
+
+use std::{collections::hash_map::RandomState, fs::{self, DirBuilder, File}, hash::BuildHasher, io::{self, Read, Seek, SeekFrom, Write}, path::{Component, Path, PathBuf}, process, sync::Mutex};
+
+use crate::{cli::Options, config::{self, Config}, pass::{BUFFER_NAMES, COMMON_NAME, CUBEMAP_NAME, SOUND_NAME}, playlist, shader};
+
+const MAGIC: &[u8; 8] = b"HORUSPAK";
+
+// where this run unpacked its project, which clean_up removes
+static UNPACKED: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// bundle the shader or project at `path` into a new executable at `output`
+pub fn create(path: &Path, output: &Path) -> Result<(), String> {
+  let mut options = Options { fragment_path: Some(path.to_path_buf()), ..Default::default() };
+  Config::apply(&mut options)?;
+  let root = if path.is_dir() { path } else { path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new(".")) };
+  let root = root.canonicalize().map_err(|error| format!("Unable to find {}: {}", root.display(), error))?;
+
+  let files = project_files(&root, &options)?;
+  let mut payload = Vec::new();
+  for file in &files {
+    let relative = file.strip_prefix(&root).map_err(|_| format!("{} is outside of {}, so it can't be bundled", file.display(), root.display()))?;
+    let name = relative.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+    let data = fs::read(file).map_err(|error| format!("Unable to read {}: {}", file.display(), error))?;
+    push_file(&mut payload, &name, &data);
+    println!("[Horus] Bundling {}", name);
+  }
+  // a shader by itself gets a horus.toml that says to run it, since the bundle unpacks into a directory
+  let shader = options.fragment_path.as_deref().filter(|shader| shader.is_file()).and_then(|shader| shader.canonicalize().ok());
+  if let (Some(shader), false) = (shader, root.join(config::NAME).is_file()) {
+    let name = shader.strip_prefix(&root).unwrap_or(&shader).to_string_lossy().replace('\\', "/");
+    push_file(&mut payload, config::NAME, format!("shader = {:?}\n", name).as_bytes());
+  }
+  payload.extend((payload.len() as u64).to_le_bytes());
+  payload.extend(MAGIC);
+
+  // a bundle of a bundle only keeps the new project
+  let executable = std::env::current_exe().map_err(|error| format!("Unable to find the horus executable: {}", error))?;
+  let mut horus = fs::read(&executable).map_err(|error| format!("Unable to read {}: {}", executable.display(), error))?;
+  if let Some(start) = payload_start(&horus) {
+    horus.truncate(start);
+  }
+  let mut output = output.to_path_buf();
+  if output.extension().is_none() && !std::env::consts::EXE_EXTENSION.is_empty() {
+    output.set_extension(std::env::consts::EXE_EXTENSION);
+  }
+  let mut file = File::create(&output).map_err(|error| format!("Unable to create {}: {}", output.display(), error))?;
+  file.write_all(&horus).and_then(|_| file.write_all(&payload)).map_err(|error| format!("Unable to write to {}: {}", output.display(), error))?;
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    file.set_permissions(fs::Permissions::from_mode(0o755)).map_err(|error| format!("Unable to make {} executable: {}", output.display(), error))?;
+  }
+  println!("[Horus] Bundled {} files into {}, which runs without Horus", files.len(), output.display());
+  Ok(())
+}
+
+// unpack the project appended to this executable into a temporary directory, returning where, if it's a bundle at all
+pub fn extract() -> Result<Option<PathBuf>, String> {
+  let Ok(executable) = std::env::current_exe() else {
+    return Ok(None);
+  };
+  let Ok(mut file) = File::open(&executable) else {
+    return Ok(None);
+  };
+  // the end is checked first so Horus itself doesn't read the whole executable every time it starts
+  let mut trailer = [0; 16];
+  if file.seek(SeekFrom::End(-16)).and_then(|_| file.read_exact(&mut trailer)).is_err() || &trailer[8..] != MAGIC {
+    return Ok(None);
+  }
+  let length = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+  let mut payload = vec![0; length as usize];
+  file.seek(SeekFrom::End(-16 - length as i64)).and_then(|_| file.read_exact(&mut payload)).map_err(|error| format!("Unable to read the bundled project: {}", error))?;
+
+  let name = executable.file_stem().unwrap_or_default().to_string_lossy();
+  let directory = create_directory(&name)?;
+  *UNPACKED.lock().unwrap() = Some(directory.clone());
+  let mut rest = payload.as_slice();
+  while !rest.is_empty() {
+    let (name, data);
+    (name, rest) = take(rest, 4).and_then(|(length, rest)| take(rest, u32::from_le_bytes(length.try_into().unwrap()) as usize)).ok_or("The bundled project is cut short")?;
+    (data, rest) = take(rest, 8).and_then(|(length, rest)| take(rest, u64::from_le_bytes(length.try_into().unwrap()) as usize)).ok_or("The bundled project is cut short")?;
+    // names only ever lead further into the directory
+    let name = Path::new(std::str::from_utf8(name).map_err(|error| error.to_string())?);
+    if !name.components().all(|component| matches!(component, Component::Normal(_))) {
+      return Err(format!("The bundled project has a file outside of it at {}", name.display()));
+    }
+    let path = directory.join(name);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).map_err(|error| format!("Unable to create {}: {}", parent.display(), error))?;
+    }
+    fs::write(&path, data).map_err(|error| format!("Unable to write {}: {}", path.display(), error))?;
+  }
+  Ok(Some(directory))
+}
+
+// remove the directory this run unpacked its project into, if it did
+pub fn clean_up() {
+  if let Some(directory) = UNPACKED.lock().unwrap().take() {
+    let _ = fs::remove_dir_all(directory);
+  }
+}
+
+// a new directory in the temporary directory that only this user can read, which is never one that's already there, since another
+// copy of the bundle could be running from it or another user could have made it to swap the files out
+fn create_directory(name: &str) -> Result<PathBuf, String> {
+  let mut builder = DirBuilder::new();
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::DirBuilderExt;
+    builder.mode(0o700);
+  }
+  for attempt in 0..16 {
+    // every RandomState is seeded differently
+    let suffix = RandomState::new().hash_one((process::id(), attempt));
+    let directory = std::env::temp_dir().join(format!("horus-{}-{}-{:016x}", name, process::id(), suffix));
+    match builder.create(&directory) {
+      Ok(()) => return Ok(directory),
+      Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+      Err(error) => return Err(format!("Unable to create {}: {}", directory.display(), error)),
+    }
+  }
+  Err("Unable to find a directory to unpack the bundled project into that doesn't exist yet".to_string())
+}
+
+// where the files start in `executable`, if there are any
+fn payload_start(executable: &[u8]) -> Option<usize> {
+  let trailer = executable.len().checked_sub(16).map(|start| &executable[start..])?;
+  if &trailer[8..] != MAGIC {
+    return None;
+  }
+  (executable.len() - 16).checked_sub(u64::from_le_bytes(trailer[..8].try_into().unwrap()) as usize)
+}
+
+fn push_file(payload: &mut Vec<u8>, name: &str, data: &[u8]) {
+  payload.extend((name.len() as u32).to_le_bytes());
+  payload.extend(name.as_bytes());
+  payload.extend((data.len() as u64).to_le_bytes());
+  payload.extend(data);
+}
+
+fn take(bytes: &[u8], length: usize) -> Option<(&[u8], &[u8])> {
+  (bytes.len() >= length).then(|| bytes.split_at(length))
+}
+
+// everything running `options` reads, which is horus.toml, the shader and whatever sits next to it under the names Horus looks for,
+// anything horus.toml points to, the files the shaders include and the channels and audio that are files
+fn project_files(root: &Path, options: &Options) -> Result<Vec<PathBuf>, String> {
+  let mut paths = vec![root.join(config::NAME)];
+  match &options.fragment_path {
+    // a playlist bundles every file in its directory
+    Some(directory) if directory.is_dir() => paths.extend(fs::read_dir(directory).map_err(|error| error.to_string())?.filter_map(|entry| Some(entry.ok()?.path())).filter(|path| path.is_file())),
+    Some(shader) => {
+      let extension = shader.extension().unwrap_or_default();
+      paths.push(shader.clone());
+      paths.extend(BUFFER_NAMES.iter().chain([&COMMON_NAME, &CUBEMAP_NAME, &SOUND_NAME]).map(|name| shader.with_file_name(name).with_extension(extension)));
+      paths.push(shader.with_extension("toml"));
+      paths.push(shader.with_extension("timeline.toml"));
+    }
+    None => return Err("There's no shader to bundle".to_string()),
+  }
+  paths.extend(options.buffers.iter().flatten().cloned());
+  paths.extend(options.common.clone());
+  paths.extend(options.cubemap.clone());
+  paths.extend(options.sound.clone());
+  paths.extend(options.uniforms.clone());
+  paths.extend(options.timeline.clone());
+  paths.extend(options.vertex.clone());
+  paths.extend(options.channels().into_iter().filter_map(|channel| channel.path));
+  paths.extend(options.audio.clone());
+
+  // cubemaps and volumes can be directories of faces and slices
+  let mut files = Vec::new();
+  while let Some(path) = paths.pop() {
+    if path.is_dir() {
+      paths.extend(fs::read_dir(&path).map_err(|error| error.to_string())?.filter_map(|entry| Some(entry.ok()?.path())));
+    } else if path.is_file() {
+      let path = path.canonicalize().map_err(|error| format!("Unable to find {}: {}", path.display(), error))?;
+      if files.contains(&path) || path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+        continue;
+      }
+      // shaders bring along the files they include
+      if let Some(source) = playlist::is_shader(&path).then(|| fs::read_to_string(&path).ok()).flatten() {
+        paths.extend(shader::includes(&path, &source, &options.modules));
+      }
+      files.push(path);
+    }
+  }
+  files.sort();
+  Ok(files)
+}
@@ -0,0 +1,246 @@
+// reads rendered frames back from the GPU so they can be saved as images
+
+use std::{fs::{create_dir_all, File}, io::Write, num::NonZeroU32, path::{Path, PathBuf}, process::{Child, Command, Stdio}};
+use chrono::Local;
+use half::f16;
+use image::{codecs::gif::{GifEncoder, Repeat}, Delay, Frame, Rgba32FImage, RgbaImage};
+use wgpu::{Buffer, BufferDescriptor, BufferUsages, CommandEncoder, Device, Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Maintain, MapMode, Origin3d, Texture, TextureAspect, TextureFormat, COPY_BYTES_PER_ROW_ALIGNMENT};
+
+use crate::metadata::Metadata;
+
+// a copy of a texture on its way to the CPU
+pub struct Readback {
+  buffer: Buffer,
+  width: u32,
+  height: u32,
+  // rows are padded to a multiple of 256 bytes in the buffer
+  padded_bytes_per_row: u32,
+  // surfaces are often BGRA, which has to be swizzled into RGBA, `horus render --format exr` reads back half floats and sound
+  // passes read back 32-bit floats
+  format: TextureFormat,
+}
+
+impl Readback {
+  // record a copy of `texture` into a buffer that can be read once `encoder` has been submitted
+  pub fn new(device: &Device, encoder: &mut CommandEncoder, texture: &Texture, width: u32, height: u32, format: TextureFormat) -> Result<Self, String> {
+    Self::region(device, encoder, texture, Origin3d::ZERO, width, height, format)
+  }
+
+  // record a copy of just the pixel at `x`, `y`, which reads back as a 1x1 image
+  pub fn pixel(device: &Device, encoder: &mut CommandEncoder, texture: &Texture, x: u32, y: u32, format: TextureFormat) -> Result<Self, String> {
+    Self::region(device, encoder, texture, Origin3d { x, y, z: 0 }, 1, 1, format)
+  }
+
+  fn region(device: &Device, encoder: &mut CommandEncoder, texture: &Texture, origin: Origin3d, width: u32, height: u32, format: TextureFormat) -> Result<Self, String> {
+    let bytes_per_pixel = match format {
+      TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb | TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => 4,
+      TextureFormat::Rgba16Float | TextureFormat::Rg32Float => 8,
+      _ => return Err(format!("unable to read back {:?} textures", format)),
+    };
+    let padded_bytes_per_row = (width * bytes_per_pixel).div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT;
+    let buffer = device.create_buffer(&BufferDescriptor {
+      label: Some("readback"),
+      size: (padded_bytes_per_row * height) as u64,
+      usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+      mapped_at_creation: false,
+    });
+    encoder.copy_texture_to_buffer(
+      ImageCopyTexture {
+        texture,
+        mip_level: 0,
+        origin,
+        aspect: TextureAspect::All,
+      },
+      ImageCopyBuffer {
+        buffer: &buffer,
+        layout: ImageDataLayout {
+          offset: 0,
+          bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+          rows_per_image: None,
+        },
+      },
+      Extent3d { width, height, depth_or_array_layers: 1 },
+    );
+    Ok(Readback { buffer, width, height, padded_bytes_per_row, format })
+  }
+
+  // wait for the copy of an 8-bit texture to finish
+  pub fn into_image(self, device: &Device) -> Result<RgbaImage, String> {
+    let bgra = match self.format {
+      TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+      TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+      format => return Err(format!("unable to read {:?} textures into 8-bit images", format)),
+    };
+    let (width, height) = (self.width, self.height);
+    let mut pixels = self.read(device, 4)?;
+    if bgra {
+      for pixel in pixels.chunks_mut(4) {
+        pixel.swap(0, 2);
+      }
+    }
+    RgbaImage::from_raw(width, height, pixels).ok_or("the frame is the wrong size".to_string())
+  }
+
+  // wait for the copy of a half float texture to finish, keeping every value as it was written
+  pub fn into_float_image(self, device: &Device) -> Result<Rgba32FImage, String> {
+    if self.format != TextureFormat::Rgba16Float {
+      return Err(format!("unable to read {:?} textures into float images", self.format));
+    }
+    let (width, height) = (self.width, self.height);
+    let values = self.read(device, 8)?.chunks_exact(2).map(|bytes| f16::from_le_bytes([bytes[0], bytes[1]]).to_f32()).collect();
+    Rgba32FImage::from_raw(width, height, values).ok_or("the frame is the wrong size".to_string())
+  }
+
+  // wait for the copy of a sound pass' samples to finish, which are a left and a right float for each pixel
+  pub fn into_samples(self, device: &Device) -> Result<Vec<f32>, String> {
+    if self.format != TextureFormat::Rg32Float {
+      return Err(format!("unable to read {:?} textures into samples", self.format));
+    }
+    Ok(self.read(device, 8)?.chunks_exact(4).map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).collect())
+  }
+
+  // wait for the copy to finish and strip the padding off of each row
+  fn read(self, device: &Device, bytes_per_pixel: u32) -> Result<Vec<u8>, String> {
+    let slice = self.buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(MapMode::Read, move |result| {
+      let _ = sender.send(result);
+    });
+    device.poll(Maintain::Wait);
+    receiver.recv().map_err(|error| error.to_string())?.map_err(|error| error.to_string())?;
+
+    let mapped = slice.get_mapped_range();
+    let mut pixels = Vec::with_capacity((self.width * self.height * bytes_per_pixel) as usize);
+    for row in mapped.chunks(self.padded_bytes_per_row as usize) {
+      pixels.extend_from_slice(&row[..(self.width * bytes_per_pixel) as usize]);
+    }
+    drop(mapped);
+    self.buffer.unmap();
+    Ok(pixels)
+  }
+}
+
+// screenshots are saved in the current directory, named after the shader and when they were taken
+pub fn screenshot_path(shader: Option<&Path>) -> PathBuf {
+  // or after the title in its header
+  let title = shader.and_then(|path| Metadata::read(path).ok()?.file_name());
+  let name = title.as_deref().or_else(|| shader.and_then(|path| path.file_stem()).and_then(|stem| stem.to_str())).unwrap_or("fragment");
+  PathBuf::from(format!("{}-{}.png", name, Local::now().format("%Y%m%d-%H%M%S")))
+}
+
+// the frame rate recordings are encoded at, regardless of how fast the window is refreshing
+const RECORD_FPS: u32 = 60;
+
+// GIF delays are counted in hundredths of a second, and browsers slow down anything faster than 50 frames per second
+const GIF_FPS: u32 = 25;
+
+// how hard the quantizer works to pick each frame's palette, from 1 (best) to 30 (fastest)
+const GIF_SPEED: i32 = 10;
+
+// where the frame at `index` of a numbered sequence is saved, padded so the names sort in order
+pub fn sequence_path(directory: &Path, index: u32, digits: usize) -> PathBuf {
+  directory.join(format!("{:0digits$}.png", index, digits = digits))
+}
+
+// the number of digits that every frame of a sequence `frames` long can be padded to
+pub fn sequence_digits(frames: u32) -> usize {
+  frames.saturating_sub(1).to_string().len().max(4)
+}
+
+// where recorded frames go
+enum Sink {
+  // piped to ffmpeg as raw RGBA, which encodes them into whatever format the output's extension implies
+  Ffmpeg(Child),
+  // saved as numbered PNGs in a directory
+  Sequence(u32),
+  // quantized and appended to a looping GIF
+  Gif(GifEncoder<File>),
+}
+
+// records every frame into a video, a GIF or a PNG sequence if the output has no extension
+pub struct Recorder {
+  sink: Sink,
+  path: PathBuf,
+  width: u32,
+  height: u32,
+  pub fps: u32,
+  // how many more frames to record before stopping, or `None` to record until Horus closes
+  frames_left: Option<u32>,
+  // ffmpeg can't change resolution partway through, so frames of any other size are left out
+  skipping: bool,
+}
+
+impl Recorder {
+  pub fn start(path: &Path, width: u32, height: u32, seconds: Option<f32>) -> Result<Self, String> {
+    let mut fps = RECORD_FPS;
+    let sink = if path.extension().is_none() {
+      create_dir_all(path).map_err(|error| format!("unable to create {}: {}", path.display(), error))?;
+      Sink::Sequence(0)
+    } else if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("gif")) {
+      let file = File::create(path).map_err(|error| format!("unable to create {}: {}", path.display(), error))?;
+      let mut encoder = GifEncoder::new_with_speed(file, GIF_SPEED);
+      encoder.set_repeat(Repeat::Infinite).map_err(|error| error.to_string())?;
+      fps = GIF_FPS;
+      Sink::Gif(encoder)
+    } else {
+      Sink::Ffmpeg(Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pixel_format", "rgba"])
+        .args(["-video_size", &format!("{}x{}", width, height), "-framerate", &fps.to_string(), "-i", "-"])
+        // most players only handle 4:2:0, which needs even dimensions
+        .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2", "-pix_fmt", "yuv420p"])
+        .arg(path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("unable to start ffmpeg: {}", error))?)
+    };
+    let frames_left = seconds.map(|seconds| (seconds * fps as f32).round() as u32);
+    Ok(Recorder { sink, path: path.to_path_buf(), width, height, fps, frames_left, skipping: false })
+  }
+
+  pub fn write(&mut self, frame: &RgbaImage) -> Result<(), String> {
+    if frame.dimensions() != (self.width, self.height) {
+      if !self.skipping {
+        println!("[Horus] Leaving frames out of {} until the window is {}x{} again", self.path.display(), self.width, self.height);
+        self.skipping = true;
+      }
+      return Ok(());
+    }
+    self.skipping = false;
+    if let Some(frames_left) = &mut self.frames_left {
+      *frames_left = frames_left.saturating_sub(1);
+    }
+    match &mut self.sink {
+      Sink::Ffmpeg(ffmpeg) => {
+        let stdin = ffmpeg.stdin.as_mut().ok_or("ffmpeg has stopped")?;
+        stdin.write_all(frame.as_raw()).map_err(|error| format!("unable to write to ffmpeg: {}", error))
+      }
+      // there's no telling how long the recording will be, so leave room for plenty of frames
+      Sink::Sequence(index) => {
+        let path = sequence_path(&self.path, *index, 6);
+        *index += 1;
+        frame.save(&path).map_err(|error| format!("unable to save {}: {}", path.display(), error))
+      }
+      Sink::Gif(encoder) => {
+        let delay = Delay::from_numer_denom_ms(1000, self.fps);
+        encoder.encode_frame(Frame::from_parts(frame.clone(), 0, 0, delay)).map_err(|error| format!("unable to encode {}: {}", self.path.display(), error))
+      }
+    }
+  }
+
+  // whether every frame of a recording with a fixed length has been written
+  pub fn is_done(&self) -> bool {
+    self.frames_left == Some(0)
+  }
+
+  // close ffmpeg's input and wait for it to finish writing the video
+  pub fn finish(self) -> Result<PathBuf, String> {
+    if let Sink::Ffmpeg(mut ffmpeg) = self.sink {
+      drop(ffmpeg.stdin.take());
+      let status = ffmpeg.wait().map_err(|error| error.to_string())?;
+      if !status.success() {
+        return Err(format!("ffmpeg exited with {}", status));
+      }
+    }
+    Ok(self.path)
+  }
+}
@@ -0,0 +1,346 @@
+use std::{f32::consts::PI, num::NonZeroU32, path::Path};
+use half::f16;
+use image::{DynamicImage, Rgba32FImage};
+use wgpu::{AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Device, Extent3d, FilterMode, Queue, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension, util::DeviceExt};
+
+use crate::{cli::{Filter, Wrap}, font, keyboard, live, mipmap, noise, pass::{BUFFER_NAMES, CUBEMAP_NAME}, volume};
+
+// the number of textures a shader can read from, same as shadertoy
+pub const CHANNEL_COUNT: usize = 4;
+
+// whether `name` is one of the names a channel can be given instead of a path, for the passes and textures Horus makes itself,
+// which are never looked for on disk
+pub fn is_built_in(name: &str) -> bool {
+  [keyboard::NAME, live::MICROPHONE, live::AUDIO, live::WEBCAM, live::GAMEPAD, font::NAME, CUBEMAP_NAME].into_iter().chain(BUFFER_NAMES).chain(noise::NAMES).any(|built_in| built_in == name)
+}
+
+// how the bytes of an image relate to the values shaders read from it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+  // converted from sRGB to linear when sampled
+  Srgb,
+  // read exactly as stored
+  Linear,
+}
+
+// how a shader reads a channel, which is set for each of them rather than for whatever they're reading
+// shadertoy repeats and linearly filters textures by default
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sampling {
+  pub wrap: Wrap,
+  pub filter: Filter,
+}
+
+// wgpu's order of cubemap faces, along with the file names each face is looked up by
+const CUBE_FACES: [[&str; 3]; 6] = [
+  ["px", "posx", "right"],
+  ["nx", "negx", "left"],
+  ["py", "posy", "top"],
+  ["ny", "negy", "bottom"],
+  ["pz", "posz", "front"],
+  ["nz", "negz", "back"],
+];
+
+// a texture and the sampler used to read it, bound as `iChannel0` through `iChannel3`
+pub struct Channel {
+  view: TextureView,
+  sampler: Sampler,
+  // shaders declare cubemap channels as `texture_cube` and volumes as `texture_3d` instead of `texture_2d`
+  pub dimension: TextureViewDimension,
+}
+
+impl Channel {
+  // decode an image and upload it, flipping it upside down for shaders whose origin is in the bottom left
+  pub fn from_image(device: &Device, queue: &Queue, path: &Path, flip: bool, color_space: Option<ColorSpace>) -> Result<Self, String> {
+    let mut image = image::open(path).map_err(|error| error.to_string())?;
+    if flip {
+      image = image.flipv();
+    }
+
+    // HDR and EXR images keep their full range instead of being squashed into 8 bits
+    if let DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) = image {
+      return Ok(Self::from_float_rgba(device, queue, &image));
+    }
+
+    // color images are almost always sRGB, while grayscale ones tend to hold data like height maps
+    let color_space = color_space.unwrap_or(if image.color().has_color() { ColorSpace::Srgb } else { ColorSpace::Linear });
+    let format = match color_space {
+      ColorSpace::Srgb => TextureFormat::Rgba8UnormSrgb,
+      ColorSpace::Linear => TextureFormat::Rgba8Unorm,
+    };
+    let rgba = image.to_rgba8();
+    Ok(Self::from_pixels(device, queue, rgba.width(), rgba.height(), format, &rgba))
+  }
+
+  // unlike 32-bit floats, half floats can be filtered on every device
+  fn from_float_rgba(device: &Device, queue: &Queue, image: &DynamicImage) -> Self {
+    let rgba = image.to_rgba32f();
+    let halves: Vec<f16> = rgba.as_raw().iter().map(|&value| f16::from_f32(value)).collect();
+    Self::from_pixels(device, queue, rgba.width(), rgba.height(), TextureFormat::Rgba16Float, bytemuck::cast_slice(&halves))
+  }
+
+  // load a cubemap from a directory of six faces, or convert it from a single equirectangular image
+  pub fn cube_from_path(device: &Device, queue: &Queue, path: &Path) -> Result<Self, String> {
+    let faces: Vec<Rgba32FImage> = if path.is_dir() {
+      let entries: Vec<_> = path.read_dir().map_err(|error| error.to_string())?.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+      CUBE_FACES.iter().map(|names| {
+        let face = entries.iter()
+          .find(|entry| entry.file_stem().and_then(|stem| stem.to_str()).is_some_and(|stem| names.contains(&stem.to_lowercase().as_str())))
+          .ok_or(format!("no {} face in {}", names[0], path.display()))?;
+        image::open(face).map(|image| image.to_rgba32f()).map_err(|error| error.to_string())
+      }).collect::<Result<_, String>>()?
+    } else {
+      let equirect = image::open(path).map_err(|error| error.to_string())?.to_rgba32f();
+      equirect_to_cube(&equirect)
+    };
+    let size = faces[0].width();
+    if faces.iter().any(|face| face.width() != size || face.height() != size) {
+      return Err("cubemap faces must be square and the same size".to_string());
+    }
+
+    let halves: Vec<f16> = faces.iter().flat_map(|face| face.as_raw()).map(|&value| f16::from_f32(value)).collect();
+    let texture = create_mipmapped(device, queue, "cube channel", Extent3d { width: size, height: size, depth_or_array_layers: 6 }, TextureFormat::Rgba16Float, bytemuck::cast_slice(&halves));
+    let view = texture.create_view(&TextureViewDescriptor {
+      dimension: Some(TextureViewDimension::Cube),
+      ..Default::default()
+    });
+    Ok(Channel { view, sampler: create_sampler(device, Sampling::default()), dimension: TextureViewDimension::Cube })
+  }
+
+  // load a volume from a file of voxels or a directory of slices, which has no mipmaps since they're only made for 2D textures
+  pub fn volume_from_path(device: &Device, queue: &Queue, path: &Path, flip: bool, color_space: Option<ColorSpace>) -> Result<Self, String> {
+    let volume = volume::load(path, flip, color_space)?;
+    let max_size = device.limits().max_texture_dimension_3d;
+    if volume.width.max(volume.height).max(volume.depth) > max_size {
+      return Err(format!("it's {}x{}x{}, and this GPU's volumes can only be {} across", volume.width, volume.height, volume.depth, max_size));
+    }
+    let texture = device.create_texture_with_data(queue, &TextureDescriptor {
+      label: Some("volume channel"),
+      size: Extent3d { width: volume.width, height: volume.height, depth_or_array_layers: volume.depth },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D3,
+      format: volume.format,
+      usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+    }, &volume.voxels);
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    Ok(Channel { view, sampler: create_sampler(device, Sampling::default()), dimension: TextureViewDimension::D3 })
+  }
+
+  // one of the noise textures built into Horus generated from `seed`, if `name` is one of `noise::NAMES`
+  pub fn noise(device: &Device, queue: &Queue, name: &str, seed: u64) -> Option<Self> {
+    let (size, pixels) = noise::generate(name, seed)?;
+    Some(Self::from_pixels(device, queue, size, size, TextureFormat::Rgba8Unorm, &pixels))
+  }
+
+  // the glyph atlas built into Horus, flipped upside down for shaders whose origin is in the bottom left like images are
+  pub fn font(device: &Device, queue: &Queue, flip: bool) -> Self {
+    let mut pixels = font::generate();
+    if flip {
+      let row = font::SIZE as usize * 4;
+      pixels = pixels.chunks_exact(row).rev().flatten().copied().collect();
+    }
+    Self::from_pixels(device, queue, font::SIZE, font::SIZE, TextureFormat::Rgba8Unorm, &pixels)
+  }
+
+  // a magenta and black checkerboard that makes missing images easy to spot
+  pub fn checkerboard(device: &Device, queue: &Queue) -> Self {
+    let size = 256;
+    let pixels: Vec<u8> = (0..size * size).flat_map(|index| {
+      let (x, y) = (index % size / 32, index / size / 32);
+      if (x + y) % 2 == 0 { [u8::MAX, 0, u8::MAX, u8::MAX] } else { [0, 0, 0, u8::MAX] }
+    }).collect();
+    Self::from_pixels(device, queue, size, size, TextureFormat::Rgba8UnormSrgb, &pixels)
+  }
+
+  // a texture that a pass renders into and later passes read from
+  pub fn target(device: &Device, width: u32, height: u32, format: TextureFormat) -> Self {
+    let texture = device.create_texture(&TextureDescriptor {
+      label: Some("buffer"),
+      size: Extent3d { width, height, depth_or_array_layers: 1 },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D2,
+      format,
+      usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+    });
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    Channel { view, sampler: create_sampler(device, Sampling::default()), dimension: TextureViewDimension::D2 }
+  }
+
+  // a cubemap that a pass renders into one face at a time, along with a view of each face in wgpu's order
+  pub fn cube_target(device: &Device, size: u32, format: TextureFormat) -> (Self, Vec<TextureView>) {
+    let texture = device.create_texture(&TextureDescriptor {
+      label: Some("cubemap"),
+      size: Extent3d { width: size, height: size, depth_or_array_layers: 6 },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D2,
+      format,
+      usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+    });
+    let faces = (0..CUBE_FACES.len() as u32).map(|face| texture.create_view(&TextureViewDescriptor {
+      dimension: Some(TextureViewDimension::D2),
+      base_array_layer: face,
+      array_layer_count: NonZeroU32::new(1),
+      ..Default::default()
+    })).collect();
+    let view = texture.create_view(&TextureViewDescriptor {
+      dimension: Some(TextureViewDimension::Cube),
+      ..Default::default()
+    });
+    (Channel { view, sampler: create_sampler(device, Sampling::default()), dimension: TextureViewDimension::Cube }, faces)
+  }
+
+  // a texture that Horus keeps up to date itself
+  pub fn from_texture(device: &Device, texture: &Texture) -> Self {
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    Channel { view, sampler: create_sampler(device, Sampling::default()), dimension: TextureViewDimension::D2 }
+  }
+
+  pub fn view(&self) -> &TextureView {
+    &self.view
+  }
+
+  // a single transparent pixel for channels that weren't given an input
+  pub fn empty(device: &Device, queue: &Queue) -> Self {
+    Self::from_pixels(device, queue, 1, 1, TextureFormat::Rgba8Unorm, &[0; 4])
+  }
+
+  // a single transparent pixel on every face, for cubemap channels whose pass the project doesn't have
+  pub fn empty_cube(device: &Device, queue: &Queue) -> Self {
+    let texture = device.create_texture_with_data(queue, &TextureDescriptor {
+      label: Some("cube channel"),
+      size: Extent3d { width: 1, height: 1, depth_or_array_layers: 6 },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D2,
+      format: TextureFormat::Rgba8Unorm,
+      usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+    }, &[0; 4 * 6]);
+    let view = texture.create_view(&TextureViewDescriptor {
+      dimension: Some(TextureViewDimension::Cube),
+      ..Default::default()
+    });
+    Channel { view, sampler: create_sampler(device, Sampling::default()), dimension: TextureViewDimension::Cube }
+  }
+
+  fn from_pixels(device: &Device, queue: &Queue, width: u32, height: u32, format: TextureFormat, pixels: &[u8]) -> Self {
+    let texture = create_mipmapped(device, queue, "channel", Extent3d { width, height, depth_or_array_layers: 1 }, format, pixels);
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    Channel { view, sampler: create_sampler(device, Sampling::default()), dimension: TextureViewDimension::D2 }
+  }
+}
+
+// a texture with a full chain of mipmaps, rendered from `pixels`
+fn create_mipmapped(device: &Device, queue: &Queue, label: &str, size: Extent3d, format: TextureFormat, pixels: &[u8]) -> Texture {
+  let levels = mipmap::level_count(size.width, size.height);
+  let texture = device.create_texture(&TextureDescriptor {
+    label: Some(label),
+    size,
+    mip_level_count: levels,
+    sample_count: 1,
+    dimension: TextureDimension::D2,
+    format,
+    usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+  });
+  mipmap::upload(device, queue, &texture, format, size, levels, pixels);
+  texture
+}
+
+pub fn create_sampler(device: &Device, sampling: Sampling) -> Sampler {
+  let address_mode = match sampling.wrap {
+    Wrap::Repeat => AddressMode::Repeat,
+    Wrap::Clamp => AddressMode::ClampToEdge,
+    Wrap::Mirror => AddressMode::MirrorRepeat,
+  };
+  let filter = match sampling.filter {
+    Filter::Nearest => FilterMode::Nearest,
+    Filter::Linear | Filter::Mipmap => FilterMode::Linear,
+  };
+  device.create_sampler(&SamplerDescriptor {
+    label: Some("channel"),
+    address_mode_u: address_mode,
+    address_mode_v: address_mode,
+    address_mode_w: address_mode,
+    mag_filter: filter,
+    min_filter: filter,
+    mipmap_filter: FilterMode::Linear,
+    // everything but mipmap only ever reads the full size texture, as it does in webgl
+    lod_max_clamp: if sampling.filter == Filter::Mipmap { f32::MAX } else { 0. },
+    ..Default::default()
+  })
+}
+
+// project an equirectangular panorama onto six faces a quarter of its width across, looking down -Z at its center
+fn equirect_to_cube(equirect: &Rgba32FImage) -> Vec<Rgba32FImage> {
+  let size = (equirect.width() / 4).max(1);
+  (0..6).map(|face| Rgba32FImage::from_fn(size, size, |x, y| {
+    let u = 2. * (x as f32 + 0.5) / size as f32 - 1.;
+    let v = 2. * (y as f32 + 0.5) / size as f32 - 1.;
+    let [dx, dy, dz] = match face {
+      0 => [1., -v, -u],
+      1 => [-1., -v, u],
+      2 => [u, 1., v],
+      3 => [u, -1., -v],
+      4 => [u, -v, 1.],
+      _ => [-u, -v, -1.],
+    };
+    let length = (dx * dx + dy * dy + dz * dz).sqrt();
+    let longitude = 0.5 + dx.atan2(-dz) / (2. * PI);
+    let latitude = (dy / length).acos() / PI;
+    let sx = ((longitude * equirect.width() as f32) as u32).min(equirect.width() - 1);
+    let sy = ((latitude * equirect.height() as f32) as u32).min(equirect.height() - 1);
+    *equirect.get_pixel(sx, sy)
+  })).collect()
+}
+
+// whether `path` has the extension of an image that channels can load
+pub fn is_image(path: &Path) -> bool {
+  image::ImageFormat::from_path(path).is_ok()
+}
+
+// channel N's texture is bound to 2N and its sampler to 2N + 1
+pub fn create_bind_group_layout(device: &Device, dimensions: &[TextureViewDimension]) -> BindGroupLayout {
+  let entries: Vec<BindGroupLayoutEntry> = dimensions.iter().zip(0u32..).flat_map(|(dimension, index)| [
+    BindGroupLayoutEntry {
+      binding: index * 2,
+      visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+      count: None,
+      ty: BindingType::Texture {
+        sample_type: TextureSampleType::Float { filterable: true },
+        view_dimension: *dimension,
+        multisampled: false,
+      },
+    },
+    BindGroupLayoutEntry {
+      binding: index * 2 + 1,
+      visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+      count: None,
+      ty: BindingType::Sampler(SamplerBindingType::Filtering),
+    },
+  ]).collect();
+  device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+    label: Some("channels"),
+    entries: &entries,
+  })
+}
+
+// each channel is read with its own sampler, unless `samplers` has one for it
+pub fn create_bind_group(device: &Device, layout: &BindGroupLayout, channels: &[&Channel], samplers: Option<&[Sampler]>) -> BindGroup {
+  let entries: Vec<BindGroupEntry> = channels.iter().zip(0u32..).flat_map(|(channel, index)| [
+    BindGroupEntry {
+      binding: index * 2,
+      resource: BindingResource::TextureView(&channel.view),
+    },
+    BindGroupEntry {
+      binding: index * 2 + 1,
+      resource: BindingResource::Sampler(samplers.and_then(|samplers| samplers.get(index as usize)).unwrap_or(&channel.sampler)),
+    },
+  ]).collect();
+  device.create_bind_group(&BindGroupDescriptor {
+    label: Some("channels"),
+    layout,
+    entries: &entries,
+  })
+}
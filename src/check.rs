@@ -0,0 +1,28 @@
+// `horus check` parses and validates a shader along with its buffers, common code and custom uniforms with naga, without a window or
+// even a GPU, so editors, pre-commit hooks and CI can catch errors anywhere
+
+use std::path::Path;
+use wgpu::TextureViewDimension;
+
+use crate::{channel, cli::Options, pass::{self, Pass}, render, volume};
+
+pub fn check(options: Options) -> Result<(), String> {
+  let Some(path) = &options.fragment_path else {
+    return Err("Pass in the shader to check".to_string());
+  };
+  let mut image = Pass::load(path.clone(), options.shadertoy, render::FORMAT).map_err(|error| format!("Unable to read {}: {}", path.display(), error))?;
+  image.entry = options.entry.clone();
+  // channels are only ever cubemaps when they're asked to be or read the cubemap pass, and volumes when they're read from one, since
+  // everything else built in is 2D
+  let channels: Vec<TextureViewDimension> = options.channels().iter().map(|channel| match &channel.path {
+    Some(path) if channel.cube || path == Path::new(pass::CUBEMAP_NAME) => TextureViewDimension::Cube,
+    Some(path) if !channel::is_built_in(path.to_str().unwrap_or_default()) && volume::is_volume(path) => TextureViewDimension::D3,
+    _ => TextureViewDimension::D2,
+  }).collect();
+  // every error has already been printed by the time the passes are checked
+  if pass::check(image, &options.sources(), &channels) > 0 {
+    return Err(format!("{} doesn't compile", path.display()));
+  }
+  println!("[Horus] {} compiles", path.display());
+  Ok(())
+}
@@ -0,0 +1,471 @@
+// the command line, where running a shader is the default so `horus shader.wgsl` is short for `horus run shader.wgsl`
+
+use std::{net::IpAddr, path::PathBuf};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+use wgpu::{Backends, PowerPreference, PresentMode};
+
+use crate::{channel::{Sampling, CHANNEL_COUNT}, golden::TestOptions, pass::{Sources, BUFFER_NAMES}, render::RenderOptions, shader::Define, template::Template};
+
+#[derive(Parser, Debug)]
+#[command(name = "horus", version, about = "Runs WGSL, GLSL and Shadertoy fragment shaders, reloading them whenever they're saved")]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Cli {
+  #[command(subcommand)]
+  pub command: Option<Command>,
+  #[command(flatten)]
+  pub options: Options,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+  #[command(about = "Run a shader in a window")]
+  Run(Options),
+  // `horus -c name` is how shaders used to be created, so it still works
+  #[command(short_flag = 'c', about = "Create a project directory with a starter shader, a horus.toml, an assets folder and a .gitignore")]
+  New {
+    #[arg(default_value = "fragment", help = "The name of the project's directory, or of the shader with --shader-only")]
+    name: PathBuf,
+    #[arg(long, help = "Only create the shader, which gets a .wgsl extension if it doesn't have one")]
+    shader_only: bool,
+    #[arg(long, value_enum, default_value_t, help = "The shader to start from")]
+    template: Template,
+  },
+  #[command(about = "Download a shader from Shadertoy into a project directory, along with its buffers, common code and textures")]
+  Import {
+    #[arg(value_name = "SHADER", help = "The shader's id or the address of its page, like https://www.shadertoy.com/view/XsXXDn")]
+    shader: String,
+    #[arg(help = "The project's directory [default: the shader's name]")]
+    name: Option<PathBuf>,
+    #[arg(long, help = "A Shadertoy API key from https://www.shadertoy.com/myapps [default: $SHADERTOY_API_KEY]")]
+    key: Option<String>,
+  },
+  #[command(about = "Build an executable that runs a shader or project by itself, with every file it reads inside of it")]
+  Bundle {
+    #[arg(value_name = "SHADER", help = "The shader to bundle, or a project directory with a horus.toml in it")]
+    path: PathBuf,
+    #[arg(short, long, value_name = "PATH", help = "Where to write the executable")]
+    output: PathBuf,
+  },
+  #[command(about = "Render frames into PNGs without opening a window")]
+  Render {
+    #[command(flatten)]
+    options: Options,
+    #[command(flatten)]
+    render: RenderOptions,
+  },
+  #[command(about = "Compile a shader along with its buffers and common code, exiting with an error if any of them don't compile")]
+  Check(Options),
+  #[command(about = "Render shaders up to a frame and compare them against their reference PNGs, exiting with an error if any of them differ")]
+  Test(TestOptions),
+}
+
+// what to load into one of the channels
+#[derive(Debug, Default)]
+pub struct ChannelOptions {
+  pub path: Option<PathBuf>,
+  // load the path as a cubemap instead of a 2D texture
+  pub cube: bool,
+  pub sampling: Sampling,
+  // load images and volumes the other way up from how they'd be loaded otherwise
+  pub flip: bool,
+  // read images and volumes exactly as they're stored, without decoding them from sRGB
+  pub linear: bool,
+}
+
+// what a channel reads past its edges, like shadertoy's wrap setting
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Wrap {
+  #[default]
+  #[value(help = "Tiles the texture, which suits noise and patterns")]
+  Repeat,
+  #[value(help = "Stretches the pixels along its edges outwards")]
+  Clamp,
+  #[value(help = "Tiles the texture flipped back and forth, so its edges always meet")]
+  Mirror,
+}
+
+// how a channel blends its pixels together, like shadertoy's filter setting
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Filter {
+  #[value(help = "Reads the closest pixel, which keeps pixel art and lookup tables exact")]
+  Nearest,
+  #[value(help = "Blends the four closest pixels")]
+  Linear,
+  #[default]
+  #[value(help = "Blends the two mipmaps closest to the size the texture is drawn at as well, so it doesn't shimmer when it's drawn small")]
+  Mipmap,
+}
+
+// how finished frames are handed to the display
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Present {
+  #[default]
+  #[value(help = "Wait for the display to refresh before showing each frame, which is vsync")]
+  Fifo,
+  #[value(help = "Replace the frame waiting to be shown with each new one, which doesn't hold back the frame rate or tear")]
+  Mailbox,
+  #[value(help = "Show every frame as soon as it's finished, which doesn't hold back the frame rate but can tear")]
+  Immediate,
+}
+
+impl Present {
+  pub fn mode(self) -> PresentMode {
+    match self {
+      Present::Fifo => PresentMode::Fifo,
+      Present::Mailbox => PresentMode::Mailbox,
+      Present::Immediate => PresentMode::Immediate,
+    }
+  }
+}
+
+// what --projection warps the cubemap the image is rendered into
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Projection {
+  #[value(help = "A 2:1 panorama of every direction, for 360° video and spherical projection")]
+  Equirect,
+  #[value(help = "A domemaster, the circular fisheye of the top half that planetarium projectors and dome players take")]
+  Dome,
+}
+
+// how --hdr brings the colors the image renders into the range the window shows
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Tonemap {
+  #[default]
+  #[value(help = "The filmic curve most games use, which desaturates the brightest colors towards white")]
+  Aces,
+  #[value(help = "Divides each color by one more than itself, which never quite reaches white")]
+  Reinhard,
+  #[value(help = "Cuts colors off at 1, like rendering straight into the window does")]
+  Clamp,
+}
+
+// the graphics API wgpu runs on, for when the one it picks has driver bugs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+  Vulkan,
+  Metal,
+  Dx12,
+  Gl,
+}
+
+impl Backend {
+  pub fn backends(self) -> Backends {
+    match self {
+      Backend::Vulkan => Backends::VULKAN,
+      Backend::Metal => Backends::METAL,
+      Backend::Dx12 => Backends::DX12,
+      Backend::Gl => Backends::GL,
+    }
+  }
+}
+
+// options for running a shader, which rendering and checking share
+#[derive(Args, Debug, Default)]
+pub struct Options {
+  #[arg(value_name = "SHADER", help = "The shader to run, a project directory with a horus.toml in it, or any other directory to cycle through every shader in it")]
+  pub fragment_path: Option<PathBuf>,
+  #[arg(long, help = "Treat the shader as Shadertoy code, even if it doesn't look like it")]
+  pub shadertoy: bool,
+  #[arg(long = "define", value_name = "NAME[=VALUE]", value_parser = parse_define, help = "Declare a constant before the shader's code, like QUALITY=3, or USE_AO by itself for true, so one shader can build several variants")]
+  pub defines: Vec<Define>,
+  #[arg(long = "modules", value_name = "DIRECTORY", help = "A directory of WGSL modules that shaders can #import, which is looked through along with every directory inside of it")]
+  pub modules: Vec<PathBuf>,
+  #[arg(long, value_name = "FUNCTION", help = "The fragment shader function the image starts at, so one file can hold several of them [default: fs_main, or main in HLSL]")]
+  pub entry: Option<String>,
+  #[arg(long, value_name = "PATH", help = "A WGSL vertex shader with a vs_main to draw the image with, instead of a triangle that covers the whole window")]
+  pub vertex: Option<PathBuf>,
+  #[arg(long, value_name = "COUNT", value_parser = clap::value_parser!(u32).range(1..), help = "How many vertices the --vertex shader is run for [default: 3]")]
+  pub vertices: Option<u32>,
+  #[arg(long, value_name = "PATH", help = "An image, a volume, a directory of cubemap faces or slices, bufferA to bufferD, cubemapA, or keyboard, microphone, audio, webcam, gamepad, font or a noise texture, bound as iChannel0")]
+  pub channel0: Option<PathBuf>,
+  #[arg(long, value_name = "PATH", help = "Bound as iChannel1, the same as --channel0")]
+  pub channel1: Option<PathBuf>,
+  #[arg(long, value_name = "PATH", help = "Bound as iChannel2, the same as --channel0")]
+  pub channel2: Option<PathBuf>,
+  #[arg(long, value_name = "PATH", help = "Bound as iChannel3, the same as --channel0")]
+  pub channel3: Option<PathBuf>,
+  #[arg(long, help = "Load iChannel0 as a cubemap")]
+  pub channel0_cube: bool,
+  #[arg(long, help = "Load iChannel1 as a cubemap")]
+  pub channel1_cube: bool,
+  #[arg(long, help = "Load iChannel2 as a cubemap")]
+  pub channel2_cube: bool,
+  #[arg(long, help = "Load iChannel3 as a cubemap")]
+  pub channel3_cube: bool,
+  #[arg(long, help = "Flip iChannel0 upside down from how it's loaded otherwise, which is already flipped in Shadertoy mode")]
+  pub channel0_flip: bool,
+  #[arg(long, help = "Flip iChannel1 upside down, the same as --channel0-flip")]
+  pub channel1_flip: bool,
+  #[arg(long, help = "Flip iChannel2 upside down, the same as --channel0-flip")]
+  pub channel2_flip: bool,
+  #[arg(long, help = "Flip iChannel3 upside down, the same as --channel0-flip")]
+  pub channel3_flip: bool,
+  #[arg(long, help = "Read iChannel0's colors exactly as they're stored, like Shadertoy mode always does, instead of decoding them from sRGB")]
+  pub channel0_linear: bool,
+  #[arg(long, help = "Read iChannel1's colors as they're stored, the same as --channel0-linear")]
+  pub channel1_linear: bool,
+  #[arg(long, help = "Read iChannel2's colors as they're stored, the same as --channel0-linear")]
+  pub channel2_linear: bool,
+  #[arg(long, help = "Read iChannel3's colors as they're stored, the same as --channel0-linear")]
+  pub channel3_linear: bool,
+  #[arg(long, value_enum, value_name = "MODE", help = "How iChannel0 is read past its edges [default: repeat]")]
+  pub channel0_wrap: Option<Wrap>,
+  #[arg(long, value_enum, value_name = "MODE", help = "How iChannel0 is read between its pixels, where nearest keeps pixel art sharp [default: mipmap]")]
+  pub channel0_filter: Option<Filter>,
+  #[arg(long, value_enum, value_name = "MODE", help = "How iChannel1 is read past its edges, the same as --channel0-wrap")]
+  pub channel1_wrap: Option<Wrap>,
+  #[arg(long, value_enum, value_name = "MODE", help = "How iChannel1 is read between its pixels, the same as --channel0-filter")]
+  pub channel1_filter: Option<Filter>,
+  #[arg(long, value_enum, value_name = "MODE", help = "How iChannel2 is read past its edges, the same as --channel0-wrap")]
+  pub channel2_wrap: Option<Wrap>,
+  #[arg(long, value_enum, value_name = "MODE", help = "How iChannel2 is read between its pixels, the same as --channel0-filter")]
+  pub channel2_filter: Option<Filter>,
+  #[arg(long, value_enum, value_name = "MODE", help = "How iChannel3 is read past its edges, the same as --channel0-wrap")]
+  pub channel3_wrap: Option<Wrap>,
+  #[arg(long, value_enum, value_name = "MODE", help = "How iChannel3 is read between its pixels, the same as --channel0-filter")]
+  pub channel3_filter: Option<Filter>,
+  #[arg(long, help = "Show a checkerboard in place of images that can't be loaded")]
+  pub placeholder: bool,
+  #[arg(long, value_name = "PATH", help = "Record every frame into a video with ffmpeg, a GIF, or numbered PNGs in a directory if there's no extension")]
+  pub record: Option<PathBuf>,
+  #[arg(long, value_name = "PATH", conflicts_with = "record", help = "Record a looping GIF, whatever the extension")]
+  pub record_gif: Option<PathBuf>,
+  #[arg(long, value_parser = parse_duration, help = "Stop recording after this many seconds")]
+  pub seconds: Option<f32>,
+  #[arg(long, value_name = "SECONDS", value_parser = parse_duration, help = "Close the window this many seconds after it opens, or have `horus render` render this many seconds of frames")]
+  pub duration: Option<f32>,
+  #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_size, help = "The window's size in logical pixels, like 1280x720 [default: 700x700]")]
+  pub size: Option<(u32, u32)>,
+  #[arg(long, help = "The window's title [default: Horus]")]
+  pub title: Option<String>,
+  #[arg(long, help = "Start in borderless fullscreen, which F11 or a double click toggles while the shader runs")]
+  pub fullscreen: bool,
+  #[arg(long, conflicts_with = "span", help = "The index or part of the name of the monitor to open on, which --fullscreen fills")]
+  pub monitor: Option<String>,
+  #[arg(long, conflicts_with_all = ["fullscreen", "size"], help = "Cover every monitor with a single borderless window, so the shader's resolution runs across all of them")]
+  pub span: bool,
+  #[arg(long, conflicts_with_all = ["fullscreen", "size"], help = "Run the shader as the desktop's wallpaper, behind its icons, on --monitor or the primary monitor")]
+  pub wallpaper: bool,
+  #[arg(long, help = "Fill the screen without a cursor and exit as soon as the mouse or keyboard is touched, like a screensaver")]
+  pub screensaver: bool,
+  // the window Windows previews a screensaver inside of, which it passes with /p
+  #[arg(long, value_name = "WINDOW", requires = "screensaver", hide = true)]
+  pub preview: Option<isize>,
+  #[arg(long, help = "Keep the window above every other window")]
+  pub on_top: bool,
+  #[arg(long, help = "Leave out the window's title bar and border")]
+  pub borderless: bool,
+  #[arg(long, help = "Show the desktop through the window wherever the shader's alpha is below 1, with its colors premultiplied by alpha")]
+  pub transparent: bool,
+  #[arg(long, help = "Keep the shader's alpha in screenshots, which are otherwise as opaque as the window, and render over transparency instead of black")]
+  pub capture_alpha: bool,
+  #[arg(long, value_enum, help = "The graphics API to run on instead of the one that's picked automatically")]
+  pub backend: Option<Backend>,
+  #[arg(long, help = "The index or part of the name of the GPU to run on, which lists every GPU if it can't be found")]
+  pub gpu: Option<String>,
+  #[arg(long, conflicts_with = "high_performance", help = "Prefer an integrated GPU, which saves battery")]
+  pub low_power: bool,
+  #[arg(long, help = "Prefer a discrete GPU, which is usually faster")]
+  pub high_performance: bool,
+  #[arg(long, conflicts_with = "linear", help = "Show the colors the shader writes as linear light, which is encoded into sRGB for the display [default]")]
+  pub srgb: bool,
+  #[arg(long, help = "Show the values the shader writes as they are, like WebGL and Shadertoy do, for shaders that apply their own gamma")]
+  pub linear: bool,
+  #[arg(long, value_enum, help = "How frames are shown, where anything but fifo lets the frame rate go past the display's for benchmarking [default: fifo]")]
+  pub present: Option<Present>,
+  #[arg(long, value_name = "FPS", value_parser = clap::value_parser!(u32).range(1..), help = "Draw at most this many frames a second, which keeps shaders that don't need to run fast from spinning up the fans")]
+  pub max_fps: Option<u32>,
+  #[arg(long = "static", help = "Only draw a new frame when the window, the mouse, the keys or a uniform change, for shaders that don't animate")]
+  pub still: bool,
+  #[arg(long, value_name = "FACTOR", value_parser = parse_scale, help = "Render the shader at this multiple of the window's resolution, like 0.5 for heavy shaders or 2 to supersample [default: 1]")]
+  pub scale: Option<f32>,
+  #[arg(long, value_name = "SAMPLES", value_parser = parse_samples, help = "Render the image with this many samples per pixel, which smooths the edges of shadertoy images, and of WGSL and GLSL that read @builtin(sample_index) or gl_SampleID, on every backend but GL. --scale 2 smooths any shader [default: 1]")]
+  pub msaa: Option<u32>,
+  #[arg(long, value_enum, help = "Render the image into a cubemap around the viewer and warp it into an equirectangular panorama or a domemaster for fulldome projection")]
+  pub projection: Option<Projection>,
+  #[arg(long, conflicts_with = "capture_alpha", help = "Render the image into half floats, which keep colors brighter than 1, and tonemap them into the window")]
+  pub hdr: bool,
+  #[arg(long, value_enum, conflicts_with = "capture_alpha", help = "How --hdr brings bright colors into the window's range, which turns on --hdr by itself [default: aces]")]
+  pub tonemap: Option<Tonemap>,
+  #[arg(long, value_name = "MULTIPLIER", value_parser = parse_speed, help = "How fast time passes, like 0.25 for slow motion, which [ and ] halve and double while the shader runs [default: 1]")]
+  pub speed: Option<f32>,
+  #[arg(long, value_name = "SECONDS", value_parser = parse_scrub_step, help = "How far the left and right arrow keys move time, which is ten times as far with Shift held [default: 0.1]")]
+  pub scrub_step: Option<f32>,
+  #[arg(long, help = "Advance time by exactly 1/60th of a second every frame, starting from midnight on January 1st 2000, so every run renders the same frames")]
+  pub deterministic: bool,
+  #[arg(long, help = "The seed the built in noise textures are generated from, and the seed uniform is picked by, which N picks again at random [default: 0, or a random one for the uniform in the window]")]
+  pub seed: Option<u64>,
+  #[arg(long, value_name = "PATH", help = "Save the mouse and keys the shader sees on every frame, along with when it saw them, which --replay-input plays back")]
+  pub record_input: Option<PathBuf>,
+  #[arg(long, value_name = "PATH", conflicts_with = "record_input", help = "Play back the mouse and keys saved with --record-input, in place of the real ones")]
+  pub replay_input: Option<PathBuf>,
+  #[arg(long, value_name = "SECONDS", value_parser = parse_cycle, help = "How long each shader runs for when Horus is given a directory of them, which Page Up and Page Down skip through [default: 30]")]
+  pub cycle: Option<f32>,
+  #[arg(long, help = "Run the shaders of a directory in a random order, starting from a random one")]
+  pub shuffle: bool,
+  #[arg(long, value_name = "PATH", help = "Play a song along with the shader, which channels read by being passed audio")]
+  pub audio: Option<PathBuf>,
+  #[arg(long, value_name = "NAME", help = "Send every frame over the network as an NDI source with this name, which needs the NDI runtime")]
+  pub ndi: Option<String>,
+  #[arg(long, value_name = "NAME", help = "Share every frame with other programs on Windows as a Spout sender with this name")]
+  pub spout: Option<String>,
+  #[arg(long, value_name = "NAME", help = "Share every frame with other programs on macOS as a Syphon server with this name, which needs Syphon.framework")]
+  pub syphon: Option<String>,
+  #[arg(long, value_name = "PATH", help = "Move the custom uniforms between keyframes in a toml or json file as time passes [default: a .timeline.toml next to the shader, if there is one]")]
+  pub timeline: Option<PathBuf>,
+  #[arg(long, value_name = "PORT", help = "Listen for OSC messages like /horus/uniform/speed on a UDP port")]
+  pub osc: Option<u16>,
+  #[arg(long, value_name = "PORT", help = "Take JSON commands like {\"command\": \"pause\"} from WebSockets on a TCP port")]
+  pub remote: Option<u16>,
+  #[arg(long, value_name = "ADDRESS", help = "Listen for WebSocket commands on this address, like 0.0.0.0 so phones on the same network can connect [default: 127.0.0.1]")]
+  pub remote_host: Option<IpAddr>,
+  #[arg(long, help = "Take a command like \"uniform speed 2\", \"time 10\" or \"reload\" from each line of standard input")]
+  pub stdin: bool,
+  // files that horus.toml moved away from the names they'd have next to the shader
+  #[arg(skip)]
+  pub buffers: [Option<PathBuf>; BUFFER_NAMES.len()],
+  #[arg(skip)]
+  pub common: Option<PathBuf>,
+  #[arg(skip)]
+  pub cubemap: Option<PathBuf>,
+  #[arg(skip)]
+  pub sound: Option<PathBuf>,
+  #[arg(skip)]
+  pub uniforms: Option<PathBuf>,
+}
+
+impl Options {
+  // where the passes are read from and what's declared in front of them
+  pub fn sources(&self) -> Sources {
+    Sources { shadertoy: self.shadertoy, buffers: self.buffers.clone(), common: self.common.clone(), cubemap: self.cubemap.clone(), sound: self.sound.clone(), uniforms: self.uniforms.clone(), timeline: self.timeline.clone(), defines: self.defines.clone(), modules: self.modules.clone(), vertex: self.vertex.clone(), vertices: self.vertices }
+  }
+
+  pub fn channels(&self) -> [ChannelOptions; CHANNEL_COUNT] {
+    let paths = [&self.channel0, &self.channel1, &self.channel2, &self.channel3];
+    let cubes = [self.channel0_cube, self.channel1_cube, self.channel2_cube, self.channel3_cube];
+    let wraps = [self.channel0_wrap, self.channel1_wrap, self.channel2_wrap, self.channel3_wrap];
+    let filters = [self.channel0_filter, self.channel1_filter, self.channel2_filter, self.channel3_filter];
+    let flips = [self.channel0_flip, self.channel1_flip, self.channel2_flip, self.channel3_flip];
+    let linears = [self.channel0_linear, self.channel1_linear, self.channel2_linear, self.channel3_linear];
+    std::array::from_fn(|index| ChannelOptions {
+      path: paths[index].clone(),
+      cube: cubes[index],
+      sampling: Sampling { wrap: wraps[index].unwrap_or_default(), filter: filters[index].unwrap_or_default() },
+      flip: flips[index],
+      linear: linears[index],
+    })
+  }
+
+  // what each channel was given, which is either a path or the name of something built in
+  pub fn channel_names(&self) -> Vec<&str> {
+    [&self.channel0, &self.channel1, &self.channel2, &self.channel3].into_iter().filter_map(|path| path.as_deref()?.to_str()).collect()
+  }
+
+  // the graphics APIs an adapter can be picked from
+  pub fn backends(&self) -> Backends {
+    self.backend.map_or(Backends::all(), Backend::backends)
+  }
+
+  // what the seed uniform is picked by in the window, which is random unless it's given or every run has to render the same frames
+  pub fn uniform_seed(&self) -> u64 {
+    self.seed.unwrap_or_else(|| if self.deterministic { 0 } else { crate::random_seed() })
+  }
+
+  // how the image is tonemapped, if it renders in HDR at all
+  pub fn tonemap(&self) -> Option<Tonemap> {
+    (self.hdr || self.tonemap.is_some()).then(|| self.tonemap.unwrap_or_default())
+  }
+
+  // which kind of GPU wgpu picks when --gpu doesn't say which one
+  pub fn power_preference(&self) -> PowerPreference {
+    match (self.low_power, self.high_performance) {
+      (true, _) => PowerPreference::LowPower,
+      (_, true) => PowerPreference::HighPerformance,
+      _ => PowerPreference::default(),
+    }
+  }
+
+  // a video, GIF or directory that every frame is recorded into
+  pub fn record(&self) -> Option<PathBuf> {
+    self.record.clone().or_else(|| self.record_gif.as_ref().map(|path| path.with_extension("gif")))
+  }
+}
+
+// speeds have to be positive, since scrubbing is how time goes backwards
+fn parse_speed(value: &str) -> Result<f32, String> {
+  match value.parse::<f32>() {
+    Ok(speed) if speed > 0. && speed.is_finite() => Ok(speed),
+    Ok(_) => Err("the speed has to be greater than 0".to_string()),
+    Err(error) => Err(error.to_string()),
+  }
+}
+
+// a step that isn't positive would swap the arrow keys around, or leave time stuck at NaN
+fn parse_scrub_step(value: &str) -> Result<f32, String> {
+  match value.parse::<f32>() {
+    Ok(step) if step > 0. && step.is_finite() => Ok(step),
+    Ok(_) => Err("the scrub step has to be greater than 0".to_string()),
+    Err(error) => Err(error.to_string()),
+  }
+}
+
+// each shader in a directory has to run for some amount of time before the next one
+fn parse_cycle(value: &str) -> Result<f32, String> {
+  match value.parse::<f32>() {
+    Ok(seconds) if seconds > 0. && seconds.is_finite() => Ok(seconds),
+    Ok(_) => Err("the cycle has to be longer than 0 seconds".to_string()),
+    Err(error) => Err(error.to_string()),
+  }
+}
+
+// closing the window as soon as it opens would leave nothing to see
+fn parse_duration(value: &str) -> Result<f32, String> {
+  match value.parse::<f32>() {
+    Ok(seconds) if seconds > 0. && seconds.is_finite() => Ok(seconds),
+    Ok(_) => Err("the duration has to be longer than 0 seconds".to_string()),
+    Err(error) => Err(error.to_string()),
+  }
+}
+
+// the shader needs at least some pixels to render into
+fn parse_scale(value: &str) -> Result<f32, String> {
+  match value.parse::<f32>() {
+    Ok(scale) if scale > 0. && scale.is_finite() => Ok(scale),
+    Ok(_) => Err("the scale has to be greater than 0".to_string()),
+    Err(error) => Err(error.to_string()),
+  }
+}
+
+// render passes can only have 1 or 4 samples per pixel
+fn parse_samples(value: &str) -> Result<u32, String> {
+  match value.parse::<u32>() {
+    Ok(samples) if samples == 1 || samples == 4 => Ok(samples),
+    Ok(_) => Err("the samples have to be 1 or 4".to_string()),
+    Err(error) => Err(error.to_string()),
+  }
+}
+
+// a define like `QUALITY=3`, or a name by itself
+fn parse_define(value: &str) -> Result<Define, String> {
+  match value.split_once('=') {
+    Some((name, value)) => Define::new(name.trim(), Some(value.trim().to_string())),
+    None => Define::new(value.trim(), None),
+  }
+}
+
+// a size like `1280x720`, where neither side can be 0
+pub fn parse_size(value: &str) -> Result<(u32, u32), String> {
+  let (width, height) = value.split_once(['x', 'X']).ok_or("the size has to be a width and a height like 1280x720")?;
+  match (width.trim().parse(), height.trim().parse()) {
+    (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+    (Ok(_), Ok(_)) => Err("the width and height have to be greater than 0".to_string()),
+    _ => Err("the size has to be a width and a height like 1280x720".to_string()),
+  }
+}
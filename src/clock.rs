@@ -0,0 +1,80 @@
+// the shader's time, which only counts the time that passes while it isn't paused
+
+use std::time::Instant;
+use chrono::{DateTime, Local, TimeZone};
+
+// how far time moves when stepping a single frame while paused, and every frame with --deterministic, which matches recordings
+pub const FRAME_STEP: f32 = 1. / 60.;
+
+// the date --deterministic runs and `horus test` start from, so shaders that read it render the same frames every time
+pub fn fixed_start() -> DateTime<Local> {
+  Local.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap()
+}
+
+pub struct Clock {
+  time: f32,
+  paused: bool,
+  // how many seconds of the shader's time pass for every real one
+  speed: f32,
+  last_tick: Instant,
+  // the first frame after starting or restarting is always at 0
+  ticked: bool,
+}
+
+impl Default for Clock {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Clock {
+  pub fn new() -> Self {
+    Clock { time: 0., paused: false, speed: 1., last_tick: Instant::now(), ticked: false }
+  }
+
+  // advance by `step`, or by however long it's been since the last frame, and return the time for this frame
+  pub fn tick(&mut self, step: Option<f32>) -> f32 {
+    let now = Instant::now();
+    if self.ticked && !self.paused {
+      self.time += step.unwrap_or((now - self.last_tick).as_secs_f32()) * self.speed;
+    }
+    self.last_tick = now;
+    self.ticked = true;
+    self.time
+  }
+
+  // jump to `time`, such as wherever the audio has got to, and carry on from there
+  pub fn sync(&mut self, time: f32) -> f32 {
+    self.time = time;
+    self.last_tick = Instant::now();
+    self.ticked = true;
+    time
+  }
+
+  // move `seconds` forwards or backwards, though never to before the start, returning the new time
+  pub fn seek(&mut self, seconds: f32) -> f32 {
+    self.time = (self.time + seconds).max(0.);
+    self.time
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  pub fn set_paused(&mut self, paused: bool) {
+    self.paused = paused;
+  }
+
+  pub fn speed(&self) -> f32 {
+    self.speed
+  }
+
+  pub fn set_speed(&mut self, speed: f32) {
+    self.speed = speed;
+  }
+
+  pub fn restart(&mut self) {
+    self.time = 0.;
+    self.ticked = false;
+  }
+}
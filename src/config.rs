@@ -0,0 +1,382 @@
+// an optional horus.toml next to the shader that holds the options a project always runs with, so they don't need to be typed every time
+//
+// shader = "main.wgsl"
+// channel0 = "noise-rgba-256"
+// channel1 = { path = "sky", cube = true }
+//
+// [window]
+// width = 1280
+// height = 720
+//
+// [render]
+// frames = 600
+//
+// [define]
+// QUALITY = 3
+
+use std::{collections::BTreeMap, fs::read_to_string, net::IpAddr, path::{Path, PathBuf}};
+use serde::Deserialize;
+
+use crate::{channel, cli::{Backend, Filter, Options, Present, Projection, Tonemap, Wrap}, render::{FrameFormat, RenderOptions}, shader::Define};
+
+pub const NAME: &str = "horus.toml";
+
+// a channel is either a path or a table with the same options as the command line
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChannelConfig {
+  Path(PathBuf),
+  Table {
+    path: PathBuf,
+    #[serde(default)]
+    cube: bool,
+    wrap: Option<Wrap>,
+    filter: Option<Filter>,
+    #[serde(default)]
+    flip: bool,
+    #[serde(default)]
+    linear: bool,
+  },
+}
+
+// the window's size is in logical pixels, before the display's scale factor is applied
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WindowConfig {
+  width: Option<u32>,
+  height: Option<u32>,
+  title: Option<String>,
+}
+
+// where each pass is found when it isn't next to the shader under the usual name
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PassesConfig {
+  #[serde(rename = "bufferA")]
+  buffer_a: Option<PathBuf>,
+  #[serde(rename = "bufferB")]
+  buffer_b: Option<PathBuf>,
+  #[serde(rename = "bufferC")]
+  buffer_c: Option<PathBuf>,
+  #[serde(rename = "bufferD")]
+  buffer_d: Option<PathBuf>,
+  common: Option<PathBuf>,
+  #[serde(rename = "cubemapA")]
+  cubemap_a: Option<PathBuf>,
+  sound: Option<PathBuf>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RenderConfig {
+  width: Option<u32>,
+  height: Option<u32>,
+  frames: Option<u32>,
+  fps: Option<u32>,
+  output: Option<PathBuf>,
+  tile: Option<u32>,
+  format: Option<FrameFormat>,
+}
+
+// every key but the tables is named after the command line option it stands in for
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+  // the directory horus.toml was found in, which every path in it is relative to
+  #[serde(skip)]
+  directory: PathBuf,
+  // the shader to run when Horus is given the project's directory instead of a shader
+  shader: Option<PathBuf>,
+  #[serde(default)]
+  shadertoy: bool,
+  // directories of WGSL modules, which are looked through after any passed with --modules
+  #[serde(default)]
+  modules: Vec<PathBuf>,
+  entry: Option<String>,
+  vertex: Option<PathBuf>,
+  vertices: Option<u32>,
+  channel0: Option<ChannelConfig>,
+  channel1: Option<ChannelConfig>,
+  channel2: Option<ChannelConfig>,
+  channel3: Option<ChannelConfig>,
+  #[serde(default)]
+  placeholder: bool,
+  #[serde(default)]
+  fullscreen: bool,
+  monitor: Option<String>,
+  #[serde(default)]
+  span: bool,
+  #[serde(default)]
+  wallpaper: bool,
+  #[serde(default)]
+  screensaver: bool,
+  #[serde(default)]
+  on_top: bool,
+  #[serde(default)]
+  borderless: bool,
+  #[serde(default)]
+  transparent: bool,
+  #[serde(default)]
+  capture_alpha: bool,
+  backend: Option<Backend>,
+  gpu: Option<String>,
+  #[serde(default)]
+  low_power: bool,
+  #[serde(default)]
+  high_performance: bool,
+  #[serde(default)]
+  srgb: bool,
+  #[serde(default)]
+  linear: bool,
+  present: Option<Present>,
+  max_fps: Option<u32>,
+  #[serde(default, rename = "static")]
+  still: bool,
+  scale: Option<f32>,
+  msaa: Option<u32>,
+  projection: Option<Projection>,
+  #[serde(default)]
+  hdr: bool,
+  tonemap: Option<Tonemap>,
+  record: Option<PathBuf>,
+  seconds: Option<f32>,
+  duration: Option<f32>,
+  speed: Option<f32>,
+  scrub_step: Option<f32>,
+  #[serde(default)]
+  deterministic: bool,
+  seed: Option<u64>,
+  record_input: Option<PathBuf>,
+  replay_input: Option<PathBuf>,
+  cycle: Option<f32>,
+  #[serde(default)]
+  shuffle: bool,
+  audio: Option<PathBuf>,
+  ndi: Option<String>,
+  spout: Option<String>,
+  syphon: Option<String>,
+  timeline: Option<PathBuf>,
+  osc: Option<u16>,
+  remote: Option<u16>,
+  remote_host: Option<IpAddr>,
+  #[serde(default)]
+  stdin: bool,
+  #[serde(default)]
+  window: WindowConfig,
+  #[serde(default)]
+  passes: PassesConfig,
+  #[serde(default)]
+  render: RenderConfig,
+  // the same as --define, with a number, true, false or a string of code for each name
+  #[serde(default)]
+  define: BTreeMap<String, toml::Value>,
+  // custom uniforms can be declared here instead of next to the shader, which params.rs reads for itself
+  #[serde(default)]
+  uniform: Vec<toml::Value>,
+}
+
+impl Config {
+  // find the horus.toml for `options` and fill in anything that wasn't passed on the command line
+  pub fn apply(options: &mut Options) -> Result<Option<Self>, String> {
+    // the shader can also be a project's directory, which is where Horus looks when it isn't given one at all
+    let directory = match &options.fragment_path {
+      Some(path) if path.is_dir() => path.clone(),
+      Some(path) => path.parent().unwrap_or(Path::new("")).to_path_buf(),
+      None => PathBuf::new(),
+    };
+    let path = directory.join(NAME);
+    // any other directory is a playlist of the shaders in it
+    if !path.is_file() {
+      return Ok(None);
+    }
+    // horus.toml says which of the project's shaders to run
+    if options.fragment_path.as_ref().is_some_and(|shader| shader.is_dir()) {
+      options.fragment_path = None;
+    }
+    let source = read_to_string(&path).map_err(|error| format!("Unable to read {}: {}", path.display(), error))?;
+    let mut config: Config = toml::from_str(&source).map_err(|error| format!("Unable to load {}\nerror: {}", path.display(), error))?;
+    println!("[Horus] Using {}", path.display());
+    config.directory = directory;
+
+    if options.fragment_path.is_none() {
+      options.fragment_path = Some(config.resolve(config.shader.as_deref().ok_or(format!("{} doesn't say which shader to run", path.display()))?));
+    }
+    options.shadertoy |= config.shadertoy;
+    options.modules.extend(config.modules.iter().map(|directory| config.resolve(directory)));
+    options.entry = options.entry.take().or(config.entry.take());
+    options.vertex = options.vertex.take().or_else(|| config.vertex.as_deref().map(|path| config.resolve(path)));
+    if config.vertices == Some(0) {
+      return Err(format!("The vertices in {} has to be greater than 0", path.display()));
+    }
+    options.vertices = options.vertices.or(config.vertices);
+    options.placeholder |= config.placeholder;
+    options.fullscreen |= config.fullscreen;
+    options.monitor = options.monitor.take().or(config.monitor.take());
+    options.span |= config.span;
+    options.wallpaper |= config.wallpaper;
+    options.screensaver |= config.screensaver;
+    options.on_top |= config.on_top;
+    options.borderless |= config.borderless;
+    options.transparent |= config.transparent;
+    options.capture_alpha |= config.capture_alpha;
+    options.backend = options.backend.or(config.backend);
+    options.gpu = options.gpu.take().or(config.gpu.take());
+    // either one on the command line replaces whichever horus.toml asked for
+    if !options.low_power && !options.high_performance {
+      options.low_power = config.low_power;
+      options.high_performance = config.high_performance;
+    }
+    if options.low_power && options.high_performance {
+      return Err(format!("{} can't ask for both low-power and high-performance", path.display()));
+    }
+    if !options.srgb && !options.linear {
+      options.srgb = config.srgb;
+      options.linear = config.linear;
+    }
+    if options.srgb && options.linear {
+      return Err(format!("{} can't ask for both srgb and linear", path.display()));
+    }
+    options.present = options.present.or(config.present);
+    if config.max_fps == Some(0) {
+      return Err(format!("The max-fps in {} has to be greater than 0", path.display()));
+    }
+    options.max_fps = options.max_fps.or(config.max_fps);
+    options.still |= config.still;
+    if config.scale.is_some_and(|scale| scale <= 0. || !scale.is_finite()) {
+      return Err(format!("The scale in {} has to be greater than 0", path.display()));
+    }
+    options.scale = options.scale.or(config.scale);
+    if config.msaa.is_some_and(|samples| samples != 1 && samples != 4) {
+      return Err(format!("The msaa in {} has to be 1 or 4", path.display()));
+    }
+    options.msaa = options.msaa.or(config.msaa);
+    options.projection = options.projection.or(config.projection);
+    options.hdr |= config.hdr;
+    options.tonemap = options.tonemap.or(config.tonemap);
+    if options.tonemap().is_some() && options.capture_alpha {
+      return Err(format!("{} can't ask for both hdr and capture-alpha", path.display()));
+    }
+    let channels = [config.channel0.take(), config.channel1.take(), config.channel2.take(), config.channel3.take()];
+    let paths = [&mut options.channel0, &mut options.channel1, &mut options.channel2, &mut options.channel3];
+    let cubes = [&mut options.channel0_cube, &mut options.channel1_cube, &mut options.channel2_cube, &mut options.channel3_cube];
+    let wraps = [&mut options.channel0_wrap, &mut options.channel1_wrap, &mut options.channel2_wrap, &mut options.channel3_wrap];
+    let filters = [&mut options.channel0_filter, &mut options.channel1_filter, &mut options.channel2_filter, &mut options.channel3_filter];
+    let flips = [&mut options.channel0_flip, &mut options.channel1_flip, &mut options.channel2_flip, &mut options.channel3_flip];
+    let linears = [&mut options.channel0_linear, &mut options.channel1_linear, &mut options.channel2_linear, &mut options.channel3_linear];
+    for ((((((channel, path), cube), wrap), filter), flip), linear) in channels.into_iter().zip(paths).zip(cubes).zip(wraps).zip(filters).zip(flips).zip(linears) {
+      // the command line replaces the whole channel, including whether it's a cubemap and how it's sampled and loaded
+      let (channel, is_cube) = match channel {
+        Some(_) if path.is_some() => continue,
+        Some(ChannelConfig::Path(channel)) => (channel, false),
+        Some(ChannelConfig::Table { path: channel, cube: is_cube, wrap: configured_wrap, filter: configured_filter, flip: configured_flip, linear: configured_linear }) => {
+          *wrap = wrap.or(configured_wrap);
+          *filter = filter.or(configured_filter);
+          *flip |= configured_flip;
+          *linear |= configured_linear;
+          (channel, is_cube)
+        }
+        None => continue,
+      };
+      *path = Some(config.resolve_channel(&channel));
+      *cube |= is_cube;
+    }
+    if options.record.is_none() && options.record_gif.is_none() {
+      options.record = config.record.as_deref().map(|path| config.resolve(path));
+    }
+    if config.seconds.is_some_and(|seconds| seconds <= 0. || !seconds.is_finite()) {
+      return Err(format!("The seconds to record in {} have to be greater than 0", path.display()));
+    }
+    options.seconds = options.seconds.or(config.seconds);
+    if config.duration.is_some_and(|seconds| seconds <= 0. || !seconds.is_finite()) {
+      return Err(format!("The duration in {} has to be longer than 0 seconds", path.display()));
+    }
+    options.duration = options.duration.or(config.duration);
+    if config.speed.is_some_and(|speed| speed <= 0. || !speed.is_finite()) {
+      return Err(format!("The speed in {} has to be greater than 0", path.display()));
+    }
+    options.speed = options.speed.or(config.speed);
+    options.deterministic |= config.deterministic;
+    options.seed = options.seed.or(config.seed);
+    if config.record_input.is_some() && config.replay_input.is_some() {
+      return Err(format!("{} can't both record input and replay it", path.display()));
+    }
+    if options.record_input.is_none() && options.replay_input.is_none() {
+      options.record_input = config.record_input.as_deref().map(|path| config.resolve(path));
+      options.replay_input = config.replay_input.as_deref().map(|path| config.resolve(path));
+    }
+    if config.scrub_step.is_some_and(|step| step <= 0. || !step.is_finite()) {
+      return Err(format!("The scrub step in {} has to be greater than 0", path.display()));
+    }
+    options.scrub_step = options.scrub_step.or(config.scrub_step);
+    if config.cycle.is_some_and(|seconds| seconds <= 0. || !seconds.is_finite()) {
+      return Err(format!("The cycle in {} has to be longer than 0 seconds", path.display()));
+    }
+    options.cycle = options.cycle.or(config.cycle);
+    options.shuffle |= config.shuffle;
+    options.audio = options.audio.take().or_else(|| config.audio.as_deref().map(|path| config.resolve(path)));
+    options.ndi = options.ndi.take().or(config.ndi.take());
+    options.spout = options.spout.take().or(config.spout.take());
+    options.syphon = options.syphon.take().or(config.syphon.take());
+    options.timeline = options.timeline.take().or_else(|| config.timeline.as_deref().map(|path| config.resolve(path)));
+    options.osc = options.osc.or(config.osc);
+    options.remote = options.remote.or(config.remote);
+    options.remote_host = options.remote_host.or(config.remote_host);
+    options.stdin |= config.stdin;
+    let size = match (config.window.width, config.window.height) {
+      (Some(width), Some(height)) if width > 0 && height > 0 => Some((width, height)),
+      (None, None) => None,
+      _ => return Err(format!("The window in {} needs a width and a height that are both greater than 0", path.display())),
+    };
+    options.size = options.size.or(size);
+    options.title = options.title.take().or(config.window.title.take());
+
+    let PassesConfig { buffer_a, buffer_b, buffer_c, buffer_d, common, cubemap_a, sound } = &config.passes;
+    for (buffer, configured) in options.buffers.iter_mut().zip([buffer_a, buffer_b, buffer_c, buffer_d]) {
+      *buffer = buffer.take().or_else(|| configured.as_deref().map(|path| config.resolve(path)));
+    }
+    options.common = options.common.take().or_else(|| common.as_deref().map(|path| config.resolve(path)));
+    options.cubemap = options.cubemap.take().or_else(|| cubemap_a.as_deref().map(|path| config.resolve(path)));
+    options.sound = options.sound.take().or_else(|| sound.as_deref().map(|path| config.resolve(path)));
+    // --define replaces any constant horus.toml declares with the same name
+    for (name, value) in &config.define {
+      if options.defines.iter().any(|define| define.name == *name) {
+        continue;
+      }
+      let value = match value {
+        toml::Value::Boolean(true) => None,
+        toml::Value::String(value) => Some(value.clone()),
+        toml::Value::Boolean(_) | toml::Value::Integer(_) | toml::Value::Float(_) => Some(value.to_string()),
+        _ => return Err(format!("The define {} in {} has to be a number, true, false or a string", name, path.display())),
+      };
+      options.defines.push(Define::new(name, value).map_err(|error| format!("{} in {}", error, path.display()))?);
+    }
+    if !config.uniform.is_empty() {
+      options.uniforms = Some(path);
+    }
+    Ok(Some(config))
+  }
+
+  // fill in whichever export settings weren't passed to `horus render`
+  pub fn apply_render(&self, render: &mut RenderOptions) {
+    render.width = render.width.or(self.render.width);
+    render.height = render.height.or(self.render.height);
+    render.frames = render.frames.or(self.render.frames);
+    render.fps = render.fps.or(self.render.fps);
+    render.tile = render.tile.or(self.render.tile);
+    render.format = render.format.or(self.render.format);
+    render.output = render.output.take().or_else(|| self.render.output.as_deref().map(|path| self.resolve(path)));
+  }
+
+  fn resolve(&self, path: &Path) -> PathBuf {
+    self.directory.join(path)
+  }
+
+  fn resolve_channel(&self, path: &Path) -> PathBuf {
+    resolve_channel(&self.directory, path)
+  }
+}
+
+// where the channel `path` is found from `directory`, which can also be the name of something built in that isn't a path at all
+pub fn resolve_channel(directory: &Path, path: &Path) -> PathBuf {
+  if channel::is_built_in(path.to_str().unwrap_or_default()) { path.to_path_buf() } else { directory.join(path) }
+}
@@ -0,0 +1,59 @@
+// a glyph atlas laid out like shadertoy's font texture, so shaders that draw text with it can be ported without changes
+//
+// the 128 ASCII characters are drawn from the same 8x8 font as the text overlay into a 16x16 grid of cells, with character `c` in column
+// `c % 16` and row `c / 16` from the top. Red, green and blue are the glyph and alpha is its distance field, which is 0.5 on its edge,
+// less inside it and more outside
+
+use font8x8::legacy::BASIC_LEGACY;
+
+// the name that can be passed to --channel0 through --channel3 instead of a path
+pub const NAME: &str = "font";
+
+pub const SIZE: u32 = 1024;
+const CELL: usize = SIZE as usize / 16;
+// each pixel of a glyph covers 6x6 pixels of the atlas, leaving a margin around it for the distance field to fade out in
+const SCALE: usize = 6;
+const MARGIN: usize = 8;
+// glyphs are 8x8 pixels including the space after them and below the baseline, so capitals are 7 pixels tall
+const GLYPH_SIZE: usize = 8;
+const CAP_HEIGHT: usize = 7;
+
+// where a glyph starts from the left of its cell, where its baseline is from the bottom, how far it is to the next one and how tall
+// its capitals are, all as a fraction of a cell
+pub const METRICS: [f32; 4] = [
+  MARGIN as f32 / CELL as f32,
+  (CELL - MARGIN - CAP_HEIGHT * SCALE) as f32 / CELL as f32,
+  (GLYPH_SIZE * SCALE) as f32 / CELL as f32,
+  (CAP_HEIGHT * SCALE) as f32 / CELL as f32,
+];
+
+// the RGBA pixels of the atlas, top row first
+pub fn generate() -> Vec<u8> {
+  // the cells past the ASCII characters are empty, which is as far outside a glyph as the distance field goes
+  let mut pixels = [0, 0, 0, u8::MAX].repeat(SIZE as usize * SIZE as usize);
+  for (character, glyph) in BASIC_LEGACY.iter().enumerate() {
+    let on = |x: i32, y: i32| (0..GLYPH_SIZE as i32).contains(&x) && (0..GLYPH_SIZE as i32).contains(&y) && glyph[y as usize] >> x & 1 == 1;
+    let (left, top) = (character % 16 * CELL, character / 16 * CELL);
+    for y in 0..CELL {
+      for x in 0..CELL {
+        // the center of the pixel in the glyph's own pixels
+        let (px, py) = ((x as f32 + 0.5 - MARGIN as f32) / SCALE as f32, (y as f32 + 0.5 - MARGIN as f32) / SCALE as f32);
+        let (gx, gy) = (px.floor() as i32, py.floor() as i32);
+        let inside = on(gx, gy);
+        // the distance to the closest pixel on the other side of the edge, which only needs looking for as far as the margin reaches
+        let reach = (MARGIN / SCALE + 1) as i32;
+        let distance = (gy - reach..=gy + reach).flat_map(|y| (gx - reach..=gx + reach).map(move |x| (x, y))).filter(|&(x, y)| on(x, y) != inside).map(|(x, y)| {
+          let dx = (x as f32 - px).max(px - (x + 1) as f32).max(0.);
+          let dy = (y as f32 - py).max(py - (y + 1) as f32).max(0.);
+          (dx * dx + dy * dy).sqrt()
+        }).fold(f32::INFINITY, f32::min) * SCALE as f32;
+        let signed = if inside { -distance } else { distance };
+        let coverage = ((0.5 - signed).clamp(0., 1.) * 255.).round() as u8;
+        let field = ((0.5 + signed / (2 * MARGIN) as f32).clamp(0., 1.) * 255.).round() as u8;
+        let index = ((top + y) * SIZE as usize + left + x) * 4;
+        pixels[index..index + 4].copy_from_slice(&[coverage, coverage, coverage, field]);
+      }
+    }
+  }
+  pixels
+}
@@ -0,0 +1,83 @@
+// up to four gamepads as a data texture, which shaders read with `texelFetch(iChannelN, ivec2(index, row), 0).x`
+// where gamepad N's axes are on row 2N, between -1 and 1 with up being positive, and its buttons are on row 2N + 1, between 0 and 1
+
+use gilrs::{Axis, Button, Gilrs};
+use half::f16;
+use wgpu::{Device, Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d, Queue, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages};
+
+use crate::channel::Channel;
+
+const GAMEPADS: usize = 4;
+
+// the order of each row, with the buttons matching the standard gamepad layout that browsers use as far as they can
+const AXES: [Axis; 8] = [Axis::LeftStickX, Axis::LeftStickY, Axis::LeftZ, Axis::RightStickX, Axis::RightStickY, Axis::RightZ, Axis::DPadX, Axis::DPadY];
+const BUTTONS: [Button; 19] = [
+  Button::South, Button::East, Button::West, Button::North,
+  Button::LeftTrigger, Button::RightTrigger, Button::LeftTrigger2, Button::RightTrigger2,
+  Button::Select, Button::Start, Button::LeftThumb, Button::RightThumb,
+  Button::DPadUp, Button::DPadDown, Button::DPadLeft, Button::DPadRight,
+  Button::Mode, Button::C, Button::Z,
+];
+const WIDTH: usize = BUTTONS.len();
+
+pub struct Gamepads {
+  gilrs: Gilrs,
+  texture: Texture,
+}
+
+impl Gamepads {
+  pub fn start(device: &Device) -> Result<Self, String> {
+    let gilrs = Gilrs::new().map_err(|error| error.to_string())?;
+    for (_, gamepad) in gilrs.gamepads() {
+      println!("[Horus] Reading {}", gamepad.name());
+    }
+    // half floats keep the sticks precise while still being filterable
+    let texture = device.create_texture(&TextureDescriptor {
+      label: Some("gamepad"),
+      size: Extent3d { width: WIDTH as u32, height: (GAMEPADS * 2) as u32, depth_or_array_layers: 1 },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D2,
+      format: TextureFormat::R16Float,
+      usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+    });
+    Ok(Gamepads { gilrs, texture })
+  }
+
+  pub fn channel(&self, device: &Device) -> Channel {
+    Channel::from_texture(device, &self.texture)
+  }
+
+  // catch up on every event since the last frame and upload where each gamepad is now
+  pub fn update(&mut self, queue: &Queue) {
+    while self.gilrs.next_event().is_some() {}
+    let mut state = vec![f16::ZERO; WIDTH * GAMEPADS * 2];
+    for (id, gamepad) in self.gilrs.gamepads() {
+      let index = usize::from(id);
+      if index >= GAMEPADS {
+        continue;
+      }
+      for (value, axis) in state[index * 2 * WIDTH..].iter_mut().zip(AXES) {
+        *value = f16::from_f32(gamepad.value(axis));
+      }
+      for (value, button) in state[(index * 2 + 1) * WIDTH..].iter_mut().zip(BUTTONS) {
+        *value = f16::from_f32(gamepad.button_data(button).map_or(0., |data| data.value()));
+      }
+    }
+    queue.write_texture(
+      ImageCopyTexture {
+        texture: &self.texture,
+        mip_level: 0,
+        origin: Origin3d::ZERO,
+        aspect: TextureAspect::All,
+      },
+      bytemuck::cast_slice(&state),
+      ImageDataLayout {
+        offset: 0,
+        bytes_per_row: std::num::NonZeroU32::new((WIDTH * 2) as u32),
+        rows_per_image: None,
+      },
+      Extent3d { width: WIDTH as u32, height: (GAMEPADS * 2) as u32, depth_or_array_layers: 1 },
+    );
+  }
+}
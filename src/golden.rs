@@ -0,0 +1,185 @@
+// `horus test` renders each shader up to one frame and compares it against a reference PNG that was saved from it earlier, so
+// changes to the shaders or to Horus that alter what they draw fail in CI
+//
+// references live in a references directory next to each shader, named after it, and --update saves them from what's rendered
+
+use std::{fs, path::{Path, PathBuf}};
+use clap::Args;
+use image::RgbaImage;
+
+use crate::{cli::{Backend, Options}, clock, config::{self, Config}, metadata::Metadata, playlist, render::{self, Offscreen}};
+
+// what's saved next to a reference when a shader doesn't match it
+const FAILED_EXTENSION: &str = "failed.png";
+
+#[derive(Args, Debug)]
+pub struct TestOptions {
+  #[arg(value_name = "SHADER", required = true, help = "The shaders to test, project directories with a horus.toml in them, or directories of shaders to test every one of")]
+  pub paths: Vec<PathBuf>,
+  #[arg(long, default_value_t = 0, help = "The frame that's compared, which every frame before it is rendered up to")]
+  pub frame: u32,
+  #[arg(long, default_value_t = 256, value_parser = clap::value_parser!(u32).range(1..), help = "The width of the frame in pixels")]
+  pub width: u32,
+  #[arg(long, default_value_t = 256, value_parser = clap::value_parser!(u32).range(1..), help = "The height of the frame in pixels")]
+  pub height: u32,
+  #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u32).range(1..), help = "How many frames make up a second of the shader's time")]
+  pub fps: u32,
+  #[arg(long, default_value_t = 2, help = "How far each channel of a pixel can be from the reference's, out of 255, since GPUs round differently")]
+  pub tolerance: u8,
+  #[arg(long, value_name = "DIRECTORY", help = "The directory every reference is kept in [default: references next to each shader]")]
+  pub references: Option<PathBuf>,
+  #[arg(long, help = "Save what each shader renders as its reference instead of comparing them")]
+  pub update: bool,
+  #[arg(long, value_enum, help = "The graphics API to render on instead of the one that's picked automatically")]
+  pub backend: Option<Backend>,
+  #[arg(long, help = "The index or part of the name of the GPU to render on")]
+  pub gpu: Option<String>,
+}
+
+// how a frame turned out against its reference
+enum Outcome {
+  Matched,
+  Updated,
+  Failed(String),
+}
+
+pub async fn test(test: TestOptions) -> Result<(), String> {
+  let shaders = find_shaders(&test.paths)?;
+  let (device, queue) = render::request_device(&Options { backend: test.backend, gpu: test.gpu.clone(), ..Default::default() }).await?;
+  let mut failed = 0;
+  for (shader, options) in &shaders {
+    let outcome = match run_test(&device, &queue, &test, shader, options) {
+      Ok(outcome) => outcome,
+      Err(error) => Outcome::Failed(error),
+    };
+    match outcome {
+      Outcome::Matched => println!("[Horus] Passed {}", shader.display()),
+      Outcome::Updated => println!("[Horus] Saved the reference for {}", shader.display()),
+      Outcome::Failed(error) => {
+        failed += 1;
+        println!("[Horus] Failed {}: {}", shader.display(), error);
+      }
+    }
+  }
+  if failed > 0 {
+    return Err(format!("{} of {} shaders didn't match their references", failed, shaders.len()));
+  }
+  match test.update {
+    true => println!("[Horus] Saved {} references", shaders.len()),
+    false => println!("[Horus] All {} shaders matched their references", shaders.len()),
+  }
+  Ok(())
+}
+
+// every shader `paths` lead to, along with the options each of them runs with
+fn find_shaders(paths: &[PathBuf]) -> Result<Vec<(PathBuf, Options)>, String> {
+  let mut shaders = Vec::new();
+  for path in paths {
+    let paths = match path.is_dir() && !path.join(config::NAME).is_file() {
+      true => playlist::shaders_in(path)?,
+      false => vec![path.clone()],
+    };
+    for path in paths {
+      let mut options = Options { fragment_path: Some(path), ..Default::default() };
+      Config::apply(&mut options)?;
+      Metadata::apply(&mut options)?;
+      let shader = options.fragment_path.clone().ok_or("There's no shader to test")?;
+      shaders.push((shader, options));
+    }
+  }
+  match shaders.is_empty() {
+    true => Err("There aren't any shaders to test".to_string()),
+    false => Ok(shaders),
+  }
+}
+
+fn run_test(device: &wgpu::Device, queue: &wgpu::Queue, test: &TestOptions, shader: &Path, options: &Options) -> Result<Outcome, String> {
+  let mut offscreen = Offscreen::new(device, queue, options, test.width, test.height, test.fps, None, render::FrameFormat::Png.texture_format(options.linear))?;
+  // the date is always the same, so shaders that read it still render the same frame
+  offscreen.set_start(clock::fixed_start());
+  let mut image = offscreen.next_frame()?;
+  for _ in 0..test.frame {
+    image = offscreen.next_frame()?;
+  }
+
+  let reference = reference_path(shader, test.references.as_deref());
+  let failed = reference.with_extension(FAILED_EXTENSION);
+  if test.update {
+    if let Some(parent) = reference.parent() {
+      fs::create_dir_all(parent).map_err(|error| format!("Unable to create {}: {}", parent.display(), error))?;
+    }
+    image.save(&reference).map_err(|error| format!("Unable to save {}: {}", reference.display(), error))?;
+    let _ = fs::remove_file(&failed);
+    return Ok(Outcome::Updated);
+  }
+  if !reference.is_file() {
+    return Ok(Outcome::Failed(format!("there's no reference at {}, which --update saves", reference.display())));
+  }
+  let expected = image::open(&reference).map_err(|error| format!("Unable to load {}: {}", reference.display(), error))?.to_rgba8();
+  match compare(&image, &expected, test.tolerance) {
+    None => {
+      let _ = fs::remove_file(&failed);
+      Ok(Outcome::Matched)
+    }
+    // what was rendered is kept so it can be looked at, or copied over the reference if it's right
+    Some(difference) => {
+      image.save(&failed).map_err(|error| format!("Unable to save {}: {}", failed.display(), error))?;
+      Ok(Outcome::Failed(format!("{}, and what it rendered is in {}", difference, failed.display())))
+    }
+  }
+}
+
+// where `shader`'s reference is kept, which is named after its file so shaders in the same directory each have their own
+fn reference_path(shader: &Path, references: Option<&Path>) -> PathBuf {
+  let directory = references.map_or_else(|| shader.parent().unwrap_or(Path::new("")).join("references"), Path::to_path_buf);
+  let name = shader.file_stem().unwrap_or_default().to_string_lossy();
+  directory.join(format!("{}.png", name))
+}
+
+// how `image` differs from `expected`, if any of its pixels are further from it than `tolerance`
+pub fn compare(image: &RgbaImage, expected: &RgbaImage, tolerance: u8) -> Option<String> {
+  if image.dimensions() != expected.dimensions() {
+    return Some(format!("it's {}x{}, but the reference is {}x{}", image.width(), image.height(), expected.width(), expected.height()));
+  }
+  let (mut different, mut furthest) = (0, 0);
+  for (pixel, expected) in image.pixels().zip(expected.pixels()) {
+    let distance = pixel.0.iter().zip(expected.0).map(|(channel, expected)| channel.abs_diff(expected)).max().unwrap_or(0);
+    if distance > tolerance {
+      different += 1;
+      furthest = furthest.max(distance);
+    }
+  }
+  (different > 0).then(|| format!("{} of its {} pixels are off by up to {}", different, image.width() * image.height(), furthest))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use image::Rgba;
+
+  fn filled(width: u32, height: u32, color: [u8; 4]) -> RgbaImage {
+    RgbaImage::from_pixel(width, height, Rgba(color))
+  }
+
+  #[test]
+  fn matches_within_the_tolerance() {
+    let expected = filled(4, 4, [100, 100, 100, 255]);
+    assert!(compare(&expected, &expected, 0).is_none());
+    assert!(compare(&filled(4, 4, [102, 98, 100, 255]), &expected, 2).is_none());
+  }
+
+  #[test]
+  fn fails_past_the_tolerance() {
+    let expected = filled(2, 2, [100, 100, 100, 255]);
+    let mut image = expected.clone();
+    image.put_pixel(1, 0, Rgba([100, 103, 100, 255]));
+    assert_eq!(compare(&image, &expected, 2).as_deref(), Some("1 of its 4 pixels are off by up to 3"));
+    // the alpha channel counts as much as the colors
+    assert!(compare(&filled(2, 2, [100, 100, 100, 250]), &expected, 4).is_some());
+  }
+
+  #[test]
+  fn fails_at_a_different_size() {
+    assert_eq!(compare(&filled(2, 3, [0; 4]), &filled(3, 2, [0; 4]), 255).as_deref(), Some("it's 2x3, but the reference is 3x2"));
+  }
+}
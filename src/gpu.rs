@@ -0,0 +1,53 @@
+// picks the graphics adapter Horus runs on, which --gpu can choose by its index or part of its name
+
+use wgpu::{Adapter, Instance, RequestAdapterOptions, Surface, TextureFormat};
+
+use crate::cli::Options;
+
+// the adapter `options` asks for, or whichever one wgpu prefers, that can draw to `surface` if there is one
+pub async fn request_adapter(instance: &Instance, options: &Options, surface: Option<&Surface>) -> Result<Adapter, String> {
+  let Some(gpu) = &options.gpu else {
+    return instance.request_adapter(&RequestAdapterOptions {
+      power_preference: options.power_preference(),
+      compatible_surface: surface,
+      force_fallback_adapter: false,
+    }).await.ok_or("Unable to find a graphics adapter".to_string());
+  };
+  // a number is the adapter's index, and anything else is part of its name
+  let found = match gpu.parse::<usize>() {
+    Ok(index) => adapters(instance, options, surface).into_iter().nth(index),
+    Err(_) => adapters(instance, options, surface).into_iter().find(|adapter| adapter.get_info().name.to_lowercase().contains(&gpu.to_lowercase())),
+  };
+  let Some(adapter) = found else {
+    let names: Vec<_> = adapters(instance, options, surface).iter().enumerate().map(|(index, adapter)| format!("  {}: {}", index, describe(adapter))).collect();
+    return Err(match names.is_empty() {
+      true => "Unable to find any graphics adapters".to_string(),
+      false => format!("Unable to find the GPU {:?}, which can be one of these:\n{}", gpu, names.join("\n")),
+    });
+  };
+  println!("[Horus] Running on {}", describe(&adapter));
+  Ok(adapter)
+}
+
+// every adapter on the backends `options` allows, in the order --gpu counts them, that can draw to `surface` if there is one
+fn adapters(instance: &Instance, options: &Options, surface: Option<&Surface>) -> Vec<Adapter> {
+  instance.enumerate_adapters(options.backends()).filter(|adapter| surface.is_none_or(|surface| adapter.is_surface_supported(surface))).collect()
+}
+
+// the 8-bit format `surface` shows frames in, encoding them into sRGB unless they're `linear`, since which one comes first differs between platforms
+pub fn surface_format(surface: &Surface, adapter: &Adapter, linear: bool) -> TextureFormat {
+  let supported = surface.get_supported_formats(adapter);
+  let formats = match linear {
+    true => [TextureFormat::Bgra8Unorm, TextureFormat::Rgba8Unorm],
+    false => [TextureFormat::Bgra8UnormSrgb, TextureFormat::Rgba8UnormSrgb],
+  };
+  formats.into_iter().find(|format| supported.contains(format)).unwrap_or_else(|| {
+    println!("[Horus] Unable to show {} colors on this platform, so they're shown as {:?}", if linear { "linear" } else { "sRGB" }, supported[0]);
+    supported[0]
+  })
+}
+
+fn describe(adapter: &Adapter) -> String {
+  let info = adapter.get_info();
+  format!("{} ({:?})", info.name, info.backend)
+}
@@ -0,0 +1,249 @@
+// `horus import` downloads a shader from Shadertoy's API into a project directory, with a GLSL file for each of its passes, the
+// textures its channels read in the assets folder, and a horus.toml that binds them to the same channels
+//
+// the API only serves shaders that their authors published as "public + API", and needs a key from https://www.shadertoy.com/myapps
+
+use std::{fs::{create_dir, write}, path::{Path, PathBuf}, process::Command};
+use serde::Deserialize;
+
+use crate::{channel::CHANNEL_COUNT, config, keyboard, live, pass::{BUFFER_NAMES, COMMON_NAME, CUBEMAP_NAME, SOUND_NAME}, project};
+
+const SITE: &str = "https://www.shadertoy.com";
+
+// where the key is read from when it isn't passed with --key
+const KEY_VARIABLE: &str = "SHADERTOY_API_KEY";
+
+const SHADER_NAME: &str = "main.glsl";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Response {
+  shader: Option<Shader>,
+  error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Shader {
+  info: Info,
+  #[serde(rename = "renderpass")]
+  passes: Vec<RenderPass>,
+}
+
+#[derive(Deserialize)]
+struct Info {
+  name: String,
+  username: String,
+}
+
+#[derive(Deserialize)]
+struct RenderPass {
+  name: String,
+  #[serde(rename = "type")]
+  kind: String,
+  code: String,
+  #[serde(default)]
+  inputs: Vec<Input>,
+  #[serde(default)]
+  outputs: Vec<Output>,
+}
+
+// the ids of buffers are strings in some shaders and numbers in others
+#[derive(Deserialize)]
+struct Input {
+  id: serde_json::Value,
+  src: String,
+  ctype: String,
+  channel: usize,
+}
+
+#[derive(Deserialize)]
+struct Output {
+  id: serde_json::Value,
+}
+
+// write the shader at Shadertoy's `shader`, which is either its id or the address of its page, into a project directory called `name`,
+// or one named after the shader
+pub fn import(shader: &str, name: Option<&Path>, key: Option<String>) -> Result<(), String> {
+  let key = key.or_else(|| std::env::var(KEY_VARIABLE).ok()).filter(|key| !key.is_empty()).ok_or(format!("Shadertoy's API needs a key from {}/myapps, passed with --key or set in {}", SITE, KEY_VARIABLE))?;
+  let id = shader_id(shader)?;
+  let response = fetch(&format!("{}/api/v1/shaders/{}?key={}", SITE, id, key))?;
+  let response: Response = serde_json::from_slice(&response).map_err(|error| format!("Unable to read Shadertoy's response: {}", error))?;
+  let shader = match (response.shader, response.error) {
+    (Some(shader), _) => shader,
+    (None, Some(error)) => return Err(format!("Shadertoy couldn't find {}: {}, which happens when it isn't published with API access", id, error)),
+    (None, None) => return Err(format!("Shadertoy didn't send {}", id)),
+  };
+  let directory = name.map_or_else(|| PathBuf::from(directory_name(&shader.info.name, &id)), Path::to_path_buf);
+  create_dir(&directory).map_err(|error| format!("Unable to create {}: {}", directory.display(), error))?;
+  let assets = directory.join("assets");
+  create_dir(&assets).map_err(|error| format!("Unable to create {}: {}", assets.display(), error))?;
+
+  // buffers are read by the id of their output, which is the same in every shader
+  let buffers: Vec<(String, &str)> = shader.passes.iter().filter(|pass| pass.kind == "buffer").filter_map(|pass| {
+    let buffer = buffer_name(&pass.name)?;
+    Some((id_string(&pass.outputs.first()?.id), buffer))
+  }).collect();
+  // and so is the cubemap's, which channels read the same way as a cubemap texture
+  let cubemap = shader.passes.iter().find(|pass| pass.kind == "cubemap").and_then(|pass| Some(id_string(&pass.outputs.first()?.id)));
+
+  // horus gives every pass the same channels, so the image's come first and buffers only fill in the ones it leaves empty
+  let mut channels: [Option<String>; CHANNEL_COUNT] = Default::default();
+  let mut audio = None;
+  let mut passes: Vec<&RenderPass> = shader.passes.iter().filter(|pass| pass.kind == "image").collect();
+  passes.extend(shader.passes.iter().filter(|pass| pass.kind != "image"));
+  for pass in passes {
+    let file = match pass.kind.as_str() {
+      "image" => SHADER_NAME.to_string(),
+      "common" => format!("{}.glsl", COMMON_NAME),
+      "sound" => format!("{}.glsl", SOUND_NAME),
+      "cubemap" => format!("{}.glsl", CUBEMAP_NAME),
+      "buffer" => match buffer_name(&pass.name) {
+        Some(buffer) => format!("{}.glsl", buffer),
+        None => {
+          println!("[Horus] Left out {}, since only Buffer A through Buffer D can be read", pass.name);
+          continue;
+        }
+      },
+      _ => {
+        println!("[Horus] Left out the {} pass, since Horus doesn't run Shadertoy's {} passes", pass.name, pass.kind);
+        continue;
+      }
+    };
+    project::create_file(&directory.join(&file), &pass.code)?;
+    println!("[Horus] Created {}", directory.join(&file).display());
+
+    for input in &pass.inputs {
+      let channel = match input.ctype.as_str() {
+        "buffer" => buffers.iter().find(|(id, _)| *id == id_string(&input.id)).map(|(_, buffer)| format!("{:?}", buffer)),
+        "keyboard" => Some(format!("{:?}", keyboard::NAME)),
+        "mic" => Some(format!("{:?}", live::MICROPHONE)),
+        "webcam" => Some(format!("{:?}", live::WEBCAM)),
+        "texture" => Some(format!("{:?}", download(&input.src, &assets)?)),
+        "cubemap" if cubemap.as_ref() == Some(&id_string(&input.id)) => Some(format!("{:?}", CUBEMAP_NAME)),
+        "cubemap" => Some(format!("{{ path = {:?}, cube = true }}", download_cube(&input.src, &assets)?)),
+        "volume" => Some(format!("{:?}", download(&input.src, &assets)?)),
+        "music" | "musicstream" => {
+          audio = Some(download(&input.src, &assets)?);
+          Some(format!("{:?}", live::AUDIO))
+        }
+        _ => None,
+      };
+      let Some(channel) = channel else {
+        println!("[Horus] Left iChannel{} of {} empty, since Horus can't read Shadertoy's {} inputs", input.channel, pass.name, input.ctype);
+        continue;
+      };
+      match channels.get_mut(input.channel) {
+        Some(Some(bound)) if *bound != channel => println!("[Horus] {}'s iChannel{} reads {} instead of {}, since every pass shares the same channels", pass.name, input.channel, bound, channel),
+        Some(bound) => *bound = Some(channel),
+        None => {}
+      }
+    }
+  }
+
+  let mut config = format!("# \"{}\" by {}, imported from {}/view/{}\nshader = {:?}\nshadertoy = true\n", shader.info.name, shader.info.username, SITE, id, SHADER_NAME);
+  for (index, channel) in channels.iter().enumerate() {
+    if let Some(channel) = channel {
+      config.push_str(&format!("channel{} = {}\n", index, channel));
+    }
+  }
+  if let Some(audio) = audio {
+    config.push_str(&format!("audio = {:?}\n", audio));
+  }
+  project::create_file(&directory.join(config::NAME), &config)?;
+  project::create_file(&directory.join(".gitignore"), project::GITIGNORE_SOURCE)?;
+  println!("[Horus] Imported \"{}\" by {} into {}, which \"horus {}\" runs", shader.info.name, shader.info.username, directory.display(), directory.display());
+  Ok(())
+}
+
+// the id at the end of a shader's address, like https://www.shadertoy.com/view/XsXXDn, or the id by itself
+fn shader_id(shader: &str) -> Result<String, String> {
+  let id = shader.split(['?', '#']).next().unwrap_or_default().trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+  match !id.is_empty() && id.chars().all(|character| character.is_ascii_alphanumeric()) {
+    true => Ok(id.to_string()),
+    false => Err(format!("{} isn't a Shadertoy id or the address of a shader", shader)),
+  }
+}
+
+// Shadertoy's "Buffer A" is horus' bufferA
+fn buffer_name(name: &str) -> Option<&'static str> {
+  let letter = name.trim().strip_prefix("Buffer")?.trim();
+  BUFFER_NAMES.into_iter().find(|buffer| buffer.strip_prefix("buffer") == Some(letter))
+}
+
+fn id_string(id: &serde_json::Value) -> String {
+  match id {
+    serde_json::Value::String(id) => id.clone(),
+    id => id.to_string(),
+  }
+}
+
+// the shader's name in lowercase with dashes between words, which is its id if there's nothing left
+fn directory_name(name: &str, id: &str) -> String {
+  let words: Vec<String> = name.split(|character: char| !character.is_ascii_alphanumeric()).filter(|word| !word.is_empty()).map(str::to_lowercase).collect();
+  if words.is_empty() { id.to_string() } else { words.join("-") }
+}
+
+// download one of Shadertoy's files, like /media/a/0123.png, into `assets`, returning its path relative to the project
+fn download(source: &str, assets: &Path) -> Result<String, String> {
+  let name = Path::new(source).file_name().ok_or(format!("{} isn't a file", source))?;
+  let path = assets.join(name);
+  write(&path, fetch(&format!("{}{}", SITE, source))?).map_err(|error| format!("Unable to write {}: {}", path.display(), error))?;
+  println!("[Horus] Downloaded {}", path.display());
+  Ok(Path::new("assets").join(name).display().to_string())
+}
+
+// a cubemap's first face is at its source and the other five have _1 through _5 after its name, which are saved as px through nz in
+// a directory of their own
+fn download_cube(source: &str, assets: &Path) -> Result<String, String> {
+  let source = Path::new(source);
+  let (Some(stem), Some(extension)) = (source.file_stem().and_then(|stem| stem.to_str()), source.extension().and_then(|extension| extension.to_str())) else {
+    return Err(format!("{} isn't a file", source.display()));
+  };
+  let directory = assets.join(stem);
+  create_dir(&directory).map_err(|error| format!("Unable to create {}: {}", directory.display(), error))?;
+  for (index, face) in ["px", "nx", "py", "ny", "pz", "nz"].into_iter().enumerate() {
+    let name = if index == 0 { format!("{}.{}", stem, extension) } else { format!("{}_{}.{}", stem, index, extension) };
+    let url = format!("{}{}", SITE, source.with_file_name(name).display());
+    let path = directory.join(face).with_extension(extension);
+    write(&path, fetch(&url)?).map_err(|error| format!("Unable to write {}: {}", path.display(), error))?;
+  }
+  println!("[Horus] Downloaded {}", directory.display());
+  Ok(Path::new("assets").join(stem).display().to_string())
+}
+
+// curl comes with every platform Horus runs on, and saves it from needing an HTTP client of its own
+fn fetch(url: &str) -> Result<Vec<u8>, String> {
+  let output = Command::new("curl").args(["--fail", "--silent", "--show-error", "--location", url]).output().map_err(|error| format!("Unable to start curl: {}", error))?;
+  match output.status.success() {
+    true => Ok(output.stdout),
+    false => Err(format!("Unable to download {}: {}", url.split('?').next().unwrap_or(url), String::from_utf8_lossy(&output.stderr).trim())),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn finds_the_id_in_an_address() {
+    assert_eq!(shader_id("XsXXDn").unwrap(), "XsXXDn");
+    assert_eq!(shader_id("https://www.shadertoy.com/view/XsXXDn").unwrap(), "XsXXDn");
+    assert_eq!(shader_id("https://www.shadertoy.com/view/XsXXDn/").unwrap(), "XsXXDn");
+    assert_eq!(shader_id("https://www.shadertoy.com/view/XsXXDn?time=3#comments").unwrap(), "XsXXDn");
+  }
+
+  #[test]
+  fn rejects_what_isnt_an_id() {
+    assert!(shader_id("").is_err());
+    assert!(shader_id("https://www.shadertoy.com/view/Xs-XDn").is_err());
+  }
+
+  #[test]
+  fn names_buffers_like_horus() {
+    assert_eq!(buffer_name("Buffer A"), Some("bufferA"));
+    assert_eq!(buffer_name(" Buffer D "), Some("bufferD"));
+    assert_eq!(buffer_name("Buffer E"), None);
+    assert_eq!(buffer_name("Cube A"), None);
+    assert_eq!(buffer_name("Image"), None);
+  }
+}
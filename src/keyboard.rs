@@ -0,0 +1,196 @@
+// shadertoy's keyboard texture, which shaders read with `texelFetch(iChannelN, ivec2(key, row), 0).x`
+// where key is a javascript key code and row 0 is held, row 1 was pressed this frame and row 2 flips every press
+
+use wgpu::{Device, Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d, Queue, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages};
+use winit::event::{ElementState, VirtualKeyCode};
+
+use crate::channel::Channel;
+
+// passed to --channel0 through --channel3 instead of a path
+pub const NAME: &str = "keyboard";
+
+const KEYS: usize = 256;
+const HELD: usize = 0;
+const PRESSED: usize = 1;
+const TOGGLED: usize = 2;
+
+pub struct Keyboard {
+  texture: Texture,
+  // one byte per key on each row, uploaded whenever it changes
+  state: [u8; KEYS * 3],
+  changed: bool,
+}
+
+impl Keyboard {
+  pub fn new(device: &Device) -> Self {
+    let texture = device.create_texture(&TextureDescriptor {
+      label: Some("keyboard"),
+      size: Extent3d { width: KEYS as u32, height: 3, depth_or_array_layers: 1 },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D2,
+      format: TextureFormat::R8Unorm,
+      usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+    });
+    Keyboard { texture, state: [0; KEYS * 3], changed: true }
+  }
+
+  pub fn channel(&self, device: &Device) -> Channel {
+    Channel::from_texture(device, &self.texture)
+  }
+
+  pub fn input(&mut self, key: VirtualKeyCode, state: ElementState) {
+    if let Some(code) = key_code(key) {
+      self.press(code, state == ElementState::Pressed);
+    }
+  }
+
+  // hold down or let go of the key with the javascript key code `code`
+  pub fn press(&mut self, code: usize, held: bool) {
+    if code >= KEYS {
+      return;
+    }
+    // holding a key down repeats its press, which shouldn't count as pressing it again
+    if held && self.state[HELD * KEYS + code] == 0 {
+      self.state[PRESSED * KEYS + code] = u8::MAX;
+      self.state[TOGGLED * KEYS + code] ^= u8::MAX;
+    }
+    self.state[HELD * KEYS + code] = if held { u8::MAX } else { 0 };
+    self.changed = true;
+  }
+
+  // send any key presses since the last frame to the texture
+  pub fn upload(&mut self, queue: &Queue) {
+    if !self.changed {
+      return;
+    }
+    queue.write_texture(
+      ImageCopyTexture {
+        texture: &self.texture,
+        mip_level: 0,
+        origin: Origin3d::ZERO,
+        aspect: TextureAspect::All,
+      },
+      &self.state,
+      ImageDataLayout {
+        offset: 0,
+        bytes_per_row: std::num::NonZeroU32::new(KEYS as u32),
+        rows_per_image: None,
+      },
+      Extent3d { width: KEYS as u32, height: 3, depth_or_array_layers: 1 },
+    );
+    self.changed = false;
+  }
+
+  // keys only count as pressed for the frame after they went down
+  pub fn end_frame(&mut self) {
+    let pressed = &mut self.state[PRESSED * KEYS..TOGGLED * KEYS];
+    if pressed.iter().any(|&key| key != 0) {
+      pressed.fill(0);
+      self.changed = true;
+    }
+  }
+}
+
+// the javascript key code that shadertoy uses for a key
+pub fn key_code(key: VirtualKeyCode) -> Option<usize> {
+  use VirtualKeyCode::*;
+  let code = match key {
+    Back => 8,
+    Tab => 9,
+    Return | NumpadEnter => 13,
+    LShift | RShift => 16,
+    LControl | RControl => 17,
+    LAlt | RAlt => 18,
+    Pause => 19,
+    Capital => 20,
+    Escape => 27,
+    Space => 32,
+    PageUp => 33,
+    PageDown => 34,
+    End => 35,
+    Home => 36,
+    Left => 37,
+    Up => 38,
+    Right => 39,
+    Down => 40,
+    Insert => 45,
+    Delete => 46,
+    Key0 => 48,
+    Key1 => 49,
+    Key2 => 50,
+    Key3 => 51,
+    Key4 => 52,
+    Key5 => 53,
+    Key6 => 54,
+    Key7 => 55,
+    Key8 => 56,
+    Key9 => 57,
+    A => 65,
+    B => 66,
+    C => 67,
+    D => 68,
+    E => 69,
+    F => 70,
+    G => 71,
+    H => 72,
+    I => 73,
+    J => 74,
+    K => 75,
+    L => 76,
+    M => 77,
+    N => 78,
+    O => 79,
+    P => 80,
+    Q => 81,
+    R => 82,
+    S => 83,
+    T => 84,
+    U => 85,
+    V => 86,
+    W => 87,
+    X => 88,
+    Y => 89,
+    Z => 90,
+    Numpad0 => 96,
+    Numpad1 => 97,
+    Numpad2 => 98,
+    Numpad3 => 99,
+    Numpad4 => 100,
+    Numpad5 => 101,
+    Numpad6 => 102,
+    Numpad7 => 103,
+    Numpad8 => 104,
+    Numpad9 => 105,
+    NumpadMultiply => 106,
+    NumpadAdd => 107,
+    NumpadSubtract => 109,
+    NumpadDecimal => 110,
+    NumpadDivide => 111,
+    F1 => 112,
+    F2 => 113,
+    F3 => 114,
+    F4 => 115,
+    F5 => 116,
+    F6 => 117,
+    F7 => 118,
+    F8 => 119,
+    F9 => 120,
+    F10 => 121,
+    F11 => 122,
+    F12 => 123,
+    Semicolon => 186,
+    Equals => 187,
+    Comma => 188,
+    Minus => 189,
+    Period => 190,
+    Slash => 191,
+    Grave => 192,
+    LBracket => 219,
+    Backslash => 220,
+    RBracket => 221,
+    Apostrophe => 222,
+    _ => return None,
+  };
+  Some(code)
+}
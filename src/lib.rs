@@ -0,0 +1,673 @@
+// the shader engine behind the horus command, which other programs can also embed with HorusRenderer
+
+use std::{borrow::Cow, collections::hash_map::RandomState, hash::{BuildHasher, Hasher}};
+use chrono::{DateTime, Datelike, Duration, Local};
+use image::RgbaImage;
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle};
+use wgpu::{BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferUsages, Color, CommandEncoder, CommandEncoderDescriptor, CompositeAlphaMode, Device, DeviceDescriptor, Extent3d, Features, Instance, Limits, LoadOp, Operations, PipelineLayout, PipelineLayoutDescriptor, PresentMode, Queue, RenderPassColorAttachment, RenderPassDescriptor, ShaderModule, ShaderModuleDescriptor, ShaderSource, ShaderStages, Surface, SurfaceConfiguration, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor, util::{BufferInitDescriptor, DeviceExt}};
+
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod bundle;
+pub mod capture;
+pub mod channel;
+pub mod check;
+pub mod cli;
+pub mod clock;
+pub mod config;
+pub mod font;
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
+pub mod golden;
+pub mod gpu;
+pub mod import;
+pub mod keyboard;
+pub mod live;
+pub mod metadata;
+pub mod midi;
+pub mod mipmap;
+pub mod module;
+pub mod monitor;
+pub mod msaa;
+pub mod ndi;
+pub mod noise;
+pub mod osc;
+pub mod overlay;
+pub mod panel;
+pub mod params;
+pub mod pass;
+pub mod playlist;
+pub mod project;
+pub mod projection;
+pub mod remote;
+pub mod render;
+pub mod replay;
+pub mod scale;
+pub mod screensaver;
+pub mod shader;
+pub mod shadertoy;
+pub mod sound;
+pub mod spout;
+pub mod stats;
+pub mod syphon;
+pub mod template;
+pub mod timeline;
+pub mod timer;
+pub mod touch;
+pub mod volume;
+pub mod wallpaper;
+#[cfg(feature = "webcam")]
+pub mod webcam;
+
+use capture::Readback;
+use channel::{Channel, ColorSpace, Sampling};
+use cli::Options;
+use clock::Clock;
+use live::LiveChannels;
+use metadata::Metadata;
+use msaa::Multisample;
+use overlay::TextOverlay;
+use pass::{ChannelInput, Pass, Passes, BUFFER_NAMES, CUBEMAP_NAME};
+use touch::Touches;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Uniforms {
+  pub mouse: [f32; 2],
+  pub resolution: [f32; 2],
+  pub time: f32,
+  // seconds since the previous frame
+  pub time_delta: f32,
+  // frames rendered since the shaders were last rebuilt or the buffers were cleared
+  pub frame: i32,
+  // samples per second of the audio file, if there is one
+  pub sample_rate: f32,
+  // shadertoy's iMouse: xy follows the cursor while the left button is held and zw is where it was pressed,
+  // z turns negative when the button is released and w is only positive on the frame it was pressed
+  pub mouse_click: [f32; 4],
+  // shadertoy's iDate: the year, the month starting from 0, the day of the month and the seconds since midnight
+  pub date: [f32; 4],
+  // where the target's top left corner is in the whole frame, which is only ever past 0 while `horus render --tile` renders it in pieces
+  // or while the cubemap and sound passes render theirs
+  pub offset: [f32; 2],
+  // 1 on a beat of the microphone or the audio file, fading towards 0 until the next one
+  pub beat: f32,
+  // the tempo those beats are at, or 0 until it's been heard
+  pub bpm: f32,
+  // where glyphs sit in the cells of the font channel
+  pub font: [f32; 4],
+  // a number between 0 and 1 picked by --seed, or at random, that generative shaders can vary what they make by
+  pub seed: f32,
+  // shaders start a vec2 on a multiple of 8 bytes
+  pub padding: f32,
+  // how many notches the mouse wheel has scrolled since Horus started, across and then up, which the magnifier doesn't count
+  pub scroll: [f32; 2],
+}
+
+impl Uniforms {
+  pub fn new(width: u32, height: u32) -> Self {
+    Uniforms { resolution: [width as _, height as _], font: font::METRICS, ..bytemuck::Zeroable::zeroed() }
+  }
+
+  // the same seed always gives the same number, mixed so that seeds next to each other don't give numbers next to each other
+  pub fn set_seed(&mut self, seed: u64) {
+    // splitmix64's finalizer
+    let mut mixed = seed.wrapping_add(0x9E3779B97F4A7C15);
+    mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D049BB133111EB);
+    mixed ^= mixed >> 31;
+    // the 24 bits a float holds exactly
+    self.seed = (mixed >> 40) as f32 / (1u64 << 24) as f32;
+  }
+
+  // move time on to `now` seconds in, and the date along with it
+  pub fn set_time(&mut self, now: f32, start: DateTime<Local>) {
+    self.time_delta = now - self.time;
+    self.time = now;
+    self.set_date(start);
+  }
+
+  // the local date `time` seconds after `start`, so recordings see their clock tick as fast as their time
+  pub fn set_date(&mut self, start: DateTime<Local>) {
+    let now = start + Duration::microseconds((self.time as f64 * 1e6) as i64);
+    let midnight = now.date_naive().and_hms_opt(0, 0, 0).unwrap_or_default();
+    let seconds = (now.naive_local() - midnight).num_microseconds().unwrap_or(0) as f64 / 1e6;
+    self.date = [now.year() as f32, now.month0() as f32, now.day() as f32, seconds as f32];
+  }
+}
+
+
+const VERTEX_SOURCE: &str = "\
+struct VertexInput {
+    @builtin(vertex_index) vertex_index: u32,
+};
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    // a single triangle that covers the entire surface
+    var vertices = array<vec2<f32>, 3>(
+        vec2<f32>(-1., 1.),
+        vec2<f32>(3., 1.),
+        vec2<f32>(-1., -3.),
+    );
+    var out: VertexOutput;
+    out.position = vec4<f32>(vertices[in.vertex_index], 0., 1.);
+    return out;
+}\
+";
+
+pub const FRAGMENT_SOURCE: &str = "\
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Uniforms {
+    mouse: vec2<f32>,
+    resolution: vec2<f32>,
+    time: f32,
+    time_delta: f32,
+    frame: i32,
+    sample_rate: f32,
+    mouse_click: vec4<f32>,
+    date: vec4<f32>,
+    offset: vec2<f32>,
+    beat: f32,
+    bpm: f32,
+    font: vec4<f32>,
+    seed: f32,
+    scroll: vec2<f32>,
+};
+
+struct Touches {
+    points: array<vec4<f32>, 4>,
+    count: i32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+@group(0) @binding(1)
+var<uniform> touches: Touches;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Horus uses the same uniforms as thebookofshaders.com and shadertoy.com
+    // `mouse` and `resolution` are in pixel coordinates
+    // `time` is the number of seconds since program start and `time_delta` is the number of seconds since the last frame
+    // `frame` counts up from 0 every time the shader is reloaded
+    // `mouse_click` is the cursor while the left button is held, followed by where it was pressed
+    // `date` is the year, month from 0, day and seconds since midnight
+    // `offset` is added to `position` so the shader can be rendered in tiles
+    // `beat` jumps to 1 on every beat of the audio and fades out, and `bpm` is its tempo
+    // `font` is where glyphs sit in the cells of the font channel
+    // `seed` is a number between 0 and 1 that's different every run unless --seed picks it
+    // `scroll` is how many notches the mouse wheel has scrolled, across and then up
+    // `touches` are the first `count` fingers on a touchscreen, each where it is followed by where it touched down
+    let rg = (in.position.xy + uniforms.offset) / uniforms.resolution;
+    return vec4<f32>(rg, 0., 1.);
+}\
+";
+
+
+// a seed that's different every time it's asked for, since every RandomState is keyed differently, which is kept short and printed so a
+// variation worth keeping can be picked again with --seed
+pub fn random_seed() -> u64 {
+  let seed = RandomState::new().build_hasher().finish() % 1_000_000;
+  println!("[Horus] Picked seed {} at random, which --seed {} picks again", seed, seed);
+  seed
+}
+
+// a single triangle that covers the whole target, shared by every pass
+pub fn create_vertex_shader(device: &Device) -> ShaderModule {
+  device.create_shader_module(ShaderModuleDescriptor {
+    label: None,
+    source: ShaderSource::Wgsl(Cow::Borrowed(VERTEX_SOURCE)),
+  })
+}
+
+// the fragment shader along with any buffers and common code next to it, and the channels they read from
+pub fn load_passes(device: &Device, queue: &Queue, options: &Options, live: &mut LiveChannels, format: TextureFormat, width: u32, height: u32) -> Result<Passes, String> {
+  let image = match &options.fragment_path {
+    Some(path) => {
+      println!("[Horus] Running {}", path.display());
+      if let Ok(metadata) = Metadata::read(path) {
+        match (metadata.name(), &metadata.description) {
+          (Some(name), Some(description)) => println!("[Horus] {}: {}", name, description),
+          (Some(name), None) => println!("[Horus] {}", name),
+          (None, Some(description)) => println!("[Horus] {}", description),
+          (None, None) => {}
+        }
+      }
+      Pass::load(path.clone(), options.shadertoy, format).map_err(|error| format!("Unable to read {}: {}", path.display(), error))?
+    }
+    None => Pass::new(None, FRAGMENT_SOURCE.to_string(), options.shadertoy, format),
+  };
+  Ok(create_passes(device, queue, options, live, image, width, height))
+}
+
+// `image` along with the buffers and common code next to it, and the channels that `options` asks for
+pub fn create_passes(device: &Device, queue: &Queue, options: &Options, live: &mut LiveChannels, mut image: Pass, width: u32, height: u32) -> Passes {
+  image.entry = options.entry.clone();
+  // with --projection, shadertoy images are asked for the ray through each pixel of the cubemap if they define `mainVR`
+  if options.projection.is_some() && image.is_shadertoy() && !image.project() {
+    println!("[Horus] {} doesn't define mainVR, so every face of the projection shows the same image", image.name);
+  }
+  // the audio channel reads the sound shader when there's no file to play
+  live.prepare_sound(device, options.audio.is_none() && Passes::sound_path(options.fragment_path.as_deref(), &options.sources()).is_some());
+
+  // textures passed in with --channel0 through --channel3
+  // shadertoy flips images and reads their bytes without any sRGB conversion, which --channel0-flip and --channel0-linear can change
+  let is_shadertoy = image.is_shadertoy();
+  let inputs: Vec<ChannelInput> = options.channels().iter().enumerate().map(|(index, channel)| match &channel.path {
+    Some(path) => {
+      let flip = is_shadertoy != channel.flip;
+      let color_space = (is_shadertoy || channel.linear).then_some(ColorSpace::Linear);
+      let name = path.to_str().unwrap_or_default();
+      if let Some(buffer) = BUFFER_NAMES.iter().position(|buffer| *buffer == name) {
+        return ChannelInput::Buffer(buffer);
+      }
+      if name == CUBEMAP_NAME {
+        return ChannelInput::Cubemap;
+      }
+      let loaded = if let Some(live) = live.channel(device, name, is_shadertoy) {
+        live
+      } else if let Some(noise) = Channel::noise(device, queue, name, options.seed.unwrap_or(0)) {
+        Ok(noise)
+      } else if name == font::NAME {
+        Ok(Channel::font(device, queue, is_shadertoy))
+      } else if name.starts_with("noise-") && !path.exists() {
+        Err(format!("the built in noise textures are {}", noise::NAMES.join(", ")))
+      } else if channel::is_built_in(name) {
+        Err("it isn't available in this session".to_string())
+      } else if channel.cube {
+        Channel::cube_from_path(device, queue, path)
+      } else if volume::is_volume(path) {
+        Channel::volume_from_path(device, queue, path, flip, color_space)
+      } else {
+        Channel::from_image(device, queue, path, flip, color_space)
+      };
+      ChannelInput::Texture(loaded.unwrap_or_else(|error| {
+        println!("[Horus] Unable to load {} into iChannel{}: {}", path.display(), index, error);
+        if options.placeholder { Channel::checkerboard(device, queue) } else { Channel::empty(device, queue) }
+      }))
+    }
+    None => ChannelInput::Texture(Channel::empty(device, queue)),
+  }).collect();
+  let mut passes = Passes::new(device, queue, image, &options.sources(), inputs, width, height);
+  // channels repeat and blend their pixels unless they're asked to be read some other way
+  let sampling = options.channels().map(|channel| channel.sampling);
+  if sampling.iter().any(|sampling| *sampling != Sampling::default()) {
+    passes.set_sampling(device, &sampling);
+  }
+  passes
+}
+
+// the buffer the uniforms are written to, bound to group 0 of every pass
+pub struct UniformsBinding {
+  pub buffer: Buffer,
+  // the fingers on a touchscreen, which are bound after the uniforms in a block of their own
+  pub touches: Buffer,
+  pub layout: BindGroupLayout,
+  pub bind_group: BindGroup,
+}
+
+impl UniformsBinding {
+  pub fn new(device: &Device, uniforms: &Uniforms) -> Self {
+    let buffer = device.create_buffer_init(&BufferInitDescriptor {
+      label: None,
+      contents: bytemuck::bytes_of(uniforms),
+      usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    });
+    let touches = device.create_buffer_init(&BufferInitDescriptor {
+      label: Some("touches"),
+      contents: bytemuck::bytes_of(&<Touches as bytemuck::Zeroable>::zeroed()),
+      usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    });
+    // a --vertex shader can read them too
+    let entry = |binding: u32| BindGroupLayoutEntry {
+      binding,
+      visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+      count: None,
+      ty: BindingType::Buffer {
+        ty: BufferBindingType::Uniform,
+        has_dynamic_offset: false,
+        min_binding_size: None,
+      },
+    };
+    let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: None,
+      entries: &[entry(0), entry(1)],
+    });
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+      label: None,
+      layout: &layout,
+      entries: &[
+        BindGroupEntry {
+          binding: 0,
+          resource: buffer.as_entire_binding(),
+        },
+        BindGroupEntry {
+          binding: 1,
+          resource: touches.as_entire_binding(),
+        },
+      ],
+    });
+    UniformsBinding { buffer, touches, layout, bind_group }
+  }
+}
+
+// determines which resources are bound to the pipeline
+pub fn create_pipeline_layout(device: &Device, uniforms: &UniformsBinding, passes: &Passes) -> PipelineLayout {
+  device.create_pipeline_layout(&PipelineLayoutDescriptor {
+    label: None,
+    bind_group_layouts: &[&uniforms.layout, passes.bind_group_layout(), passes.params.bind_group_layout()],
+    push_constant_ranges: &[],
+  })
+}
+
+// render the buffers, unless they're holding still, and then the image into `view`, with the overlay on top if there is one
+// anything the image doesn't cover, like when it hasn't compiled yet, is cleared to `clear`
+// `viewport` is where the whole frame is when `view` only holds a tile of it, so a --vertex shader's geometry lands in the right place
+#[allow(clippy::too_many_arguments)]
+pub fn render_frame(encoder: &mut CommandEncoder, view: &TextureView, multisample: Option<&Multisample>, passes: &Passes, uniforms: &BindGroup, frame: u64, buffers: bool, clear: Color, viewport: Option<[f32; 4]>, text_overlay: Option<&TextOverlay>) {
+  if buffers {
+    passes.render_buffers(encoder, uniforms, frame);
+  }
+  // with --msaa the samples are resolved into `view`, and only the resolved frame needs to be kept
+  let (view, resolve_target) = match multisample {
+    Some(multisample) => (multisample.view(), Some(view)),
+    None => (view, None),
+  };
+  let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+    label: None,
+    color_attachments: &[Some(RenderPassColorAttachment {
+      view,
+      resolve_target,
+      ops: Operations {
+        load: LoadOp::Clear(clear),
+        store: resolve_target.is_none(),
+      },
+    })],
+    depth_stencil_attachment: None,
+  });
+  if let Some(render_pipeline) = &passes.image.pipeline {
+    if let Some([x, y, width, height]) = viewport {
+      render_pass.set_viewport(x, y, width, height, 0., 1.);
+    }
+    render_pass.set_pipeline(render_pipeline);
+    render_pass.set_bind_group(0, uniforms, &[]);
+    render_pass.set_bind_group(1, passes.image_bind_group(frame), &[]);
+    render_pass.set_bind_group(2, passes.params.bind_group(), &[]);
+    render_pass.draw(0..passes.vertices(), 0..1);
+  }
+  if let Some(text_overlay) = text_overlay {
+    text_overlay.draw(&mut render_pass);
+  }
+}
+
+// write the uniforms a frame is drawn with where every pass reads them, along with the fingers on a touchscreen
+pub fn upload_uniforms(queue: &Queue, binding: &UniformsBinding, passes: &Passes, uniforms: &Uniforms, touches: &Touches) {
+  queue.write_buffer(&binding.buffer, 0, bytemuck::bytes_of(uniforms));
+  queue.write_buffer(&binding.touches, 0, bytemuck::bytes_of(touches));
+  passes.upload_uniforms(queue, uniforms);
+}
+
+// ready the uniforms for the frame after the one they were just uploaded for, which only counts as a new frame while time is `advancing`
+pub fn end_frame(uniforms: &mut Uniforms, advancing: bool) {
+  // a click only counts as new on the first frame it's seen
+  uniforms.mouse_click[3] = -uniforms.mouse_click[3].abs();
+  if advancing {
+    uniforms.frame += 1;
+  }
+}
+
+// where a HorusRenderer draws
+pub enum SurfaceTarget<W> {
+  // a window, or anything else wgpu can present to, that's `width` by `height` physical pixels
+  Window { window: W, width: u32, height: u32 },
+  // a texture of the renderer's own that each frame can be read back from, for tests and programs without a window
+  Offscreen { width: u32, height: u32 },
+}
+
+impl SurfaceTarget<NoWindow> {
+  pub fn offscreen(width: u32, height: u32) -> Self {
+    SurfaceTarget::Offscreen { width, height }
+  }
+}
+
+// the window of a renderer that draws offscreen, which there's never one of
+pub enum NoWindow {}
+
+unsafe impl HasRawWindowHandle for NoWindow {
+  fn raw_window_handle(&self) -> RawWindowHandle {
+    match *self {}
+  }
+}
+
+unsafe impl HasRawDisplayHandle for NoWindow {
+  fn raw_display_handle(&self) -> RawDisplayHandle {
+    match *self {}
+  }
+}
+
+enum Target<W> {
+  // the surface is dropped before the window it presents to
+  Window { surface: Surface, window: W, config: SurfaceConfiguration },
+  Offscreen { texture: Texture, width: u32, height: u32 },
+}
+
+// a shader drawn to a window or a texture for programs that want to embed Horus, which sets it up and renders each frame the same way
+// the horus window does, from the buffers, common code and channels `options` asks for
+//
+// let mut renderer = pollster::block_on(HorusRenderer::new(SurfaceTarget::Window { window, width: 700, height: 700 }, source, &Options::default()))?;
+// renderer.update();
+// renderer.render()?;
+pub struct HorusRenderer<W> {
+  pub device: Device,
+  pub queue: Queue,
+  target: Target<W>,
+  format: TextureFormat,
+  vertex_shader: ShaderModule,
+  layout: PipelineLayout,
+  live: LiveChannels,
+  passes: Passes,
+  multisample: Option<Multisample>,
+  msaa: Option<u32>,
+  uniforms: Uniforms,
+  uniforms_binding: UniformsBinding,
+  text_overlay: TextOverlay,
+  clock: Clock,
+  // with --deterministic, time moves by exactly one frame every frame
+  step: Option<f32>,
+  start: DateTime<Local>,
+  frame: u64,
+  mouse_down: bool,
+  touches: Touches,
+}
+
+impl<W: HasRawWindowHandle + HasRawDisplayHandle> HorusRenderer<W> {
+  // draw `source` to `target`, on the backend and GPU `options` picks. Buffers and common code are found next to `options.fragment_path`
+  // if it's given, which is where the source is treated as coming from, and the channels are the ones it asks for
+  pub async fn new(target: SurfaceTarget<W>, source: &str, options: &Options) -> Result<Self, String> {
+    let instance = Instance::new(options.backends());
+    let (window, width, height) = match target {
+      SurfaceTarget::Window { window, width, height } => (Some(window), width.max(1), height.max(1)),
+      SurfaceTarget::Offscreen { width, height } => (None, width.max(1), height.max(1)),
+    };
+    // holding onto the window keeps it alive for as long as the surface presents to it
+    let surface = window.as_ref().map(|window| unsafe { instance.create_surface(window) });
+    let adapter = gpu::request_adapter(&instance, options, surface.as_ref()).await?;
+    let (device, queue) = adapter.request_device(&DeviceDescriptor {
+      features: Features::empty(),
+      limits: Limits::default(),
+      label: None,
+    }, None).await.map_err(|error| error.to_string())?;
+    let (target, format) = match (surface, window) {
+      (Some(surface), Some(window)) => {
+        let config = SurfaceConfiguration {
+          usage: TextureUsages::RENDER_ATTACHMENT,
+          format: gpu::surface_format(&surface, &adapter, options.linear),
+          width,
+          height,
+          present_mode: PresentMode::Fifo,
+          alpha_mode: CompositeAlphaMode::Auto,
+        };
+        surface.configure(&device, &config);
+        let format = config.format;
+        (Target::Window { surface, window, config }, format)
+      }
+      _ => {
+        let format = if options.linear { render::LINEAR_FORMAT } else { render::FORMAT };
+        (Target::Offscreen { texture: create_target(&device, format, width, height), width, height }, format)
+      }
+    };
+
+    let mut live = LiveChannels::new(&device, &options.channel_names(), options.audio.as_deref());
+    let image = Pass::new(options.fragment_path.clone(), source.to_string(), options.shadertoy, format);
+    let mut passes = create_passes(&device, &queue, options, &mut live, image, width, height);
+    let multisample = msaa::create(&device, format, options.msaa, width, height);
+    passes.image.samples = multisample.as_ref().map_or(1, Multisample::samples);
+    let mut uniforms = Uniforms::new(width, height);
+    uniforms.sample_rate = live.sample_rate() as f32;
+    uniforms.set_seed(options.uniform_seed());
+    let mut clock = Clock::new();
+    clock.set_speed(options.speed.unwrap_or(1.));
+    let uniforms_binding = UniformsBinding::new(&device, &uniforms);
+    let vertex_shader = create_vertex_shader(&device);
+    let layout = create_pipeline_layout(&device, &uniforms_binding, &passes);
+    passes.compile(&device, &layout, &vertex_shader, None);
+    let mut text_overlay = TextOverlay::new(&device, format, passes.image.samples);
+    text_overlay.set_text(&device, &queue, &passes.errors());
+    let start = if options.deterministic { clock::fixed_start() } else { Local::now() };
+    let step = options.deterministic.then_some(clock::FRAME_STEP);
+    Ok(HorusRenderer { device, queue, target, format, vertex_shader, layout, live, passes, multisample, msaa: options.msaa, uniforms, uniforms_binding, text_overlay, clock, step, start, frame: 0, mouse_down: false, touches: bytemuck::Zeroable::zeroed() })
+  }
+}
+
+impl<W> HorusRenderer<W> {
+  // the window it draws to, unless it draws offscreen
+  pub fn window(&self) -> Option<&W> {
+    match &self.target {
+      Target::Window { window, .. } => Some(window),
+      Target::Offscreen { .. } => None,
+    }
+  }
+
+  // replace the shader, returning why it didn't compile if it doesn't, in which case the last one that did keeps rendering
+  pub fn set_source(&mut self, source: &str) -> Result<(), String> {
+    self.passes.image.set_source(source.to_string());
+    if self.passes.compile(&self.device, &self.layout, &self.vertex_shader, None) {
+      self.uniforms.frame = 0;
+    }
+    let errors = self.passes.errors();
+    self.text_overlay.set_text(&self.device, &self.queue, &errors);
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+  }
+
+  // match the target to a window that's now `width` by `height` physical pixels
+  pub fn resize(&mut self, width: u32, height: u32) {
+    if width == 0 || height == 0 {
+      return;
+    }
+    match &mut self.target {
+      Target::Window { surface, config, .. } => {
+        config.width = width;
+        config.height = height;
+        surface.configure(&self.device, config);
+      }
+      Target::Offscreen { texture, width: target_width, height: target_height } => {
+        *texture = create_target(&self.device, self.format, width, height);
+        (*target_width, *target_height) = (width, height);
+      }
+    }
+    self.multisample = msaa::create(&self.device, self.format, self.msaa, width, height);
+    self.uniforms.resolution = [width as _, height as _];
+    self.uniforms.frame = 0;
+    self.passes.resize(&self.device, width, height);
+  }
+
+  // where the cursor is in pixels from the top left corner, and whether the left button is held
+  pub fn set_mouse(&mut self, x: f32, y: f32, pressed: bool) {
+    self.uniforms.mouse = [x, y];
+    match (pressed, self.mouse_down) {
+      (true, false) => self.uniforms.mouse_click = [x, y, x, y],
+      (true, true) => self.uniforms.mouse_click[..2].copy_from_slice(&[x, y]),
+      (false, true) => self.uniforms.mouse_click[2] = -self.uniforms.mouse_click[2].abs(),
+      (false, false) => {}
+    }
+    self.mouse_down = pressed;
+  }
+
+  // the mouse wheel scrolled `x` notches across and `y` notches up
+  pub fn scroll(&mut self, x: f32, y: f32) {
+    self.uniforms.scroll = [self.uniforms.scroll[0] + x, self.uniforms.scroll[1] + y];
+  }
+
+  // the fingers on a touchscreen, which a touch::TouchScreen can follow from winit's touch events
+  pub fn set_touches(&mut self, touches: Touches) {
+    self.touches = touches;
+  }
+
+  pub fn set_paused(&mut self, paused: bool) {
+    self.clock.set_paused(paused);
+  }
+
+  // advance time to now, ready for the next frame to be rendered
+  pub fn update(&mut self) {
+    let now = self.clock.tick(self.step);
+    self.uniforms.set_time(now, self.start);
+    // the timeline has the last say over any uniform it animates
+    self.passes.animate(now);
+    self.passes.params.upload(&self.queue);
+    self.live.update(&self.queue, now);
+    [self.uniforms.beat, self.uniforms.bpm] = self.live.beat();
+  }
+
+  // draw a frame at the current time, and present it if it's drawn to a window
+  pub fn render(&mut self) -> Result<(), String> {
+    let (output, view) = match &self.target {
+      Target::Window { surface, .. } => {
+        let output = surface.get_current_texture().map_err(|error| error.to_string())?;
+        let view = output.texture.create_view(&TextureViewDescriptor::default());
+        (Some(output), view)
+      }
+      Target::Offscreen { texture, .. } => (None, texture.create_view(&TextureViewDescriptor::default())),
+    };
+    upload_uniforms(&self.queue, &self.uniforms_binding, &self.passes, &self.uniforms, &self.touches);
+    self.live.end_frame();
+    let mut encoder = self.device.create_command_encoder(&CommandEncoderDescriptor { label: None });
+    render_frame(&mut encoder, &view, self.multisample.as_ref(), &self.passes, &self.uniforms_binding.bind_group, self.frame, true, Color::BLACK, None, Some(&self.text_overlay));
+    self.queue.submit(std::iter::once(encoder.finish()));
+    if let Some(output) = output {
+      output.present();
+    }
+    end_frame(&mut self.uniforms, true);
+    self.frame += 1;
+    Ok(())
+  }
+
+  // the frame that was rendered last, which is only kept when it's drawn offscreen
+  pub fn read_frame(&self) -> Result<RgbaImage, String> {
+    let Target::Offscreen { texture, width, height } = &self.target else {
+      return Err("frames presented to a window aren't kept to be read back".to_string());
+    };
+    let mut encoder = self.device.create_command_encoder(&CommandEncoderDescriptor { label: None });
+    let readback = Readback::new(&self.device, &mut encoder, texture, *width, *height, self.format)?;
+    self.queue.submit(std::iter::once(encoder.finish()));
+    readback.into_image(&self.device)
+  }
+}
+
+// the texture an offscreen renderer draws into, which frames are copied out of
+fn create_target(device: &Device, format: TextureFormat, width: u32, height: u32) -> Texture {
+  device.create_texture(&TextureDescriptor {
+    label: Some("renderer target"),
+    size: Extent3d { width, height, depth_or_array_layers: 1 },
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: TextureDimension::D2,
+    format,
+    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+  })
+}
@@ -0,0 +1,216 @@
+// channels that Horus fills in itself every frame instead of loading from a file
+
+use std::path::Path;
+use wgpu::{Device, Queue};
+
+#[cfg(feature = "audio")]
+use crate::audio::{AudioFile, Microphone};
+#[cfg(feature = "gamepad")]
+use crate::gamepad::Gamepads;
+#[cfg(feature = "webcam")]
+use crate::webcam::Webcam;
+use crate::{channel::Channel, keyboard::{self, Keyboard}};
+
+// passed to --channel0 through --channel3 instead of a path to listen to the default input device
+pub const MICROPHONE: &str = "microphone";
+// passed instead of a path to read the file given to --audio
+pub const AUDIO: &str = "audio";
+// passed instead of a path to show the default camera's feed
+pub const WEBCAM: &str = "webcam";
+// passed instead of a path to read the sticks and buttons of connected gamepads
+pub const GAMEPAD: &str = "gamepad";
+
+// shadertoy's sample rate when nothing is playing
+const DEFAULT_SAMPLE_RATE: u32 = 44100;
+
+pub struct LiveChannels {
+  keyboard: Keyboard,
+  // only listened to if a channel asks for it, along with why it couldn't be if it wasn't
+  #[cfg(feature = "audio")]
+  microphone: Option<Result<Microphone, String>>,
+  #[cfg(feature = "audio")]
+  audio: Option<Result<AudioFile, String>>,
+  // whether the audio is a sound shader's rather than a file's
+  #[cfg(feature = "audio")]
+  sound: bool,
+  #[cfg(feature = "webcam")]
+  webcam: Option<Result<Webcam, String>>,
+  #[cfg(feature = "gamepad")]
+  gamepads: Option<Result<Gamepads, String>>,
+}
+
+impl LiveChannels {
+  // start listening to whichever devices the channels named `names` read from, and decode `audio` if there is one
+  #[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+  pub fn new(device: &Device, names: &[&str], audio: Option<&Path>) -> Self {
+    #[cfg(not(feature = "audio"))]
+    if audio.is_some() {
+      println!("[Horus] Unable to play audio since Horus was built without the audio feature");
+    }
+    LiveChannels {
+      keyboard: Keyboard::new(device),
+      #[cfg(feature = "audio")]
+      microphone: names.contains(&MICROPHONE).then(|| Microphone::start(device)),
+      #[cfg(feature = "audio")]
+      audio: audio.map(|path| AudioFile::load(device, path).map_err(|error| {
+        println!("[Horus] Unable to load {}: {}", path.display(), error);
+        error
+      })),
+      #[cfg(feature = "audio")]
+      sound: false,
+      #[cfg(feature = "webcam")]
+      webcam: names.contains(&WEBCAM).then(|| Webcam::start(device)),
+      #[cfg(feature = "gamepad")]
+      gamepads: names.contains(&GAMEPAD).then(|| Gamepads::start(device)),
+    }
+  }
+
+  // the channel called `name`, if it's one of the live ones, for a shadertoy shader if `shadertoy` is set
+  #[cfg_attr(not(feature = "webcam"), allow(unused_variables))]
+  pub fn channel(&mut self, device: &Device, name: &str, shadertoy: bool) -> Option<Result<Channel, String>> {
+    match name {
+      keyboard::NAME => Some(Ok(self.keyboard.channel(device))),
+      #[cfg(feature = "audio")]
+      MICROPHONE => self.microphone.as_ref().map(|microphone| microphone.as_ref().map(|microphone| microphone.channel(device)).map_err(Clone::clone)),
+      #[cfg(feature = "audio")]
+      AUDIO => Some(match &self.audio {
+        Some(Ok(audio)) => Ok(audio.channel(device)),
+        Some(Err(error)) => Err(error.clone()),
+        None => Err("there's no file to read, pass one to --audio".to_string()),
+      }),
+      #[cfg(not(feature = "audio"))]
+      MICROPHONE | AUDIO => Some(Err("Horus was built without the audio feature".to_string())),
+      #[cfg(feature = "webcam")]
+      WEBCAM => self.webcam.as_mut().map(|webcam| webcam.as_mut().map(|webcam| webcam.channel(device, shadertoy)).map_err(|error| error.clone())),
+      #[cfg(not(feature = "webcam"))]
+      WEBCAM => Some(Err("Horus was built without the webcam feature".to_string())),
+      #[cfg(feature = "gamepad")]
+      GAMEPAD => self.gamepads.as_ref().map(|gamepads| gamepads.as_ref().map(|gamepads| gamepads.channel(device)).map_err(Clone::clone)),
+      #[cfg(not(feature = "gamepad"))]
+      GAMEPAD => Some(Err("Horus was built without the gamepad feature".to_string())),
+      _ => None,
+    }
+  }
+
+  // have the audio channel read the sound shader if `sound` is set and there's no file to play, which is silent until its samples
+  // are rendered, or stop playing the last shader's sound if it isn't
+  #[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+  pub fn prepare_sound(&mut self, device: &Device, sound: bool) {
+    #[cfg(feature = "audio")]
+    match (sound, &self.audio) {
+      (true, None) => {
+        self.audio = Some(Ok(AudioFile::from_samples(device, Vec::new(), 2, DEFAULT_SAMPLE_RATE)));
+        self.sound = true;
+      }
+      (false, _) if self.sound => {
+        self.audio = None;
+        self.sound = false;
+      }
+      _ => {}
+    }
+  }
+
+  // play `samples` from the sound shader, interleaved left and right, in place of an audio file
+  #[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+  pub fn set_sound(&mut self, samples: Vec<f32>, sample_rate: u32) {
+    #[cfg(feature = "audio")]
+    if let (true, Some(Ok(audio))) = (self.sound, &mut self.audio) {
+      audio.set_samples(samples, 2, sample_rate);
+    }
+  }
+
+  pub fn keyboard(&mut self) -> &mut Keyboard {
+    &mut self.keyboard
+  }
+
+  // send this frame's input to the textures, with the audio file's texture showing `time` seconds in
+  #[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+  pub fn update(&mut self, queue: &Queue, time: f32) {
+    self.keyboard.upload(queue);
+    #[cfg(feature = "audio")]
+    if let Some(Ok(microphone)) = &mut self.microphone {
+      microphone.update(queue, time);
+    }
+    #[cfg(feature = "audio")]
+    if let Some(Ok(audio)) = &mut self.audio {
+      audio.update(queue, time);
+    }
+    #[cfg(feature = "webcam")]
+    if let Some(Ok(webcam)) = &mut self.webcam {
+      webcam.update(queue);
+    }
+    #[cfg(feature = "gamepad")]
+    if let Some(Ok(gamepads)) = &mut self.gamepads {
+      gamepads.update(queue);
+    }
+  }
+
+  // start playing the audio file out loud from the beginning, if there is one
+  pub fn play_audio(&mut self) {
+    #[cfg(feature = "audio")]
+    if let Some(Ok(audio)) = &mut self.audio {
+      if let Err(error) = audio.play() {
+        println!("[Horus] Unable to play audio: {}", error);
+      }
+    }
+  }
+
+  #[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+  pub fn pause_audio(&self, paused: bool) {
+    #[cfg(feature = "audio")]
+    if let Some(Ok(audio)) = &self.audio {
+      audio.set_paused(paused);
+    }
+  }
+
+  #[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+  pub fn set_audio_speed(&self, speed: f32) {
+    #[cfg(feature = "audio")]
+    if let Some(Ok(audio)) = &self.audio {
+      audio.set_speed(speed);
+    }
+  }
+
+  #[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+  pub fn seek_audio(&self, time: f32) {
+    #[cfg(feature = "audio")]
+    if let Some(Ok(audio)) = &self.audio {
+      audio.seek(time);
+    }
+  }
+
+  // how far the audio file has played, which time follows so the two stay in sync
+  pub fn audio_position(&self) -> Option<f32> {
+    #[cfg(feature = "audio")]
+    if let Some(Ok(audio)) = &self.audio {
+      return audio.position();
+    }
+    None
+  }
+
+  pub fn sample_rate(&self) -> u32 {
+    #[cfg(feature = "audio")]
+    if let Some(Ok(audio)) = &self.audio {
+      return audio.sample_rate;
+    }
+    DEFAULT_SAMPLE_RATE
+  }
+
+  // the pulse of the latest beat and the tempo in beats per minute, heard from the microphone if it's listening or the audio file
+  // otherwise, which are both 0 without either of them
+  pub fn beat(&self) -> [f32; 2] {
+    #[cfg(feature = "audio")]
+    if let Some(Ok(microphone)) = &self.microphone {
+      return microphone.beat();
+    }
+    #[cfg(feature = "audio")]
+    if let Some(Ok(audio)) = &self.audio {
+      return audio.beat();
+    }
+    [0.; 2]
+  }
+
+  pub fn end_frame(&mut self) {
+    self.keyboard.end_frame();
+  }
+}
@@ -1,223 +1,556 @@
 use std::{
-    env::args,
-    fs::{File, read_to_string},
-    io::Write,
+    ffi::OsString,
+    net::Ipv4Addr,
+    path::{Path, PathBuf},
     time::Instant,
 };
-use wgpu::{Backends, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, BufferBindingType, BufferUsages, Color, CommandEncoderDescriptor, CompositeAlphaMode, Device, DeviceDescriptor, Features, FragmentState, Instance, Limits, LoadOp, MultisampleState, Operations, PipelineLayoutDescriptor, PowerPreference, PresentMode, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, RequestAdapterOptions, ShaderModuleDescriptor, ShaderSource, ShaderStages, Surface, SurfaceConfiguration, TextureUsages, TextureViewDescriptor, util::{BufferInitDescriptor, DeviceExt}, VertexState};
+use chrono::Local;
+use clap::Parser;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use wgpu::{Color, CommandEncoderDescriptor, CompositeAlphaMode, Device, DeviceDescriptor, Instance, Limits, PresentMode, Queue, Surface, SurfaceConfiguration, TextureUsages, TextureViewDescriptor};
 use winit::{
     event::*,
-    event_loop,
-    window::WindowBuilder,
+    event_loop::{self, EventLoopBuilder, EventLoopProxy},
+    window::{Fullscreen, Window, WindowBuilder},
 };
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-struct Uniforms {
-  mouse: [f32; 2],
-  resolution: [f32; 2],
-  time: f32,
-  padding: f32,
-}
+use horus::{bundle, capture::{self, Readback, Recorder}, channel::{self, Channel, ColorSpace}, check, cli::{Cli, Command, Options}, clock::{self, Clock}, config::Config, create_pipeline_layout, create_vertex_shader, end_frame, golden, gpu, import, keyboard, live::LiveChannels, load_passes, metadata::Metadata, midi::Midi, monitor, msaa::{self, Multisample}, ndi::NdiSender, osc::Osc, overlay::TextOverlay, panel::{Action, Panel, Status, MAX_SPEED, MIN_SPEED}, pass::{Passes, BUFFER_NAMES}, playlist::{self, Playlist}, project, projection::Projection, remote::{Remote, RemoteCommand}, render, render_frame, replay::{InputFrame, InputRecorder, Replay}, scale::Canvas, screensaver::{self, Request, Screensaver}, sound, spout::SpoutSender, stats::FrameRate, syphon::SyphonServer, template::Template, timer::GpuTimer, touch::{MouseTouch, TouchScreen}, upload_uniforms, wallpaper, Uniforms, UniformsBinding};
 
-const VERTEX_SOURCE: &str = "\
-struct VertexInput {
-    @builtin(vertex_index) vertex_index: u32,
-};
-struct VertexOutput {
-    @builtin(position) position: vec4<f32>,
-};
-@vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
-    // a single triangle that covers the entire surface
-    var vertices = array<vec2<f32>, 3>(
-        vec2<f32>(-1., 1.),
-        vec2<f32>(3., 1.),
-        vec2<f32>(-1., -3.),
-    );
-    var out: VertexOutput;
-    out.position = vec4<f32>(vertices[in.vertex_index], 0., 1.);
-    return out;
-}\
-";
-
-const FRAGMENT_SOURCE: &str = "\
-struct VertexOutput {
-    @builtin(position) position: vec4<f32>,
-};
 
-struct Uniforms {
-    mouse: vec2<f32>,
-    resolution: vec2<f32>,
-    time: f32,
-};
+// how many seconds each shader of a directory runs for without --cycle
+const DEFAULT_CYCLE: f32 = 30.;
 
-@group(0) @binding(0)
-var<uniform> uniforms: Uniforms;
+// the longest two clicks can be apart to count as a double click
+const DOUBLE_CLICK: std::time::Duration = std::time::Duration::from_millis(400);
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    // Horus uses the same uniforms as thebookofshaders.com and shadertoy.com
-    // `mouse` and `resolution` are in pixel coordinates
-    // `time` is the number of seconds since program start
-    let rg = in.position.xy / uniforms.resolution;
-    return vec4<f32>(rg, 0., 1.);
-}\
-";
+// how long messages like which channel a dropped image was bound to stay over the frame
+const TOAST: std::time::Duration = std::time::Duration::from_secs(3);
 
-fn main() {
-  if args().len() > 1 && args().nth(1).unwrap().contains("-c") {
-    let mut name = args().nth(2).unwrap_or("fragment".to_string());
-    name.push_str(".wgsl");
+// how often --static wakes up to check for anything that came in without an event, like MIDI, OSC and remote commands
+const STILL_POLL: std::time::Duration = std::time::Duration::from_millis(50);
 
-    println!("[Horus] Created {}", name);
-    let mut file = File::create(name).expect("Unable to create a new shader file.");
+// how much each notch of the mouse wheel zooms the magnifier in or out
+const ZOOM_STEP: f32 = 1.25;
 
-    file.write_all(FRAGMENT_SOURCE.as_ref()).expect("Unable to write to new shader.");
+// how many pixels of scrolling on a touchpad count as one notch of a mouse wheel
+const PIXELS_PER_LINE: f32 = 50.;
 
-    return;
-  } else if args().len() <= 1 {
-    println!("[Horus] Use \"-c filename\" to create a new shader\n[Horus] Pass in an existing shader to run it");
-  }
-  pollster::block_on(run());
+// events sent to the event loop from outside of winit
+#[derive(Debug)]
+enum HorusEvent {
+  // one of the shaders was modified on disk
+  ShaderChanged(PathBuf),
 }
 
-async fn run() {
+
+fn main() {
   env_logger::init();
+  let mut args: Vec<OsString> = std::env::args_os().collect();
+  // Windows runs screensavers with arguments of its own
+  match screensaver::request(&args) {
+    Some(Request::Configure) => return screensaver::configure(),
+    Some(request) => args = screensaver::arguments(args[0].clone(), &request),
+    None => {}
+  }
+  let cli = Cli::parse_from(args);
+  // running a shader doesn't need its own subcommand
+  let mut command = cli.command.unwrap_or(Command::Run(cli.options));
+  // a bundled executable runs the project it carries whenever it isn't given a shader of its own
+  match bundle::extract() {
+    Ok(Some(directory)) => if let Command::Run(options) | Command::Render { options, .. } | Command::Check(options) = &mut command {
+      options.fragment_path.get_or_insert(directory);
+    },
+    Ok(None) => {}
+    Err(error) => {
+      println!("[Horus] {}", error);
+      bundle::clean_up();
+      std::process::exit(1);
+    }
+  }
+  let result = match command {
+    Command::Bundle { path, output } => bundle::create(&path, &output),
+    Command::New { name, shader_only: false, template } => project::create(&name, template),
+    Command::New { name, shader_only: true, template } => create_shader(name, template),
+    Command::Import { shader, name, key } => import::import(&shader, name.as_deref(), key),
+    Command::Render { mut options, mut render } => Config::apply(&mut options).and_then(|config| {
+      if let Some(config) = config {
+        config.apply_render(&mut render);
+      }
+      Metadata::apply(&mut options)?.apply_render(&mut render);
+      pollster::block_on(render::render(options, render))
+    }),
+    Command::Test(test) => pollster::block_on(golden::test(test)),
+    Command::Check(mut options) => Config::apply(&mut options).and_then(|_| Metadata::apply(&mut options)).and_then(|_| check::check(options)),
+    Command::Run(mut options) => Config::apply(&mut options).and_then(|_| {
+      let playlist = match &options.fragment_path {
+        Some(path) if path.is_dir() => Some(Playlist::find(path, options.cycle.unwrap_or(DEFAULT_CYCLE), options.shuffle)?),
+        _ => None,
+      };
+      if let Some(playlist) = &playlist {
+        options.fragment_path = Some(playlist.current().to_path_buf());
+      }
+      Metadata::apply(&mut options)?;
+      if options.fragment_path.is_none() {
+        println!("[Horus] Use \"horus new name\" to create a new project\n[Horus] Pass in an existing shader or drop one onto the window to run it, or see \"horus --help\" for everything else");
+      }
+      pollster::block_on(run(options, playlist))
+    }),
+  };
+  bundle::clean_up();
+  if let Err(error) = result {
+    println!("[Horus] {}", error);
+    std::process::exit(1);
+  }
+}
 
+// write the starter shader to `name`, refusing to replace a shader that's already there
+fn create_shader(mut name: PathBuf, template: Template) -> Result<(), String> {
+  if name.extension().is_none() {
+    name.set_extension("wgsl");
+  }
+  project::create_file(&name, template.shader())?;
+  println!("[Horus] Created {}", name.display());
+  // buffers are found next to the shader, and the channels need to be passed in since there's no horus.toml
+  if let Some(buffer) = template.buffer() {
+    let path = name.with_file_name(BUFFER_NAMES[0]).with_extension("wgsl");
+    project::create_file(&path, buffer)?;
+    println!("[Horus] Created {}", path.display());
+  }
+  if let Some(channel) = template.channel0() {
+    println!("[Horus] Run it with \"horus {} --channel0 {}\"", name.display(), channel);
+  }
+  Ok(())
+}
+
+async fn run(mut options: Options, mut playlist: Option<Playlist>) -> Result<(), String> {
   // context for retrieving events from the system
-  let event_loop = event_loop::EventLoop::new();
+  let event_loop = EventLoopBuilder::<HorusEvent>::with_user_event().build();
+
+  let mut title = window_title(&options);
+
+  // a screensaver fills the screen, unless it's spanning every monitor or shown in the preview of Windows' settings
+  if options.screensaver && !options.span && !options.wallpaper && options.preview.is_none() {
+    options.fullscreen = true;
+  }
 
   // register a new window within the context
-  let window = WindowBuilder::new()
-    .with_title("Horus")
-    .with_inner_size(winit::dpi::LogicalSize::new(700.0, 700.0))
-    .build(&event_loop).unwrap();
+  let monitor = options.monitor.as_deref().map(|monitor| monitor::find(&event_loop, monitor)).transpose()?;
+  let mut builder = WindowBuilder::new()
+    .with_title(&title)
+    .with_inner_size(winit::dpi::LogicalSize::<u32>::from(options.size.unwrap_or((700, 700))))
+    .with_always_on_top(options.on_top)
+    .with_decorations(!options.borderless)
+    .with_transparent(options.transparent);
+  // fullscreen only ever covers a single monitor, so spanning all of them is a borderless window over every one instead
+  if options.span {
+    let (position, size) = monitor::span(&event_loop).ok_or("Unable to find any monitors to span")?;
+    builder = builder.with_position(position).with_inner_size(size).with_decorations(false);
+  } else if options.wallpaper {
+    let covered = monitor.or_else(|| event_loop.primary_monitor()).or_else(|| event_loop.available_monitors().next()).ok_or("Unable to find a monitor to put the wallpaper on")?;
+    builder = builder.with_position(covered.position()).with_inner_size(covered.size());
+  } else {
+    if let Some(monitor) = &monitor {
+      builder = builder.with_position(monitor.position());
+    }
+    builder = builder.with_fullscreen(options.fullscreen.then_some(Fullscreen::Borderless(monitor)));
+  }
+  // a wallpaper can span every monitor too
+  if options.wallpaper {
+    builder = wallpaper::prepare(builder);
+  }
+  if let Some(parent) = options.preview {
+    builder = screensaver::preview(builder, parent)?;
+  }
+  let window = builder.build(&event_loop).unwrap();
+  // the preview is only ever looked at, so it's left alone by the mouse and keyboard
+  let mut screensaver = (options.screensaver && options.preview.is_none()).then(Screensaver::default);
+  if screensaver.is_some() {
+    window.set_cursor_visible(false);
+  }
+  if options.wallpaper {
+    match wallpaper::attach(&window) {
+      Ok(()) => println!("[Horus] Running as the wallpaper"),
+      Err(error) => println!("[Horus] Unable to put the window behind the desktop, since {}", error),
+    }
+  }
   let size = window.inner_size();
 
   // wgpu
-  let instance = Instance::new(Backends::all());
+  let instance = Instance::new(options.backends());
 
   // winit window -> wgpu window
   let mut surface = unsafe { instance.create_surface(&window) };
 
   // graphics card
-  let adapter = instance.request_adapter(
-    &RequestAdapterOptions {
-      power_preference: PowerPreference::default(),
-      compatible_surface: Some(&surface),
-      force_fallback_adapter: false,
-    },
-  ).await.unwrap();
+  let adapter = gpu::request_adapter(&instance, &options, Some(&surface)).await?;
 
   // device - logical representation of the graphics card
   // queue - how we assign work to the device
   let (device, queue) = adapter.request_device(
     &DeviceDescriptor {
-      features: Features::empty(),
+      // timestamp queries let the stats overlay show how long the GPU spends on each frame
+      features: GpuTimer::features(adapter.features()),
       limits: Limits::default(),
       label: None,
     },
     None,
   ).await.unwrap();
+  let mut gpu_timer = GpuTimer::new(&device, &queue);
+
+  // transparent windows show the desktop wherever the shader's alpha is below 1, expecting its colors to be multiplied by alpha
+  let alpha_mode = match options.transparent {
+    false => CompositeAlphaMode::Auto,
+    true => {
+      let supported = surface.get_supported_alpha_modes(&adapter);
+      [CompositeAlphaMode::PreMultiplied, CompositeAlphaMode::PostMultiplied, CompositeAlphaMode::Inherit].into_iter().find(|mode| supported.contains(mode)).unwrap_or_else(|| {
+        println!("[Horus] Unable to make the window transparent on this platform");
+        CompositeAlphaMode::Auto
+      })
+    }
+  };
+  let clear = if options.transparent || options.capture_alpha { Color::TRANSPARENT } else { Color::BLACK };
+
+  // fifo is vsync, which every platform supports, so it's what the others fall back to
+  let present = options.present.unwrap_or_default();
+  let present_mode = if surface.get_supported_present_modes(&adapter).contains(&present.mode()) {
+    present.mode()
+  } else {
+    println!("[Horus] Unable to present with {:?} on this platform, so frames wait for vsync instead", present.mode());
+    PresentMode::Fifo
+  };
 
   // configure the surface
   let mut config = SurfaceConfiguration {
-    usage: TextureUsages::RENDER_ATTACHMENT,
-    format: surface.get_supported_formats(&adapter)[0],
+    // frames are copied out of the surface to take screenshots
+    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+    format: gpu::surface_format(&surface, &adapter, options.linear),
     width: size.width,
     height: size.height,
-    present_mode: PresentMode::Fifo, // basically vsync
-    alpha_mode: CompositeAlphaMode::Auto,
+    present_mode,
+    alpha_mode,
   };
   surface.configure(&device, &config);
 
-  // vertex shader
-  let vertex_shader = device.create_shader_module(ShaderModuleDescriptor {
-    label: None,
-    source: ShaderSource::Wgsl(std::borrow::Cow::Borrowed(&VERTEX_SOURCE)),
+  let vertex_shader = create_vertex_shader(&device);
+
+  // the fragment shader along with any buffers, common code and channels it reads from
+  let mut live = LiveChannels::new(&device, &options.channel_names(), options.audio.as_deref());
+  let reads_keyboard = options.channel_names().contains(&keyboard::NAME);
+  // with --scale the shader renders at a different resolution to the window, which it's stretched over
+  // with --capture-alpha it renders into a canvas too, which screenshots are read from since the surface's alpha is only for the compositor,
+  // and with --hdr into one of half floats that's tonemapped into the window
+  let canvas_scale = options.scale.filter(|scale| *scale != 1.).or((options.capture_alpha || options.tonemap().is_some()).then_some(1.));
+  let mut canvas = canvas_scale.map(|scale| Canvas::new(&device, config.format, scale, size.width, size.height, options.tonemap()));
+  let image_format = canvas.as_ref().map_or(config.format, Canvas::format);
+  // with --projection it renders into a cubemap instead, which is warped into the window or the canvas
+  let mut projection = options.projection.map(|kind| {
+    let (width, height) = canvas.as_ref().map_or((size.width, size.height), Canvas::size);
+    Projection::new(&device, image_format, kind, width, height)
   });
+  let (width, height) = shader_size(&config, canvas.as_ref(), projection.as_ref());
+  let mut passes = load_passes(&device, &queue, &options, &mut live, image_format, width, height)?;
+  // with --msaa the image pass renders several samples per pixel, which are resolved into whatever it's drawn to
+  let mut multisample = msaa::create(&device, image_format, options.msaa, width, height);
+  passes.image.samples = multisample.as_ref().map_or(1, Multisample::samples);
+  let mut frame: u64 = 0;
+
+  // uniform data to be sent to the shaders
+  let mut uniforms = Uniforms::new(width, height);
+  uniforms.sample_rate = live.sample_rate() as f32;
+  uniforms.set_seed(options.uniform_seed());
+  let mut mouse_down = false;
+  let mut touch_screen = TouchScreen::default();
+  let mut clock = Clock::new();
+  clock.set_speed(options.speed.unwrap_or(1.));
+  let start = if options.deterministic { clock::fixed_start() } else { Local::now() };
+  let uniforms_binding = UniformsBinding::new(&device, &uniforms);
 
-  // fragment shader
-  let mut fragment_source = FRAGMENT_SOURCE.to_string();
-  if args().len() > 1 {
-    let fragment_path = args().nth(1).unwrap();
-    println!("[Horus] Running {}", fragment_path);
-    fragment_source = read_to_string(&fragment_path).unwrap();
+  // determines which resources are bound to the pipeline
+  let mut render_pipeline_layout = create_pipeline_layout(&device, &uniforms_binding, &passes);
+
+  // represents all stages of the rendering process, with compilation errors shown in the window
+  // the text overlay is drawn along with the image, unless the image is being stretched or warped over the window
+  let mut text_overlay = TextOverlay::new(&device, config.format, if canvas.is_some() || projection.is_some() { 1 } else { passes.image.samples });
+  let mut panel = Panel::new(&event_loop, &window, &device, config.format);
+  let mut midi = Midi::new();
+  let mut osc = options.osc.and_then(|port| Osc::listen(port).map_err(|error| println!("[Horus] Unable to listen for OSC messages on port {}: {}", port, error)).ok());
+  let remote = Remote::new();
+  if let Some(port) = options.remote {
+    if let Err(error) = remote.listen(options.remote_host.unwrap_or(Ipv4Addr::LOCALHOST.into()), port) {
+      println!("[Horus] Unable to listen for WebSocket commands on port {}: {}", port, error);
+    }
   }
-  let fragment_shader = device.create_shader_module(ShaderModuleDescriptor {
-    label: None,
-    source: ShaderSource::Wgsl(std::borrow::Cow::Borrowed(&fragment_source)),
+  if options.stdin {
+    remote.read_stdin();
+  }
+  passes.compile(&device, &render_pipeline_layout, &vertex_shader, None);
+  text_overlay.set_text(&device, &queue, &passes.errors());
+  // the sound starts playing along with an audio file would, further down
+  update_sound(&device, &queue, &mut passes, &mut live, &options, &clock, 0., false);
+
+  // rebuild the pipelines whenever one of the shaders is saved
+  let proxy = event_loop.create_proxy();
+  let mut watched = passes.paths();
+  let mut watcher = watch(&watched, proxy.clone());
+  let mut changed_paths: Vec<PathBuf> = Vec::new();
+  // the shader a playlist is moving on to, which is loaded at the start of the next frame
+  let mut next_shader: Option<PathBuf> = None;
+  // when the message over the frame goes back to showing only the errors
+  let mut toast: Option<Instant> = None;
+
+  // F12 or S saves the next frame as a PNG, though S is left alone for shaders that read the keyboard
+  let mut screenshot_requested = false;
+  // Ctrl clicking reads back the pixel under the cursor, in the window's pixels, once the next frame has been drawn
+  let mut inspect_requested: Option<[f32; 2]> = None;
+  // where the cursor is in the window's pixels
+  let mut cursor = [0.; 2];
+  // Z draws the window through a magnifier that the mouse wheel zooms and dragging pans, instead of them reaching the shader
+  let mut magnifying = false;
+  let mut panning = false;
+  let keeps_canvas = canvas.is_some();
+  // keys that control time, waiting to be carried out along with the panel's buttons at the start of the next frame
+  let mut actions: Vec<Action> = Vec::new();
+  let mut frame_rate = FrameRate::new();
+  let scrub_step = options.scrub_step.unwrap_or(0.1);
+  let mut modifiers = ModifiersState::empty();
+  // a second click this soon after the last one is a double click, which toggles fullscreen
+  let mut last_click: Option<Instant> = None;
+  // with --max-fps, the event loop sleeps until it's time for the next frame instead of drawing as fast as it can
+  let frame_interval = options.max_fps.map(|fps| std::time::Duration::from_secs_f64(1. / fps as f64));
+  let mut next_frame = Instant::now();
+  // --duration closes the window once it's been open that long, however the shader's time has been paused or sped up
+  let closes_at = options.duration.map(|seconds| Instant::now() + std::time::Duration::from_secs_f32(seconds));
+
+  // frames are recorded at whatever size the window was when Horus started
+  let mut recorder = options.record().as_deref().and_then(|path| match Recorder::start(path, config.width, config.height, options.seconds) {
+    Ok(recorder) => {
+      println!("[Horus] Recording {}", path.display());
+      Some(recorder)
+    }
+    Err(error) => {
+      println!("[Horus] Unable to record {}: {}", path.display(), error);
+      None
+    }
   });
 
-  // uniform data to be sent to the shaders
-  let mut uniforms = Uniforms { mouse: [0., 0.], resolution: [size.width.clone() as _, size.height.clone() as _], time: 0., padding: 0. };
-  let time = Instant::now();
-  let uniforms_buffer = device.create_buffer_init(&BufferInitDescriptor {
-    label: None,
-    contents: bytemuck::bytes_of(&uniforms),
-    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+  // NDI is sent each frame as it's shown, at the rate the window is drawn at
+  let mut ndi = options.ndi.as_deref().and_then(|name| match NdiSender::new(name, options.max_fps.unwrap_or(60)) {
+    Ok(sender) => {
+      println!("[Horus] Sending {} over NDI", name);
+      Some(sender)
+    }
+    Err(error) => {
+      println!("[Horus] Unable to send {} over NDI: {}", name, error);
+      None
+    }
   });
-  let uniforms_buffer_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-    label: None,
-    entries: &[BindGroupLayoutEntry {
-      binding: 0,
-      visibility: ShaderStages::FRAGMENT,
-      count: None,
-      ty: BindingType::Buffer {
-        ty: BufferBindingType::Uniform,
-        has_dynamic_offset: false,
-        min_binding_size: None,
-      },
-    }],
+  // Spout and Syphon share each frame with other programs on the same computer
+  let mut spout = options.spout.as_deref().and_then(|name| match SpoutSender::new(name) {
+    Ok(sender) => {
+      println!("[Horus] Sharing {} over Spout", name);
+      Some(sender)
+    }
+    Err(error) => {
+      println!("[Horus] Unable to share {} over Spout: {}", name, error);
+      None
+    }
   });
-  let uniforms_buffer_bind_group = device.create_bind_group(&BindGroupDescriptor {
-    label: None,
-    layout: &uniforms_buffer_layout,
-    entries: &[BindGroupEntry {
-      binding: 0,
-      resource: uniforms_buffer.as_entire_binding(),
-    }],
+  let mut syphon = options.syphon.as_deref().and_then(|name| match SyphonServer::new(name) {
+    Ok(server) => {
+      println!("[Horus] Publishing {} over Syphon", name);
+      Some(server)
+    }
+    Err(error) => {
+      println!("[Horus] Unable to publish {} over Syphon: {}", name, error);
+      None
+    }
   });
 
-  // determines which resources are bound to the pipeline
-  let render_pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
-    label: None,
-    bind_group_layouts: &[&uniforms_buffer_layout], // just our uniforms
-    push_constant_ranges: &[],
+  // --record-input saves what the shader saw of the mouse and keys, and --replay-input shows it them again in place of the real ones
+  let mut input_recorder = options.record_input.as_deref().and_then(|path| match InputRecorder::create(path) {
+    Ok(recorder) => {
+      println!("[Horus] Recording input into {}", path.display());
+      Some(recorder)
+    }
+    Err(error) => {
+      println!("[Horus] Unable to record input into {}: {}", path.display(), error);
+      None
+    }
   });
+  // keys that went down or came up since the last frame was recorded, by their javascript key codes
+  let mut recorded_keys: Vec<(usize, bool)> = Vec::new();
+  let mut replay = match options.replay_input.as_deref().map(Replay::load) {
+    Some(Ok(replay)) => Some(replay),
+    Some(Err(error)) => {
+      println!("[Horus] {}", error);
+      None
+    }
+    None => None,
+  };
 
-  // represents all stages of the rendering process
-  let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
-    label: None,
-    layout: Some(&render_pipeline_layout),
-    vertex: VertexState {
-      module: &vertex_shader,
-      entry_point: "vs_main",
-      buffers: &[],
-    },
-    fragment: Some(FragmentState {
-      module: &fragment_shader,
-      entry_point: "fs_main",
-      targets: &[Some(config.format.into())],
-    }),
-    primitive: PrimitiveState::default(),
-    depth_stencil: None,
-    multisample: MultisampleState::default(),
-    multiview: None,
-  });
+  // with --static, frames are only drawn once something the shader can see has changed, apart from when every frame has to be sent somewhere
+  let still = options.still && recorder.is_none() && ndi.is_none() && spout.is_none() && syphon.is_none() && replay.is_none();
+  if options.still && !still {
+    println!("[Horus] Drawing every frame, since they're being recorded, sent or replayed");
+  }
+  // the first frame always has to be drawn
+  let mut redraw = true;
+
+  // recordings and --deterministic run on their own clock, so the audio would only drift out of sync with them
+  if recorder.is_none() && !options.deterministic {
+    live.play_audio();
+    live.set_audio_speed(clock.speed());
+  }
 
   // continuously poll window events from the system
   event_loop.run(move |event, _, control_flow| {
-    *control_flow = event_loop::ControlFlow::Poll;
+    // keep the watcher alive for as long as the event loop runs
+    let _ = &watcher;
+    // a static shader still checks for saves, commands and MIDI every so often while it waits for input
+    *control_flow = match frame_interval {
+      _ if still => event_loop::ControlFlow::WaitUntil(Instant::now() + STILL_POLL),
+      Some(_) => event_loop::ControlFlow::WaitUntil(next_frame),
+      None => event_loop::ControlFlow::Poll,
+    };
     match event {
-      Event::MainEventsCleared => window.request_redraw(),
+      Event::UserEvent(HorusEvent::ShaderChanged(path)) if !changed_paths.contains(&path) => changed_paths.push(path),
+      Event::MainEventsCleared => {
+        if closes_at.is_some_and(|closes_at| Instant::now() >= closes_at) {
+          println!("[Horus] Closing after {}s", options.duration.unwrap_or_default());
+          *control_flow = event_loop::ControlFlow::Exit;
+          return;
+        }
+        if let Some(playlist) = playlist.as_mut().filter(|playlist| playlist.is_due()) {
+          next_shader = Some(playlist.skip(1).to_path_buf());
+        }
+        // the next shader in a playlist, or one that was dropped onto the window, runs with the same channels, starting from the beginning
+        if let Some(path) = next_shader.take() {
+          redraw = true;
+          options.fragment_path = Some(path);
+          let (width, height) = shader_size(&config, canvas.as_ref(), projection.as_ref());
+          match load_passes(&device, &queue, &options, &mut live, image_format, width, height) {
+            Ok(next) => {
+              passes = next;
+              passes.image.samples = multisample.as_ref().map_or(1, Multisample::samples);
+              render_pipeline_layout = create_pipeline_layout(&device, &uniforms_binding, &passes);
+              passes.compile(&device, &render_pipeline_layout, &vertex_shader, None);
+              text_overlay.set_text(&device, &queue, &passes.errors());
+              watched = passes.paths();
+              watcher = watch(&watched, proxy.clone());
+              changed_paths.clear();
+              title = window_title(&options);
+              window.set_title(&title);
+              clock.restart();
+              update_sound(&device, &queue, &mut passes, &mut live, &options, &clock, 0., recorder.is_none() && !options.deterministic);
+              uniforms.frame = 0;
+              frame = 0;
+            }
+            Err(error) => println!("[Horus] {}", error),
+          }
+        }
+        // remote and stdin commands are answered once they've been carried out, apart from the ones that wait for the next frame like the keys do
+        for request in remote.requests() {
+          redraw = true;
+          let result = match request.command {
+            RemoteCommand::Uniform { name, value } => match passes.params.index_of(&name) {
+              Some(index) => {
+                passes.params.set(index, &value.to_vec());
+                Ok(())
+              }
+              None => Err(format!("there's no uniform called {}", name)),
+            },
+            RemoteCommand::Pause | RemoteCommand::Resume => {
+              if clock.is_paused() != matches!(request.command, RemoteCommand::Pause) {
+                actions.push(Action::TogglePause);
+              }
+              Ok(())
+            }
+            RemoteCommand::Time { seconds } => {
+              actions.push(Action::Seek(seconds - uniforms.time));
+              Ok(())
+            }
+            RemoteCommand::Speed { speed } if speed > 0. && speed.is_finite() => {
+              actions.push(Action::SetSpeed(speed));
+              Ok(())
+            }
+            RemoteCommand::Speed { .. } => Err("the speed has to be greater than 0".to_string()),
+            RemoteCommand::Restart => {
+              actions.push(Action::Restart);
+              Ok(())
+            }
+            RemoteCommand::Reload => {
+              if passes.compile(&device, &render_pipeline_layout, &vertex_shader, None) {
+                uniforms.frame = 0;
+              }
+              update_sound(&device, &queue, &mut passes, &mut live, &options, &clock, uniforms.time, recorder.is_none() && !options.deterministic);
+              let errors = passes.errors();
+              text_overlay.set_text(&device, &queue, &errors);
+              if errors.is_empty() { Ok(()) } else { Err(errors) }
+            }
+            RemoteCommand::Screenshot => {
+              screenshot_requested = true;
+              Ok(())
+            }
+          };
+          let _ = request.reply.send(result);
+        }
+        // a single save can produce several file events, so reload at most once per frame
+        // passes that fail to compile keep rendering their last working pipeline until the error is fixed
+        if !changed_paths.is_empty() {
+          redraw = true;
+          // simulations start over once they've been rebuilt
+          if passes.compile(&device, &render_pipeline_layout, &vertex_shader, Some(&changed_paths)) {
+            uniforms.frame = 0;
+          }
+          update_sound(&device, &queue, &mut passes, &mut live, &options, &clock, uniforms.time, recorder.is_none() && !options.deterministic);
+          text_overlay.set_text(&device, &queue, &passes.errors());
+          changed_paths.clear();
+          // a save can include files that weren't being watched yet
+          if passes.paths() != watched {
+            watched = passes.paths();
+            watcher = watch(&watched, proxy.clone());
+          }
+        }
+        if toast.is_some_and(|until| Instant::now() >= until) {
+          toast = None;
+          redraw = true;
+          text_overlay.set_text(&device, &queue, &passes.errors());
+        }
+        midi.update(&mut passes.params);
+        if let Some(osc) = &mut osc {
+          osc.update(&mut passes.params);
+        }
+        redraw |= passes.params.has_changed();
+        if let Some(interval) = frame_interval {
+          let now = Instant::now();
+          if now < next_frame {
+            return;
+          }
+          // a frame that was late doesn't make the next ones hurry to catch up
+          next_frame = (next_frame + interval).max(now);
+        }
+        if still && !std::mem::take(&mut redraw) {
+          return;
+        }
+        window.request_redraw();
+      }
       Event::WindowEvent {
         ref event,
         window_id,
       } if window_id == window.id() => {
+        redraw = true;
+        // anyone coming back to the computer closes the screensaver
+        if screensaver.as_mut().is_some_and(|screensaver| screensaver.wakes(event)) {
+          *control_flow = event_loop::ControlFlow::Exit;
+          return;
+        }
+        // anything the panel uses doesn't reach the shader
+        if panel.input(event) {
+          return;
+        }
         match event {
-          WindowEvent::CloseRequested | WindowEvent::KeyboardInput {
+          // a preview's window is destroyed along with the settings it's in
+          WindowEvent::CloseRequested | WindowEvent::Destroyed | WindowEvent::KeyboardInput {
             input: KeyboardInput {
               state: ElementState::Pressed,
               virtual_keycode: Some(VirtualKeyCode::Escape),
@@ -225,15 +558,171 @@ async fn run() {
             },
             ..
           } => *control_flow = event_loop::ControlFlow::Exit,
+          WindowEvent::KeyboardInput {
+            input: KeyboardInput {
+              state,
+              virtual_keycode: Some(key),
+              ..
+            },
+            ..
+          } => {
+            if replay.is_none() {
+              live.keyboard().input(*key, *state);
+            }
+            if let Some(code) = keyboard::key_code(*key).filter(|_| input_recorder.is_some()) {
+              recorded_keys.push((code, *state == ElementState::Pressed));
+            }
+            if *state == ElementState::Pressed {
+              match key {
+                VirtualKeyCode::F12 => screenshot_requested = true,
+                VirtualKeyCode::F11 => toggle_fullscreen(&window),
+                VirtualKeyCode::F1 => panel.stats = !panel.stats,
+                VirtualKeyCode::F2 => panel.visible = !panel.visible,
+                VirtualKeyCode::PageUp | VirtualKeyCode::PageDown => if let Some(playlist) = &mut playlist {
+                  next_shader = Some(playlist.skip(if *key == VirtualKeyCode::PageUp { -1 } else { 1 }).to_path_buf());
+                },
+                VirtualKeyCode::S if !reads_keyboard => screenshot_requested = true,
+                VirtualKeyCode::Space if !reads_keyboard => actions.push(Action::TogglePause),
+                VirtualKeyCode::Period if !reads_keyboard => actions.push(Action::Step),
+                VirtualKeyCode::R if !reads_keyboard => actions.push(Action::Restart),
+                VirtualKeyCode::N if !reads_keyboard => uniforms.set_seed(horus::random_seed()),
+                VirtualKeyCode::Z if !reads_keyboard => {
+                  magnifying = !magnifying;
+                  panning = false;
+                  match (magnifying, &mut canvas) {
+                    // the window has to be drawn through a canvas to be magnified, which the text overlay is then drawn over at a single sample
+                    (true, None) => {
+                      canvas = Some(Canvas::new(&device, config.format, 1., config.width, config.height, None));
+                      text_overlay = TextOverlay::new(&device, config.format, 1);
+                      text_overlay.set_text(&device, &queue, &passes.errors());
+                    }
+                    (false, Some(_)) if !keeps_canvas => {
+                      canvas = None;
+                      text_overlay = TextOverlay::new(&device, config.format, if projection.is_some() { 1 } else { passes.image.samples });
+                      text_overlay.set_text(&device, &queue, &passes.errors());
+                    }
+                    (false, Some(canvas)) => canvas.reset_view(&queue),
+                    _ => {}
+                  }
+                  match magnifying {
+                    true => println!("[Horus] Magnifying, so scroll to zoom in and drag to look around"),
+                    false => println!("[Horus] Stopped magnifying"),
+                  }
+                }
+                VirtualKeyCode::LBracket | VirtualKeyCode::RBracket if !reads_keyboard => {
+                  let speed = if *key == VirtualKeyCode::LBracket { clock.speed() / 2. } else { clock.speed() * 2. }.clamp(MIN_SPEED, MAX_SPEED);
+                  println!("[Horus] Running at {}x speed", speed);
+                  actions.push(Action::SetSpeed(speed));
+                }
+                VirtualKeyCode::Left | VirtualKeyCode::Right if !reads_keyboard => {
+                  let step = if modifiers.shift() { scrub_step * 10. } else { scrub_step };
+                  actions.push(Action::Seek(if *key == VirtualKeyCode::Left { -step } else { step }));
+                }
+                _ => {}
+              }
+            }
+          }
+          // a dropped shader takes over from the playlist, and from the files the last one's horus.toml moved its passes to
+          WindowEvent::DroppedFile(path) if playlist::is_shader(path) => {
+            playlist = None;
+            options.buffers = Default::default();
+            options.common = None;
+            options.uniforms = None;
+            next_shader = Some(path.clone());
+          }
+          // a dropped image is bound to the first channel that isn't reading anything, which it stays bound to for the next shader
+          WindowEvent::DroppedFile(path) if channel::is_image(path) => {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let slots = [&mut options.channel0, &mut options.channel1, &mut options.channel2, &mut options.channel3];
+            let message = match slots.into_iter().enumerate().find(|(_, slot)| slot.is_none()) {
+              Some((index, slot)) => {
+                let is_shadertoy = passes.image.is_shadertoy();
+                match Channel::from_image(&device, &queue, path, is_shadertoy, is_shadertoy.then_some(ColorSpace::Linear)) {
+                  Ok(channel) => {
+                    passes.set_channel(&device, index, channel);
+                    *slot = Some(path.clone());
+                    format!("Bound {} to iChannel{}", name, index)
+                  }
+                  Err(error) => format!("Unable to load {}: {}", name, error),
+                }
+              }
+              None => format!("Every channel is already reading something, so {} wasn't bound", name),
+            };
+            println!("[Horus] {}", message);
+            text_overlay.set_text(&device, &queue, &with_errors(&message, &passes.errors()));
+            toast = Some(Instant::now() + TOAST);
+          }
+          WindowEvent::DroppedFile(path) => println!("[Horus] Unable to run {}, which isn't a shader or an image", path.display()),
+          WindowEvent::ModifiersChanged(state) => modifiers = *state,
           WindowEvent::Resized(physical_size) => {
-            resize(&device, &mut surface, &mut config, (*physical_size).clone(), &mut uniforms);
+            resize(&device, &mut surface, &mut config, *physical_size, &mut uniforms, &mut passes, canvas.as_mut(), projection.as_mut(), multisample.as_mut());
           }
           WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-            resize(&device, &mut surface, &mut config, (**new_inner_size).clone(), &mut uniforms);
+            resize(&device, &mut surface, &mut config, **new_inner_size, &mut uniforms, &mut passes, canvas.as_mut(), projection.as_mut(), multisample.as_mut());
           }
           WindowEvent::CursorMoved { position, .. } => {
-            // update uniforms
-            uniforms.mouse = [position.x.clone() as _, position.y.clone() as _];
+            let position = [position.x as f32, position.y as f32];
+            if let Some(canvas) = canvas.as_mut().filter(|_| panning) {
+              canvas.pan(&queue, [position[0] - cursor[0], position[1] - cursor[1]]);
+            }
+            cursor = position;
+            if replay.is_some() {
+              return;
+            }
+            // update uniforms, in the pixels the shader renders rather than the window's
+            uniforms.mouse = canvas.as_ref().map_or(position, |canvas| canvas.pixel_at(position));
+            if mouse_down {
+              uniforms.mouse_click[..2].copy_from_slice(&uniforms.mouse);
+            }
+          }
+          // inspecting a pixel isn't a click as far as the shader is concerned
+          WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } if modifiers.ctrl() => inspect_requested = Some(cursor),
+          WindowEvent::MouseInput { state, button: MouseButton::Left, .. } if magnifying => panning = *state == ElementState::Pressed,
+          WindowEvent::MouseWheel { delta, .. } if magnifying => if let Some(canvas) = &mut canvas {
+            canvas.zoom(&queue, cursor, ZOOM_STEP.powf(notches(delta)[1]));
+          },
+          WindowEvent::MouseWheel { delta, .. } if replay.is_none() => {
+            let [x, y] = notches(delta);
+            uniforms.scroll = [uniforms.scroll[0] + x, uniforms.scroll[1] + y];
+          }
+          // touches are in the window's pixels, the same as the cursor, and they aren't recorded so a replay leaves them alone
+          WindowEvent::Touch(touch) if replay.is_none() => {
+            let position = [touch.location.x as f32, touch.location.y as f32];
+            let position = canvas.as_ref().map_or(position, |canvas| canvas.pixel_at(position));
+            match touch_screen.input(touch, position) {
+              Some(MouseTouch::Pressed([x, y])) => {
+                mouse_down = true;
+                uniforms.mouse = [x, y];
+                uniforms.mouse_click = [x, y, x, y];
+              }
+              Some(MouseTouch::Moved(position)) => {
+                uniforms.mouse = position;
+                uniforms.mouse_click[..2].copy_from_slice(&position);
+              }
+              Some(MouseTouch::Released) => {
+                mouse_down = false;
+                uniforms.mouse_click[2] = -uniforms.mouse_click[2].abs();
+              }
+              None => {}
+            }
+          }
+          WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+            mouse_down = *state == ElementState::Pressed;
+            if mouse_down {
+              if replay.is_none() {
+                let [x, y] = uniforms.mouse;
+                uniforms.mouse_click = [x, y, x, y];
+              }
+              let now = Instant::now();
+              if last_click.is_some_and(|last| now - last < DOUBLE_CLICK) {
+                toggle_fullscreen(&window);
+                last_click = None;
+              } else {
+                last_click = Some(now);
+              }
+            } else if replay.is_none() {
+              uniforms.mouse_click[2] = -uniforms.mouse_click[2].abs();
+            }
           }
           _ => {}
         }
@@ -243,46 +732,385 @@ async fn run() {
         let output = surface.get_current_texture().unwrap();
         let view = output.texture.create_view(&TextureViewDescriptor::default());
 
+        let status = Status { paused: clock.is_paused(), time: uniforms.time, speed: clock.speed(), resolution: uniforms.resolution, mouse: uniforms.mouse, frame_rate: &frame_rate, gpu: gpu_timer.as_mut().and_then(|timer| timer.milliseconds(&device)) };
+        actions.extend(panel.update(&window, &mut passes.params, &status));
+        let mut stepped = false;
+        for action in actions.drain(..) {
+          match action {
+            Action::TogglePause => {
+              clock.set_paused(!clock.is_paused());
+              live.pause_audio(clock.is_paused());
+              if clock.is_paused() {
+                println!("[Horus] Paused at {:.2}s", uniforms.time);
+              } else {
+                println!("[Horus] Resumed");
+              }
+            }
+            // buffers carry on from wherever they were, since they can't go back in time
+            Action::Seek(seconds) => {
+              let time = clock.seek(seconds);
+              if recorder.is_none() {
+                live.seek_audio(time);
+              }
+            }
+            // one frame's worth of time, and one more frame of the buffers
+            Action::Step => {
+              let time = clock.seek(clock::FRAME_STEP);
+              if recorder.is_none() {
+                live.seek_audio(time);
+              }
+              stepped = true;
+            }
+            Action::SetSpeed(speed) => {
+              clock.set_speed(speed.clamp(MIN_SPEED, MAX_SPEED));
+              live.set_audio_speed(clock.speed());
+            }
+            // recreating the buffers clears them, so simulations start over too
+            Action::Restart => {
+              println!("[Horus] Restarted");
+              clock.restart();
+              if let Some(replay) = &mut replay {
+                replay.restart();
+              }
+              uniforms.frame = 0;
+              let (width, height) = shader_size(&config, canvas.as_ref(), projection.as_ref());
+              passes.resize(&device, width, height);
+              if recorder.is_none() && !options.deterministic {
+                live.play_audio();
+                live.pause_audio(clock.is_paused());
+                live.set_audio_speed(clock.speed());
+              }
+            }
+          }
+        }
+
         // update uniforms
-        uniforms.time = time.elapsed().as_secs_f32();
-        queue.write_buffer(&uniforms_buffer, 0, bytemuck::bytes_of(&uniforms));
+        // recordings advance by exactly one frame at a time so they play back at the right speed
+        let now = match (&recorder, live.audio_position()) {
+          (Some(recorder), _) => clock.tick(Some(1. / recorder.fps as f32)),
+          (None, _) if options.deterministic => clock.tick(Some(clock::FRAME_STEP)),
+          (None, Some(position)) => clock.sync(position),
+          (None, None) => clock.tick(None),
+        };
+        uniforms.set_time(now, start);
+        // the timeline has the last say over any uniform it animates
+        passes.animate(now);
+        passes.params.upload(&queue);
+        // while paused the buffers hold still, and the image keeps showing the last frame they rendered
+        let advancing = !clock.is_paused() || stepped;
+        // the replay picks up from wherever time is, so seeking back waits for it to catch up
+        if let Some(input) = replay.as_mut().filter(|_| advancing).and_then(|replay| replay.advance(uniforms.time)) {
+          input.apply(&mut uniforms, live.keyboard());
+        }
+        if replay.as_ref().is_some_and(Replay::is_done) {
+          println!("[Horus] Finished replaying the input");
+          replay = None;
+        }
+        live.update(&queue, uniforms.time);
+        [uniforms.beat, uniforms.bpm] = live.beat();
+        let shown_frame = if advancing { frame } else { frame.saturating_sub(1) };
+        let mut shown = uniforms;
+        if !advancing {
+          shown.frame = (uniforms.frame - 1).max(0);
+        }
+        upload_uniforms(&queue, &uniforms_binding, &passes, &shown, &touch_screen.touches());
+        if let Some(projection) = &projection {
+          projection.upload_uniforms(&queue, &shown);
+        }
+        if let Some(recorder) = input_recorder.as_mut().filter(|_| advancing) {
+          if let Err(error) = recorder.write(&InputFrame::new(&shown, std::mem::take(&mut recorded_keys))) {
+            println!("[Horus] Stopped recording input: {}", error);
+            input_recorder = None;
+          }
+        }
+        live.end_frame();
+        end_frame(&mut uniforms, advancing);
 
         // the encoder will create a command buffer to send to the device
         let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: None });
-
-        {
-          let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
-            label: None,
-            color_attachments: &[Some(RenderPassColorAttachment {
-              view: &view,
-              resolve_target: None,
-              ops: Operations {
-                load: LoadOp::Clear(Color::BLACK),
-                store: true,
-              },
-            })],
-            depth_stencil_attachment: None,
-          });
-          render_pass.set_pipeline(&render_pipeline);
-          render_pass.set_bind_group(0, &uniforms_buffer_bind_group, &[]);
-          render_pass.draw(0..3, 0..1);
+        if let Some(timer) = &mut gpu_timer {
+          timer.start(&mut encoder);
+        }
+        let target = canvas.as_ref().map_or(&view, Canvas::view);
+        match &projection {
+          Some(projection) => {
+            projection.render(&mut encoder, multisample.as_ref(), &passes, shown_frame, advancing, clear);
+            projection.draw(&mut encoder, target, canvas.is_none().then_some(&text_overlay));
+          }
+          None => render_frame(&mut encoder, target, multisample.as_ref(), &passes, &uniforms_binding.bind_group, shown_frame, advancing, clear, None, canvas.is_none().then_some(&text_overlay)),
         }
+        if let Some(canvas) = &canvas {
+          canvas.draw(&mut encoder, &view, Some(&text_overlay));
+        }
+        if let Some(timer) = &mut gpu_timer {
+          timer.end(&mut encoder);
+        }
+        let alpha_screenshot = canvas.as_ref().filter(|_| screenshot_requested && options.capture_alpha).map(|canvas| {
+          let (width, height) = canvas.size();
+          Readback::new(&device, &mut encoder, canvas.texture(), width, height, config.format)
+        });
+        let readback = ((screenshot_requested && alpha_screenshot.is_none()) || recorder.is_some() || ndi.is_some() || spout.is_some() || syphon.is_some()).then(|| Readback::new(&device, &mut encoder, &output.texture, config.width, config.height, config.format));
+        let inspected = inspect_requested.take().map(|position| {
+          let (x, y) = ((position[0].max(0.) as u32).min(config.width - 1), (position[1].max(0.) as u32).min(config.height - 1));
+          (position, Readback::pixel(&device, &mut encoder, &output.texture, x, y, config.format))
+        });
+        // the panel is drawn after the frame is copied so it's left out of screenshots and recordings
+        panel.draw(&device, &queue, &mut encoder, &view);
 
         // send it to the device for rendering
         queue.submit(std::iter::once(encoder.finish()));
+        if let Some(timer) = &mut gpu_timer {
+          timer.submitted();
+        }
+        match readback.map(|readback| readback.and_then(|readback| readback.into_image(&device))) {
+          Some(Ok(image)) => {
+            if screenshot_requested && alpha_screenshot.is_none() {
+              // the surface's alpha means nothing unless the window is transparent, so the screenshot is as opaque as the window looks
+              let mut screenshot = image.clone();
+              if !options.transparent {
+                screenshot.pixels_mut().for_each(|pixel| pixel.0[3] = u8::MAX);
+              }
+              save_screenshot(&screenshot, passes.image.path.as_deref());
+            }
+            if let Some(ndi) = &mut ndi {
+              ndi.send(&image);
+            }
+            if let Some(spout) = &mut spout {
+              spout.send(&image);
+            }
+            if let Some(syphon) = &mut syphon {
+              syphon.send(&image);
+            }
+            match recorder.as_mut().map(|recorder| recorder.write(&image)) {
+              Some(Err(error)) => {
+                println!("[Horus] Stopped recording: {}", error);
+                recorder = None;
+              }
+              Some(Ok(())) if recorder.as_ref().is_some_and(Recorder::is_done) => finish_recording(recorder.take()),
+              _ => {}
+            }
+          }
+          Some(Err(error)) => {
+            println!("[Horus] Unable to read back the frame: {}", error);
+            recorder = None;
+            ndi = None;
+            spout = None;
+            syphon = None;
+          }
+          None => {}
+        }
+        match alpha_screenshot.map(|readback| readback.and_then(|readback| readback.into_image(&device))) {
+          Some(Ok(image)) => save_screenshot(&image, passes.image.path.as_deref()),
+          Some(Err(error)) => println!("[Horus] Unable to read back the frame: {}", error),
+          None => {}
+        }
+        screenshot_requested = false;
+        if let Some((position, readback)) = inspected {
+          match readback.and_then(|readback| readback.into_image(&device)) {
+            Ok(image) => {
+              // the pixel is given in the shader's own coordinates, which Shadertoy counts from the bottom
+              let [x, y] = canvas.as_ref().map_or(position, |canvas| canvas.pixel_at(position));
+              let [width, height] = uniforms.resolution;
+              let x = (x.max(0.) as u32).min(width as u32 - 1);
+              let mut y = (y.max(0.) as u32).min(height as u32 - 1);
+              if passes.image.is_shadertoy() {
+                y = height as u32 - 1 - y;
+              }
+              let message = describe_pixel(image.get_pixel(0, 0).0, config.format.describe().srgb, [x, y], uniforms.resolution);
+              println!("[Horus] {}", message);
+              text_overlay.set_text(&device, &queue, &with_errors(&message, &passes.errors()));
+              toast = Some(Instant::now() + TOAST);
+            }
+            Err(error) => println!("[Horus] Unable to read back the pixel: {}", error),
+          }
+        }
         output.present();
+        if let Some(fps) = frame_rate.tick() {
+          window.set_title(&format!("{} — {:.0} fps", title, fps));
+        }
+        if advancing {
+          frame += 1;
+        }
+      }
+      // ffmpeg only finishes the video once its input is closed
+      Event::LoopDestroyed => {
+        finish_recording(recorder.take());
+        match input_recorder.take().map(InputRecorder::finish) {
+          Some(Ok(path)) => println!("[Horus] Saved the input into {}", path.display()),
+          Some(Err(error)) => println!("[Horus] Unable to finish recording input: {}", error),
+          None => {}
+        }
+        // the window never returns from its event loop, so a bundle's files are removed here instead
+        bundle::clean_up();
       }
       _ => {}
     }
   });
 }
 
-// update uniforms, config and then resize surface to fit the window
-fn resize(device: &Device, surface: &mut Surface, config: &mut SurfaceConfiguration, new_size: winit::dpi::PhysicalSize<u32>, uniforms: &mut Uniforms) {
+
+// the title is followed by the shader's name, and how fast it's running once a second has passed
+fn window_title(options: &Options) -> String {
+  match (&options.title, &options.fragment_path) {
+    (Some(title), _) => title.clone(),
+    (None, Some(path)) => match Metadata::read(path).ok().and_then(|metadata| metadata.name()) {
+      Some(name) => format!("Horus — {}", name),
+      None => format!("Horus — {}", path.file_name().unwrap_or_default().to_string_lossy()),
+    },
+    (None, None) => "Horus".to_string(),
+  }
+}
+
+// save a screenshot named after the shader at `shader`
+fn save_screenshot(image: &image::RgbaImage, shader: Option<&Path>) {
+  let path = capture::screenshot_path(shader);
+  match image.save(&path) {
+    Ok(()) => println!("[Horus] Saved {}", path.display()),
+    Err(error) => println!("[Horus] Unable to save {}: {}", path.display(), error),
+  }
+}
+
+// `message` followed by any compilation errors, which have to stay on screen while it's shown
+fn with_errors(message: &str, errors: &str) -> String {
+  match errors.is_empty() {
+    true => message.to_string(),
+    false => format!("{}\n\n{}", message, errors),
+  }
+}
+
+// the pixel the inspector read back, at `position` in the pixels the shader renders
+fn describe_pixel(pixel: [u8; 4], srgb: bool, position: [u32; 2], resolution: [f32; 2]) -> String {
+  // sRGB surfaces encode the colors the shader wrote, apart from alpha, so they're decoded back into them
+  let written: Vec<f32> = pixel.iter().enumerate().map(|(index, byte)| {
+    let value = *byte as f32 / 255.;
+    match srgb && index < 3 {
+      true if value <= 0.04045 => value / 12.92,
+      true => ((value + 0.055) / 1.055).powf(2.4),
+      false => value,
+    }
+  }).collect();
+  // the UV is at the pixel's center, where the shader ran
+  let uv = [(position[0] as f32 + 0.5) / resolution[0], (position[1] as f32 + 0.5) / resolution[1]];
+  format!(
+    "Pixel ({}, {}) at UV ({:.3}, {:.3}) is RGBA ({}, {}, {}, {}), which the shader wrote as ({:.3}, {:.3}, {:.3}, {:.3})",
+    position[0], position[1], uv[0], uv[1], pixel[0], pixel[1], pixel[2], pixel[3], written[0], written[1], written[2], written[3],
+  )
+}
+
+// render the sound pass again once it's been rebuilt, and play it from `time` if `plays` says the audio isn't being left alone
+#[allow(clippy::too_many_arguments)]
+fn update_sound(device: &Device, queue: &Queue, passes: &mut Passes, live: &mut LiveChannels, options: &Options, clock: &Clock, time: f32, plays: bool) {
+  if passes.take_sound_changed() && sound::play(device, queue, passes, live, options.audio.as_deref()) && plays {
+    live.play_audio();
+    live.seek_audio(time);
+    live.pause_audio(clock.is_paused());
+    live.set_audio_speed(clock.speed());
+  }
+}
+
+// how far the mouse wheel or touchpad scrolled across and up, in notches of a mouse wheel
+fn notches(delta: &MouseScrollDelta) -> [f32; 2] {
+  match delta {
+    MouseScrollDelta::LineDelta(x, y) => [*x, *y],
+    MouseScrollDelta::PixelDelta(position) => [position.x as f32 / PIXELS_PER_LINE, position.y as f32 / PIXELS_PER_LINE],
+  }
+}
+
+// switch between a window and borderless fullscreen on whichever monitor it's on, which resizes the surface along with it
+fn toggle_fullscreen(window: &Window) {
+  window.set_fullscreen(match window.fullscreen() {
+    Some(_) => None,
+    None => Some(Fullscreen::Borderless(window.current_monitor())),
+  });
+}
+
+// wait for a recording to be written out, if there is one
+fn finish_recording(recorder: Option<Recorder>) {
+  match recorder.map(Recorder::finish) {
+    Some(Ok(path)) => println!("[Horus] Saved {}", path.display()),
+    Some(Err(error)) => println!("[Horus] Unable to finish recording: {}", error),
+    None => {}
+  }
+}
+
+// the resolution the image renders at, which is a face of the cubemap with --projection and the canvas's with --scale
+fn shader_size(config: &SurfaceConfiguration, canvas: Option<&Canvas>, projection: Option<&Projection>) -> (u32, u32) {
+  match (projection, canvas) {
+    (Some(projection), _) => projection.size(),
+    (None, Some(canvas)) => canvas.size(),
+    (None, None) => (config.width, config.height),
+  }
+}
+
+// update uniforms, config and then resize surface and buffers to fit the window
+#[allow(clippy::too_many_arguments)]
+fn resize(device: &Device, surface: &mut Surface, config: &mut SurfaceConfiguration, new_size: winit::dpi::PhysicalSize<u32>, uniforms: &mut Uniforms, passes: &mut Passes, canvas: Option<&mut Canvas>, projection: Option<&mut Projection>, multisample: Option<&mut Multisample>) {
   if new_size.width > 0 && new_size.height > 0 {
     config.width = new_size.width;
     config.height = new_size.height;
-    uniforms.resolution = [new_size.width.clone() as _, new_size.height.clone() as _];
     surface.configure(device, config);
+    let (width, height) = match canvas {
+      Some(canvas) => {
+        canvas.resize(device, new_size.width, new_size.height);
+        canvas.size()
+      }
+      None => (new_size.width, new_size.height),
+    };
+    let (width, height) = match projection {
+      Some(projection) => {
+        projection.resize(device, width, height);
+        projection.size()
+      }
+      None => (width, height),
+    };
+    if let Some(multisample) = multisample {
+      multisample.resize(device, width, height);
+    }
+    uniforms.resolution = [width as _, height as _];
+    // resizing clears the buffers, so simulations need to start over
+    uniforms.frame = 0;
+    passes.resize(device, width, height);
+  }
+}
+
+// notify the event loop whenever one of the files at `paths` changes
+fn watch(paths: &[PathBuf], proxy: EventLoopProxy<HorusEvent>) -> Option<RecommendedWatcher> {
+  // files are told apart by their whole path, since a shader and the files it includes can share a name in different directories
+  let watched: Vec<(PathBuf, PathBuf)> = paths.iter().map(|path| (canonical(path), path.clone())).collect();
+  let mut directories: Vec<PathBuf> = watched.iter()
+    .map(|(path, _)| path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new(".")).to_path_buf())
+    .collect();
+  let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+    if let Ok(event) = result {
+      if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+        return;
+      }
+      let changed: Vec<PathBuf> = event.paths.iter().map(|path| canonical(path)).collect();
+      for (_, path) in watched.iter().filter(|(watched, _)| changed.contains(watched)) {
+        let _ = proxy.send_event(HorusEvent::ShaderChanged(path.clone()));
+      }
+    }
+  }).map_err(|error| println!("[Horus] Unable to watch the shader files, so they won't be reloaded as they change: {}", error)).ok()?;
+
+  // editors often save by replacing the file, so watch its directory instead of the file itself
+  directories.sort();
+  directories.dedup();
+  for directory in directories {
+    if let Err(error) = watcher.watch(&directory, RecursiveMode::NonRecursive) {
+      println!("[Horus] Unable to watch {}, so the shaders in it won't be reloaded as they change: {}", directory.display(), error);
+    }
+  }
+  Some(watcher)
+}
+
+// the same file has the same canonical path however it was named, and a file that was just replaced may only have its directory to resolve
+fn canonical(path: &Path) -> PathBuf {
+  if let Ok(path) = path.canonicalize() {
+    return path;
+  }
+  let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+  match (parent.canonicalize(), path.file_name()) {
+    (Ok(parent), Some(name)) => parent.join(name),
+    _ => path.to_path_buf(),
   }
 }
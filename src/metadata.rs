@@ -0,0 +1,106 @@
+// a comment at the top of a shader can describe it, which names the window and its screenshots and sets up the channels it reads
+// without needing a horus.toml
+//
+// // title: Plasma
+// // author: Jane Doe
+// // description: Swirling colors that follow the mouse
+// // resolution: 1280x720
+// // channel0: noise-rgba-256
+// // channel1: sky (cube)
+//
+// every line of the first comment is looked through, and any that aren't one of these are left alone as ordinary comments
+
+use std::{fs::read_to_string, path::{Path, PathBuf}};
+
+use crate::{channel::CHANNEL_COUNT, cli::{self, Options}, config, render::RenderOptions};
+
+#[derive(Debug, Default)]
+pub struct Metadata {
+  pub title: Option<String>,
+  pub author: Option<String>,
+  pub description: Option<String>,
+  // the window's size in logical pixels, and the size of the frames `horus render` saves
+  pub resolution: Option<(u32, u32)>,
+  // what each channel reads, relative to the shader unless it's built in, and whether it's a cubemap
+  pub channels: [Option<(PathBuf, bool)>; CHANNEL_COUNT],
+}
+
+impl Metadata {
+  // the header of the shader at `path`, which is empty if it doesn't have one or can't be read
+  pub fn read(path: &Path) -> Result<Self, String> {
+    let Ok(source) = read_to_string(path) else {
+      return Ok(Self::default());
+    };
+    Self::parse(&source).map_err(|error| format!("Unable to read the header of {}, since {}", path.display(), error))
+  }
+
+  pub fn parse(source: &str) -> Result<Self, String> {
+    let mut metadata = Self::default();
+    let comment = source.lines().map(str::trim).skip_while(|line| line.is_empty()).map_while(|line| line.strip_prefix("//"));
+    for line in comment {
+      let Some((key, value)) = line.split_once(':') else {
+        continue;
+      };
+      let (key, value) = (key.trim().to_lowercase(), value.trim());
+      if value.is_empty() {
+        continue;
+      }
+      match key.as_str() {
+        "title" => metadata.title = Some(value.to_string()),
+        "author" => metadata.author = Some(value.to_string()),
+        "description" => metadata.description = Some(value.to_string()),
+        "resolution" => metadata.resolution = Some(cli::parse_size(value).map_err(|error| format!("its resolution is wrong: {}", error))?),
+        // shadertoy's names for them work too
+        _ => if let Some(index) = key.strip_prefix('i').unwrap_or(&key).strip_prefix("channel").and_then(|index| index.parse::<usize>().ok()).filter(|index| *index < CHANNEL_COUNT) {
+          metadata.channels[index] = Some(match value.strip_suffix("(cube)") {
+            Some(path) => (PathBuf::from(path.trim_end()), true),
+            None => (PathBuf::from(value), false),
+          });
+        },
+      }
+    }
+    Ok(metadata)
+  }
+
+  // read the header of the shader `options` runs, and fill in anything that neither the command line nor horus.toml did
+  pub fn apply(options: &mut Options) -> Result<Self, String> {
+    let Some(path) = options.fragment_path.clone().filter(|path| path.is_file()) else {
+      return Ok(Self::default());
+    };
+    let metadata = Self::read(&path)?;
+    let directory = path.parent().unwrap_or(Path::new(""));
+    options.size = options.size.or(metadata.resolution);
+    let paths = [&mut options.channel0, &mut options.channel1, &mut options.channel2, &mut options.channel3];
+    let cubes = [&mut options.channel0_cube, &mut options.channel1_cube, &mut options.channel2_cube, &mut options.channel3_cube];
+    for ((channel, path), cube) in metadata.channels.iter().zip(paths).zip(cubes) {
+      if let (Some((channel, is_cube)), None) = (channel, &path) {
+        *path = Some(config::resolve_channel(directory, channel));
+        *cube |= is_cube;
+      }
+    }
+    Ok(metadata)
+  }
+
+  // frames are rendered at the shader's resolution unless `horus render` or horus.toml ask for another size
+  pub fn apply_render(&self, render: &mut RenderOptions) {
+    if let (None, None, Some((width, height))) = (render.width, render.height, self.resolution) {
+      (render.width, render.height) = (Some(width), Some(height));
+    }
+  }
+
+  // the title along with whoever made it
+  pub fn name(&self) -> Option<String> {
+    let title = self.title.as_deref()?;
+    Some(match &self.author {
+      Some(author) => format!("{} by {}", title, author),
+      None => title.to_string(),
+    })
+  }
+
+  // the title as part of a file name, with anything but letters and numbers turned into dashes
+  pub fn file_name(&self) -> Option<String> {
+    let title = self.title.as_deref()?.to_lowercase();
+    let name = title.split(|letter: char| !letter.is_alphanumeric()).filter(|word| !word.is_empty()).collect::<Vec<_>>().join("-");
+    (!name.is_empty()).then_some(name)
+  }
+}
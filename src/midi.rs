@@ -0,0 +1,90 @@
+// moves custom uniforms with the knobs and faders of MIDI controllers, mapped with `midi = 21` in the toml file
+
+#[cfg(feature = "midi")]
+use std::sync::mpsc::{self, Receiver};
+#[cfg(feature = "midi")]
+use midir::{MidiInput, MidiInputConnection};
+
+use crate::params::Params;
+
+// control change messages are 0xB0 plus the channel, followed by the controller and its value
+#[cfg(feature = "midi")]
+const CONTROL_CHANGE: u8 = 0xB0;
+
+pub struct Midi {
+  // Horus only connects once something follows a controller, and only tries once
+  connected: bool,
+  // every input port, which stay open for as long as these are alive
+  #[cfg(feature = "midi")]
+  connections: Vec<MidiInputConnection<()>>,
+  // each controller that moved and its value, from every port
+  #[cfg(feature = "midi")]
+  messages: Option<Receiver<(u8, u8)>>,
+}
+
+impl Default for Midi {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Midi {
+  pub fn new() -> Self {
+    Midi {
+      connected: false,
+      #[cfg(feature = "midi")]
+      connections: Vec::new(),
+      #[cfg(feature = "midi")]
+      messages: None,
+    }
+  }
+
+  // connect the first time a parameter follows a controller, then set them from any messages that came in since the last frame
+  #[cfg_attr(not(feature = "midi"), allow(unused_variables))]
+  pub fn update(&mut self, params: &mut Params) {
+    if !self.connected && params.uses_midi() {
+      self.connected = true;
+      #[cfg(not(feature = "midi"))]
+      println!("[Horus] Unable to read MIDI controllers since Horus was built without the midi feature");
+      #[cfg(feature = "midi")]
+      if let Err(error) = self.connect() {
+        println!("[Horus] Unable to read MIDI controllers: {}", error);
+      }
+    }
+    #[cfg(feature = "midi")]
+    for (controller, value) in self.messages.iter().flat_map(Receiver::try_iter) {
+      params.control(controller, value);
+    }
+  }
+
+  #[cfg(feature = "midi")]
+  fn connect(&mut self) -> Result<(), String> {
+    let ports = MidiInput::new("Horus").map_err(|error| error.to_string())?.ports();
+    if ports.is_empty() {
+      return Err("there are no MIDI inputs".to_string());
+    }
+    let (sender, receiver) = mpsc::channel();
+    for port in ports {
+      // connecting uses up the input, so every port needs one of its own
+      let input = MidiInput::new("Horus").map_err(|error| error.to_string())?;
+      let name = input.port_name(&port).unwrap_or_else(|_| "a MIDI input".to_string());
+      let sender = sender.clone();
+      let connection = input.connect(&port, "Horus", move |_, message, _| {
+        if let [status, controller, value] = *message {
+          if status & 0xF0 == CONTROL_CHANGE {
+            let _ = sender.send((controller, value));
+          }
+        }
+      }, ());
+      match connection {
+        Ok(connection) => {
+          println!("[Horus] Listening to {}", name);
+          self.connections.push(connection);
+        }
+        Err(error) => println!("[Horus] Unable to listen to {}: {}", name, error),
+      }
+    }
+    self.messages = Some(receiver);
+    Ok(())
+  }
+}
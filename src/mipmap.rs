@@ -0,0 +1,162 @@
+// textures loaded into channels are given a full chain of mipmaps, like they are on shadertoy, so shaders can read them at a coarser
+// level of detail with textureSampleLevel and textures that are drawn small don't shimmer
+//
+// each level is rendered from the one before it with a linear filter, which averages every 2x2 block of its pixels, in linear light
+// for sRGB textures
+
+use std::num::NonZeroU32;
+use wgpu::{BindGroupDescriptor, BindGroupEntry, BindingResource, Color, ColorTargetState, ColorWrites, CommandEncoderDescriptor, Device, Extent3d, FilterMode, FragmentState, ImageCopyTexture, ImageDataLayout, LoadOp, MultisampleState, Operations, Origin3d, PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, SamplerDescriptor, ShaderModuleDescriptor, ShaderSource, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureViewDescriptor, TextureViewDimension, VertexState};
+
+use crate::channel;
+
+const SOURCE: &str = "\
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var level: texture_2d<f32>;
+@group(0) @binding(1)
+var level_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var vertices = array<vec2<f32>, 3>(
+        vec2<f32>(-1., 1.),
+        vec2<f32>(3., 1.),
+        vec2<f32>(-1., -3.),
+    );
+    var out: VertexOutput;
+    out.position = vec4<f32>(vertices[vertex_index], 0., 1.);
+    out.uv = vertices[vertex_index] * vec2<f32>(0.5, -0.5) + 0.5;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSampleLevel(level, level_sampler, in.uv, 0.);
+}\
+";
+
+// how many levels it takes to halve a `width` by `height` texture down to a single pixel, including the texture itself
+pub fn level_count(width: u32, height: u32) -> u32 {
+  u32::BITS - width.max(height).leading_zeros()
+}
+
+// upload `pixels` into the first level of `texture`, layer after layer, and render every level after it from the one before
+//
+// wgpu's GL backend can only sample a whole texture and can't copy out of a cubemap, so each level is rendered into a texture of its own,
+// starting from `pixels` themselves, and copied into place
+pub fn upload(device: &Device, queue: &Queue, texture: &Texture, format: TextureFormat, size: Extent3d, levels: u32, pixels: &[u8]) {
+  let bytes_per_row = size.width * format.describe().block_size as u32;
+  let data_layout = ImageDataLayout {
+    offset: 0,
+    bytes_per_row: NonZeroU32::new(bytes_per_row),
+    rows_per_image: NonZeroU32::new(size.height),
+  };
+  queue.write_texture(texture.as_image_copy(), pixels, data_layout, size);
+  if levels < 2 {
+    return;
+  }
+  let shader = device.create_shader_module(ShaderModuleDescriptor {
+    label: Some("mipmap"),
+    source: ShaderSource::Wgsl(SOURCE.into()),
+  });
+  let layout = channel::create_bind_group_layout(device, &[TextureViewDimension::D2]);
+  let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+    label: Some("mipmap"),
+    bind_group_layouts: &[&layout],
+    push_constant_ranges: &[],
+  });
+  let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+    label: Some("mipmap"),
+    layout: Some(&pipeline_layout),
+    vertex: VertexState {
+      module: &shader,
+      entry_point: "vs_main",
+      buffers: &[],
+    },
+    fragment: Some(FragmentState {
+      module: &shader,
+      entry_point: "fs_main",
+      targets: &[Some(ColorTargetState {
+        format,
+        blend: None,
+        write_mask: ColorWrites::ALL,
+      })],
+    }),
+    primitive: PrimitiveState::default(),
+    depth_stencil: None,
+    multisample: MultisampleState::default(),
+    multiview: None,
+  });
+  let sampler = device.create_sampler(&SamplerDescriptor {
+    label: Some("mipmap"),
+    mag_filter: FilterMode::Linear,
+    min_filter: FilterMode::Linear,
+    ..Default::default()
+  });
+  let scratch = |width: u32, height: u32| device.create_texture(&TextureDescriptor {
+    label: Some("mipmap"),
+    size: Extent3d { width, height, depth_or_array_layers: 1 },
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: TextureDimension::D2,
+    format,
+    usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC | TextureUsages::COPY_DST,
+  });
+  // a level of a layer of `texture`
+  let level = |mip_level: u32, layer: u32| ImageCopyTexture {
+    texture,
+    mip_level,
+    origin: Origin3d { x: 0, y: 0, z: layer },
+    aspect: TextureAspect::All,
+  };
+  let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: Some("mipmap") });
+  let layer_size = (bytes_per_row * size.height) as usize;
+  for (layer, pixels) in (0..size.depth_or_array_layers).zip(pixels.chunks(layer_size)) {
+    let mut source = scratch(size.width, size.height);
+    queue.write_texture(source.as_image_copy(), pixels, data_layout, Extent3d { depth_or_array_layers: 1, ..size });
+    for mip_level in 1..levels {
+      let (width, height) = ((size.width >> mip_level).max(1), (size.height >> mip_level).max(1));
+      let target = scratch(width, height);
+      let source_view = source.create_view(&TextureViewDescriptor::default());
+      let target_view = target.create_view(&TextureViewDescriptor::default());
+      let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("mipmap"),
+        layout: &layout,
+        entries: &[
+          BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::TextureView(&source_view),
+          },
+          BindGroupEntry {
+            binding: 1,
+            resource: BindingResource::Sampler(&sampler),
+          },
+        ],
+      });
+      {
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+          label: Some("mipmap"),
+          color_attachments: &[Some(RenderPassColorAttachment {
+            view: &target_view,
+            resolve_target: None,
+            ops: Operations {
+              load: LoadOp::Clear(Color::TRANSPARENT),
+              store: true,
+            },
+          })],
+          depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+      }
+      encoder.copy_texture_to_texture(target.as_image_copy(), level(mip_level, layer), Extent3d { width, height, depth_or_array_layers: 1 });
+      source = target;
+    }
+  }
+  queue.submit(std::iter::once(encoder.finish()));
+}
@@ -0,0 +1,281 @@
+// WGSL modules written the way naga_oil's are, so shader code can be shared between projects as packages:
+//
+// #define_import_path sdf::shapes
+// fn circle(p: vec2<f32>, r: f32) -> f32 { ... }
+//
+// is found by shaders in the same directory or any --modules directory, which use it with `#import sdf::shapes` or
+// `#import sdf::shapes as shapes` and call `sdf::shapes::circle` or `shapes::circle`
+//
+// everything a module declares is renamed after its import path before it's pasted in, so two modules can each have a `circle`
+// without clashing, and nothing from a module is reachable without saying which one it came from
+//
+// naga_oil itself is built on a newer naga than the one wgpu 0.14 uses, so it can't compose Horus' shaders. Only its #define_import_path
+// and #import lines are understood here, and the rest of what it has, like importing items with braces and #ifdef, is an error
+
+use std::{collections::BTreeMap, fs::{self, read_to_string}, path::{Path, PathBuf}};
+
+// a line starting with this names the module the rest of the file is
+pub const DEFINE_IMPORT_PATH: &str = "#define_import_path";
+
+// a line starting with this pastes in a module
+pub const IMPORT: &str = "#import";
+
+// naga_oil's conditional lines, which Horus' modules don't have
+pub const CONDITIONALS: &[&str] = &["#ifdef", "#ifndef", "#if", "#else", "#endif"];
+
+// what an `#import` line asked for
+pub struct Import {
+  pub path: String,
+  // what the module's items are prefixed with in the file that imports it
+  pub alias: String,
+}
+
+impl Import {
+  pub fn parse(line: &str) -> Result<Self, String> {
+    if line.contains('{') || line.contains('"') {
+      return Err("#import only takes a whole module, so importing items with braces or a module by its file's path isn't supported".to_string());
+    }
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (path, alias) = match words[..] {
+      [path] => (path, path),
+      [path, "as", alias] => (path, alias),
+      _ => return Err("#import takes a module's path, like #import sdf::shapes or #import sdf::shapes as shapes".to_string()),
+    };
+    if !is_path(path) || !is_path(alias) {
+      return Err(format!("{} isn't a module's path, which is names separated by ::", line.trim()));
+    }
+    Ok(Import { path: path.to_string(), alias: alias.to_string() })
+  }
+}
+
+// whether `path` is one or more identifiers separated by ::
+pub fn is_path(path: &str) -> bool {
+  path.split("::").all(|name| name.chars().next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_') && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+}
+
+// the path that the module in `source` says it's imported by, if it's a module at all
+pub fn import_path(source: &str) -> Option<&str> {
+  source.lines().find_map(|line| line.trim_start().strip_prefix(DEFINE_IMPORT_PATH)).map(str::trim)
+}
+
+// the name an item declared by the module at `path` is given once it's pasted in
+pub fn mangle(path: &str, name: &str) -> String {
+  // every underscore in the path is followed by u and every :: becomes _s, so `a_b` and `a::b` aren't renamed the same, and nothing
+  // after the last _in_ can be part of the name. GLSL reserves names with two underscores in a row, which these end up in on the GL backend
+  format!("{}_in_{}", name, path.replace('_', "_u").replace("::", "_s"))
+}
+
+// every module in the shader's own directory `own` or anywhere inside of `modules`, by its import path, where the first one found wins
+pub fn find(own: Option<&Path>, modules: &[PathBuf]) -> BTreeMap<String, PathBuf> {
+  let mut found = BTreeMap::new();
+  // the shader's own directory could be anywhere, so only the files right inside it are read
+  let mut directories: Vec<(PathBuf, bool)> = own.map(|own| (own.to_path_buf(), false)).into_iter().chain(modules.iter().map(|directory| (directory.clone(), true))).collect();
+  directories.reverse();
+  while let Some((directory, recursive)) = directories.pop() {
+    let Ok(entries) = fs::read_dir(&directory) else {
+      continue;
+    };
+    let mut entries: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+    entries.sort();
+    for path in entries {
+      if path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+        continue;
+      }
+      if path.is_dir() {
+        if recursive {
+          directories.push((path, true));
+        }
+        continue;
+      }
+      if path.extension().is_some_and(|extension| extension == "wgsl") {
+        if let Some(name) = read_to_string(&path).ok().as_deref().and_then(import_path) {
+          // modules next to a shader that's given by its name alone are named the same way
+          found.entry(name.to_string()).or_insert_with(|| path.strip_prefix(".").map_or(path.clone(), Path::to_path_buf));
+        }
+      }
+    }
+  }
+  found
+}
+
+// a piece of WGSL, where comments are kept as they are
+enum Token<'a> {
+  // an identifier, or several of them joined by ::
+  Name(&'a str),
+  Comment(&'a str),
+  Other(&'a str),
+}
+
+fn tokens(source: &str) -> Vec<Token<'_>> {
+  let bytes = source.as_bytes();
+  let is_start = |byte: u8| byte.is_ascii_alphabetic() || byte == b'_';
+  let is_name = |byte: u8| byte.is_ascii_alphanumeric() || byte == b'_';
+  let mut tokens = Vec::new();
+  let (mut index, mut other) = (0, 0);
+  while index < bytes.len() {
+    let start = index;
+    if source[index..].starts_with("//") {
+      index = source[index..].find('\n').map_or(bytes.len(), |end| index + end);
+    } else if source[index..].starts_with("/*") {
+      // block comments nest in WGSL
+      let mut depth = 0;
+      while index < bytes.len() {
+        if source[index..].starts_with("/*") {
+          depth += 1;
+          index += 2;
+        } else if source[index..].starts_with("*/") {
+          depth -= 1;
+          index += 2;
+          if depth == 0 {
+            break;
+          }
+        } else {
+          index += 1;
+        }
+      }
+    } else if is_start(bytes[index]) && (index == 0 || !is_name(bytes[index - 1])) {
+      loop {
+        while index < bytes.len() && is_name(bytes[index]) {
+          index += 1;
+        }
+        match source[index..].strip_prefix("::") {
+          Some(rest) if rest.bytes().next().is_some_and(is_start) => index += 2,
+          _ => break,
+        }
+      }
+    } else {
+      index += source[index..].chars().next().map_or(1, char::len_utf8);
+      continue;
+    }
+    if other < start {
+      tokens.push(Token::Other(&source[other..start]));
+    }
+    let text = &source[start..index];
+    tokens.push(match text.starts_with('/') {
+      true => Token::Comment(text),
+      false => Token::Name(text),
+    });
+    other = index;
+  }
+  if other < bytes.len() {
+    tokens.push(Token::Other(&source[other..]));
+  }
+  tokens
+}
+
+// the names that `source` declares outside of any function or struct
+pub fn declarations(source: &str) -> Vec<String> {
+  let mut names = Vec::new();
+  let mut depth = 0;
+  // how many angle brackets are open since the keyword of a declaration, which `var<private> name` has before its name
+  let mut declaring: Option<i32> = None;
+  for token in tokens(source) {
+    match token {
+      Token::Name("fn" | "struct" | "let" | "const" | "var" | "override" | "type" | "alias") if depth == 0 && declaring.is_none() => declaring = Some(0),
+      Token::Name(name) if depth == 0 && declaring == Some(0) => {
+        names.push(name.to_string());
+        declaring = None;
+      }
+      Token::Name(_) | Token::Comment(_) => {}
+      Token::Other(text) => for character in text.chars() {
+        match (character, &mut declaring) {
+          ('{', _) => depth += 1,
+          ('}', _) => depth -= 1,
+          ('<', Some(angles)) => *angles += 1,
+          ('>', Some(angles)) => *angles -= 1,
+          _ => {}
+        }
+      },
+    }
+  }
+  names
+}
+
+// `source` with every path through one of `imports` replaced by the name the item it reaches is pasted in under, along with the
+// names of everything `module` declares if `source` is that module, given as its import path and the names it declares,
+// or the line that went wrong and why
+pub fn rewrite(source: &str, imports: &[(Import, Vec<String>)], module: Option<(&str, &[String])>) -> Result<String, (usize, String)> {
+  let mut rewritten = String::with_capacity(source.len());
+  // whether the braces that are open were opened by a struct, whose members keep their names
+  let mut braces: Vec<bool> = Vec::new();
+  let mut struct_next = false;
+  let tokens = tokens(source);
+  for (index, token) in tokens.iter().enumerate() {
+    let text = match token {
+      Token::Comment(text) => {
+        rewritten.push_str(text);
+        continue;
+      }
+      Token::Other(text) => {
+        for character in text.chars() {
+          match character {
+            '{' => braces.push(std::mem::take(&mut struct_next)),
+            '}' => {
+              braces.pop();
+            }
+            _ => {}
+          }
+        }
+        rewritten.push_str(text);
+        continue;
+      }
+      Token::Name(text) => *text,
+    };
+    if let Some((prefix, name)) = text.rsplit_once("::") {
+      let line = rewritten.matches('\n').count();
+      let (import, names) = imports.iter().find(|(import, _)| import.alias == prefix).ok_or((line, format!("{} isn't imported, which #import {} does", prefix, prefix)))?;
+      if !names.iter().any(|declared| declared == name) {
+        return Err((line, format!("{} doesn't declare {}", import.path, name)));
+      }
+      rewritten.push_str(&mangle(&import.path, name));
+      continue;
+    }
+    struct_next |= text == "struct" && braces.is_empty();
+    let previous = match index.checked_sub(1).map(|index| &tokens[index]) {
+      Some(Token::Other(text)) => text.trim_end().chars().last(),
+      _ => None,
+    };
+    let next = match tokens.get(index + 1) {
+      Some(Token::Other(text)) => text.trim_start().chars().next(),
+      _ => None,
+    };
+    // fields and attributes share names with declarations without being them
+    let keeps_name = matches!(previous, Some('.' | '@')) || (braces.last() == Some(&true) && next == Some(':'));
+    match module {
+      Some((path, names)) if !keeps_name && names.iter().any(|declared| declared == text) => rewritten.push_str(&mangle(path, text)),
+      _ => rewritten.push_str(text),
+    }
+  }
+  Ok(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_imports() {
+    let import = Import::parse(" sdf::shapes").unwrap();
+    assert_eq!((import.path.as_str(), import.alias.as_str()), ("sdf::shapes", "sdf::shapes"));
+    let import = Import::parse(" sdf::shapes as shapes\n").unwrap();
+    assert_eq!((import.path.as_str(), import.alias.as_str()), ("sdf::shapes", "shapes"));
+  }
+
+  #[test]
+  fn rejects_what_horus_modules_dont_have() {
+    assert!(Import::parse(" sdf::{circle, square}").is_err());
+    assert!(Import::parse(" \"sdf.wgsl\"").is_err());
+    assert!(Import::parse("").is_err());
+    assert!(Import::parse(" sdf::shapes as").is_err());
+    assert!(Import::parse(" sdf:shapes").is_err());
+    assert!(Import::parse(" 2d::shapes").is_err());
+  }
+
+  #[test]
+  fn mangles_every_path_differently() {
+    assert_eq!(mangle("a::b", "x"), "x_in_a_sb");
+    assert_eq!(mangle("a_b", "x"), "x_in_a_ub");
+    assert_ne!(mangle("a_s", "x"), mangle("a::s", "x"));
+    assert_ne!(mangle("a", "x_in_b"), mangle("b", "x_in_a"));
+  }
+}
@@ -0,0 +1,38 @@
+// picks the monitor the window opens on, which --monitor can choose by its index or part of its name, and the area --span covers
+// across every monitor at once
+
+use winit::{dpi::{PhysicalPosition, PhysicalSize}, event_loop::EventLoopWindowTarget, monitor::MonitorHandle};
+
+// the monitor `monitor` asks for, listing every one there is if it doesn't match any
+pub fn find<T>(event_loop: &EventLoopWindowTarget<T>, monitor: &str) -> Result<MonitorHandle, String> {
+  // a number is the monitor's index, and anything else is part of its name
+  let found = match monitor.parse::<usize>() {
+    Ok(index) => event_loop.available_monitors().nth(index),
+    Err(_) => event_loop.available_monitors().find(|handle| handle.name().is_some_and(|name| name.to_lowercase().contains(&monitor.to_lowercase()))),
+  };
+  let Some(handle) = found else {
+    let names: Vec<_> = event_loop.available_monitors().enumerate().map(|(index, handle)| format!("  {}: {}", index, describe(&handle))).collect();
+    return Err(match names.is_empty() {
+      true => "Unable to find any monitors".to_string(),
+      false => format!("Unable to find the monitor {:?}, which can be one of these:\n{}", monitor, names.join("\n")),
+    });
+  };
+  println!("[Horus] Opening on {}", describe(&handle));
+  Ok(handle)
+}
+
+// the smallest rectangle around every monitor, in the desktop's pixels, if there are any
+pub fn span<T>(event_loop: &EventLoopWindowTarget<T>) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+  let bounds = event_loop.available_monitors().map(|handle| {
+    let (position, size) = (handle.position(), handle.size());
+    [position.x, position.y, position.x + size.width as i32, position.y + size.height as i32]
+  }).reduce(|first, second| [first[0].min(second[0]), first[1].min(second[1]), first[2].max(second[2]), first[3].max(second[3])]);
+  let [left, top, right, bottom] = bounds?;
+  println!("[Horus] Spanning {}x{} across every monitor", right - left, bottom - top);
+  Some((PhysicalPosition::new(left, top), PhysicalSize::new((right - left) as u32, (bottom - top) as u32)))
+}
+
+fn describe(handle: &MonitorHandle) -> String {
+  let (position, size) = (handle.position(), handle.size());
+  format!("{} ({}x{} at {}, {})", handle.name().unwrap_or_else(|| "an unnamed monitor".to_string()), size.width, size.height, position.x, position.y)
+}
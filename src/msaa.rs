@@ -0,0 +1,55 @@
+// with --msaa, the image pass renders into a multisampled texture that's resolved into the frame, which smooths the edges of anything
+// that's drawn per sample rather than per pixel, like shadertoy images, which are made to run per sample by reading gl_SampleID
+
+use wgpu::{Device, ErrorFilter, Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor};
+
+pub struct Multisample {
+  samples: u32,
+  format: TextureFormat,
+  view: TextureView,
+}
+
+impl Multisample {
+  // a `width` by `height` target with `samples` samples per pixel, if the GPU supports that many for `format`
+  pub fn new(device: &Device, format: TextureFormat, samples: u32, width: u32, height: u32) -> Result<Self, String> {
+    device.push_error_scope(ErrorFilter::Validation);
+    let view = create_view(device, format, samples, width, height);
+    match pollster::block_on(device.pop_error_scope()) {
+      Some(error) => Err(error.to_string()),
+      None => Ok(Multisample { samples, format, view }),
+    }
+  }
+
+  pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+    self.view = create_view(device, self.format, self.samples, width, height);
+  }
+
+  pub fn samples(&self) -> u32 {
+    self.samples
+  }
+
+  // what the image pass renders into before it's resolved
+  pub fn view(&self) -> &TextureView {
+    &self.view
+  }
+}
+
+fn create_view(device: &Device, format: TextureFormat, samples: u32, width: u32, height: u32) -> TextureView {
+  device.create_texture(&TextureDescriptor {
+    label: Some("multisample"),
+    size: Extent3d { width, height, depth_or_array_layers: 1 },
+    mip_level_count: 1,
+    sample_count: samples,
+    dimension: TextureDimension::D2,
+    format,
+    usage: TextureUsages::RENDER_ATTACHMENT,
+  }).create_view(&TextureViewDescriptor::default())
+}
+
+// the multisampled target for --msaa, falling back to a sample per pixel if the GPU doesn't support as many as were asked for
+pub fn create(device: &Device, format: TextureFormat, samples: Option<u32>, width: u32, height: u32) -> Option<Multisample> {
+  let samples = samples.filter(|samples| *samples > 1)?;
+  Multisample::new(device, format, samples, width, height).map_err(|error| {
+    println!("[Horus] Unable to use {}x MSAA, so it's turned off: {}", samples, error);
+  }).ok()
+}
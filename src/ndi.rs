@@ -0,0 +1,139 @@
+// with --ndi, every frame is sent over the network as an NDI source that OBS, vMix, Resolume and anything else with NDI can receive
+//
+// the NDI runtime is loaded when Horus starts sending rather than linked, since it's installed separately from https://ndi.video
+// and most people running Horus won't have it
+
+#[cfg(feature = "ndi")]
+use std::{ffi::{c_char, c_void, CString}, ptr};
+#[cfg(feature = "ndi")]
+use libloading::Library;
+use image::RgbaImage;
+
+// NDI's four character code for 8 bit RGBA, which is how frames are read back
+#[cfg(feature = "ndi")]
+const FOURCC_RGBA: u32 = u32::from_le_bytes(*b"RGBA");
+#[cfg(feature = "ndi")]
+const FRAME_FORMAT_PROGRESSIVE: i32 = 1;
+// asks NDI to fill in the timecode itself
+#[cfg(feature = "ndi")]
+const TIMECODE_SYNTHESIZE: i64 = i64::MAX;
+
+// where the NDI runtime is on each platform, which NDI_RUNTIME_DIR_V6 or NDI_RUNTIME_DIR_V5 point to if it was installed somewhere else
+#[cfg(all(feature = "ndi", target_os = "windows"))]
+const LIBRARY_NAMES: &[&str] = &["Processing.NDI.Lib.x64.dll"];
+#[cfg(all(feature = "ndi", target_os = "macos"))]
+const LIBRARY_NAMES: &[&str] = &["libndi.dylib", "/usr/local/lib/libndi.dylib"];
+#[cfg(all(feature = "ndi", not(any(target_os = "windows", target_os = "macos"))))]
+const LIBRARY_NAMES: &[&str] = &["libndi.so.6", "libndi.so.5", "libndi.so"];
+
+#[cfg(feature = "ndi")]
+#[repr(C)]
+struct SendCreate {
+  name: *const c_char,
+  groups: *const c_char,
+  clock_video: bool,
+  clock_audio: bool,
+}
+
+#[cfg(feature = "ndi")]
+#[repr(C)]
+struct VideoFrame {
+  width: i32,
+  height: i32,
+  fourcc: u32,
+  frame_rate_numerator: i32,
+  frame_rate_denominator: i32,
+  aspect_ratio: f32,
+  frame_format: i32,
+  timecode: i64,
+  data: *const u8,
+  line_stride: i32,
+  metadata: *const c_char,
+  timestamp: i64,
+}
+
+pub struct NdiSender {
+  #[cfg(feature = "ndi")]
+  instance: *mut c_void,
+  #[cfg(feature = "ndi")]
+  send_video: unsafe extern "C" fn(*mut c_void, *const VideoFrame),
+  #[cfg(feature = "ndi")]
+  destroy: unsafe extern "C" fn(*mut c_void),
+  // the functions above live in the library, so it's dropped last
+  #[cfg(feature = "ndi")]
+  _library: Library,
+  // how many frames a second receivers are told to expect
+  #[cfg_attr(not(feature = "ndi"), allow(dead_code))]
+  fps: u32,
+}
+
+impl NdiSender {
+  // start sending an NDI source called `name`, which receivers list as "MACHINE (name)"
+  #[cfg_attr(not(feature = "ndi"), allow(unused_variables))]
+  pub fn new(name: &str, fps: u32) -> Result<Self, String> {
+    #[cfg(not(feature = "ndi"))]
+    return Err("Horus was built without the ndi feature".to_string());
+    #[cfg(feature = "ndi")]
+    {
+      let library = load_library()?;
+      let name = CString::new(name).map_err(|error| error.to_string())?;
+      // the functions are looked up by the names and signatures in NDI's SDK headers
+      unsafe {
+        let initialize = *library.get::<unsafe extern "C" fn() -> bool>(b"NDIlib_initialize\0").map_err(|error| error.to_string())?;
+        let create = *library.get::<unsafe extern "C" fn(*const SendCreate) -> *mut c_void>(b"NDIlib_send_create\0").map_err(|error| error.to_string())?;
+        let send_video = *library.get(b"NDIlib_send_send_video_v2\0").map_err(|error| error.to_string())?;
+        let destroy = *library.get(b"NDIlib_send_destroy\0").map_err(|error| error.to_string())?;
+        if !initialize() {
+          return Err("NDI isn't supported on this CPU".to_string());
+        }
+        // frames are paced by the window, so NDI doesn't need to hold them back
+        let instance = create(&SendCreate { name: name.as_ptr(), groups: ptr::null(), clock_video: false, clock_audio: false });
+        if instance.is_null() {
+          return Err("NDI was unable to create a source".to_string());
+        }
+        Ok(NdiSender { instance, send_video, destroy, _library: library, fps })
+      }
+    }
+  }
+
+  // send `frame` to every receiver, which NDI copies before this returns
+  #[cfg_attr(not(feature = "ndi"), allow(unused_variables))]
+  pub fn send(&mut self, frame: &RgbaImage) {
+    #[cfg(feature = "ndi")]
+    unsafe {
+      (self.send_video)(self.instance, &VideoFrame {
+        width: frame.width() as i32,
+        height: frame.height() as i32,
+        fourcc: FOURCC_RGBA,
+        frame_rate_numerator: self.fps as i32,
+        frame_rate_denominator: 1,
+        aspect_ratio: frame.width() as f32 / frame.height() as f32,
+        frame_format: FRAME_FORMAT_PROGRESSIVE,
+        timecode: TIMECODE_SYNTHESIZE,
+        data: frame.as_raw().as_ptr(),
+        line_stride: frame.width() as i32 * 4,
+        metadata: ptr::null(),
+        timestamp: 0,
+      });
+    }
+  }
+}
+
+#[cfg(feature = "ndi")]
+impl Drop for NdiSender {
+  fn drop(&mut self) {
+    unsafe { (self.destroy)(self.instance) };
+  }
+}
+
+#[cfg(feature = "ndi")]
+fn load_library() -> Result<Library, String> {
+  let directories: Vec<_> = ["NDI_RUNTIME_DIR_V6", "NDI_RUNTIME_DIR_V5"].into_iter().filter_map(std::env::var_os).collect();
+  let paths = directories.iter().flat_map(|directory| LIBRARY_NAMES.iter().map(move |name| std::path::Path::new(directory).join(name).into_os_string()));
+  for path in paths.chain(LIBRARY_NAMES.iter().map(Into::into)) {
+    if let Ok(library) = unsafe { Library::new(&path) } {
+      return Ok(library);
+    }
+  }
+  Err("the NDI runtime isn't installed, which comes from https://ndi.video".to_string())
+}
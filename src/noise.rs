@@ -0,0 +1,67 @@
+// noise textures generated at startup that match the ones shadertoy offers as channel inputs
+
+// the names that can be passed to --channel0 through --channel3 instead of a path
+pub const NAMES: [&str; 5] = ["noise-rgba-64", "noise-rgba-256", "noise-gray-64", "noise-gray-256", "noise-value-256"];
+
+// xorshift* is plenty random for textures and always produces the same noise
+struct Random(u64);
+
+impl Random {
+  fn next(&mut self) -> u8 {
+    self.0 ^= self.0 >> 12;
+    self.0 ^= self.0 << 25;
+    self.0 ^= self.0 >> 27;
+    (self.0.wrapping_mul(0x2545F4914F6CDD1D) >> 56) as u8
+  }
+}
+
+// the noise every seed is mixed into, which is what seed 0 produces
+const SEED: u64 = 0x9E3779B97F4A7C15;
+
+// the size and RGBA pixels of a built in noise texture generated from `seed`, or `None` if there isn't one named `name`
+pub fn generate(name: &str, seed: u64) -> Option<(u32, Vec<u8>)> {
+  // xorshift never leaves a state of 0
+  let mut random = Random(match SEED ^ seed {
+    0 => SEED,
+    state => state,
+  });
+  let (size, pixels) = match name {
+    "noise-rgba-64" => (64, rgba(&mut random, 64)),
+    "noise-rgba-256" => (256, rgba(&mut random, 256)),
+    "noise-gray-64" => (64, gray(&mut random, 64)),
+    "noise-gray-256" => (256, gray(&mut random, 256)),
+    "noise-value-256" => (256, value(&mut random, 256, 16)),
+    _ => return None,
+  };
+  Some((size as u32, pixels))
+}
+
+// every channel of every pixel is independent
+fn rgba(random: &mut Random, size: usize) -> Vec<u8> {
+  (0..size * size * 4).map(|_| random.next()).collect()
+}
+
+fn gray(random: &mut Random, size: usize) -> Vec<u8> {
+  (0..size * size).flat_map(|_| {
+    let value = random.next();
+    [value, value, value, u8::MAX]
+  }).collect()
+}
+
+// random values on a grid `cell` pixels apart, smoothly interpolated and wrapped so the texture tiles
+fn value(random: &mut Random, size: usize, cell: usize) -> Vec<u8> {
+  let cells = size / cell;
+  let lattice: Vec<f32> = (0..cells * cells).map(|_| random.next() as f32).collect();
+  let at = |x: usize, y: usize| lattice[(y % cells) * cells + x % cells];
+  (0..size * size).flat_map(|index| {
+    let (x, y) = (index % size, index / size);
+    let (cx, cy) = (x / cell, y / cell);
+    let smooth = |t: f32| t * t * (3. - 2. * t);
+    let tx = smooth((x % cell) as f32 / cell as f32);
+    let ty = smooth((y % cell) as f32 / cell as f32);
+    let top = at(cx, cy) + (at(cx + 1, cy) - at(cx, cy)) * tx;
+    let bottom = at(cx, cy + 1) + (at(cx + 1, cy + 1) - at(cx, cy + 1)) * tx;
+    let value = (top + (bottom - top) * ty) as u8;
+    [value, value, value, u8::MAX]
+  }).collect()
+}
@@ -0,0 +1,76 @@
+// listens for OSC messages like `/horus/uniform/speed 0.5` over UDP and routes them into the custom uniforms
+
+use std::{net::UdpSocket, sync::mpsc::{self, Receiver, Sender}, thread};
+use rosc::{OscPacket, OscType};
+
+use crate::params::Params;
+
+// messages to this address followed by a uniform's name set that uniform
+const ADDRESS: &str = "/horus/uniform/";
+
+pub struct Osc {
+  // the uniform each message was addressed to and the numbers it carried
+  messages: Receiver<(String, Vec<f32>)>,
+  // names that have already been complained about, since rigs tend to send the same message many times a second
+  unknown: Vec<String>,
+}
+
+impl Osc {
+  pub fn listen(port: u16) -> Result<Self, String> {
+    let socket = UdpSocket::bind(("0.0.0.0", port)).map_err(|error| error.to_string())?;
+    let (sender, messages) = mpsc::channel();
+    thread::spawn(move || {
+      let mut buffer = [0; rosc::decoder::MTU];
+      loop {
+        let Ok((size, _)) = socket.recv_from(&mut buffer) else {
+          continue;
+        };
+        match rosc::decoder::decode_udp(&buffer[..size]) {
+          Ok((_, packet)) => if !route(packet, &sender) {
+            return;
+          },
+          Err(error) => println!("[Horus] Ignoring an OSC packet that can't be read: {}", error),
+        }
+      }
+    });
+    println!("[Horus] Listening for OSC messages on port {}", port);
+    Ok(Osc { messages, unknown: Vec::new() })
+  }
+
+  // set the uniforms from every message that came in since the last frame
+  pub fn update(&mut self, params: &mut Params) {
+    for (name, value) in self.messages.try_iter() {
+      match params.index_of(&name) {
+        Some(index) => params.set(index, &value),
+        None if !self.unknown.contains(&name) => {
+          println!("[Horus] Ignoring OSC messages to {}{} since there's no uniform called {}", ADDRESS, name, name);
+          self.unknown.push(name);
+        }
+        None => {}
+      }
+    }
+  }
+}
+
+// send the uniform messages in `packet` to the event loop, returning whether it's still listening
+fn route(packet: OscPacket, sender: &Sender<(String, Vec<f32>)>) -> bool {
+  match packet {
+    // bundles are applied as soon as they arrive rather than waiting for their time tag
+    OscPacket::Bundle(bundle) => bundle.content.into_iter().all(|packet| route(packet, sender)),
+    OscPacket::Message(message) => {
+      let Some(name) = message.addr.strip_prefix(ADDRESS) else {
+        return true;
+      };
+      let value = message.args.iter().flat_map(|arg| match arg {
+        OscType::Float(value) => vec![*value],
+        OscType::Double(value) => vec![*value as f32],
+        OscType::Int(value) => vec![*value as f32],
+        OscType::Long(value) => vec![*value as f32],
+        OscType::Bool(value) => vec![if *value { 1. } else { 0. }],
+        OscType::Color(color) => [color.red, color.green, color.blue].iter().map(|&channel| channel as f32 / 255.).collect(),
+        _ => Vec::new(),
+      }).collect();
+      sender.send((name.to_string(), value)).is_ok()
+    }
+  }
+}
@@ -0,0 +1,168 @@
+use font8x8::legacy::BASIC_LEGACY;
+use wgpu::{BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, BlendState, ColorTargetState, ColorWrites, Device, Extent3d, FragmentState, MultisampleState, PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPass, RenderPipeline, RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages, TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureViewDescriptor, TextureViewDimension, VertexState, util::DeviceExt};
+
+// glyphs are 8x8 pixels, which keeps the largest texture within the default limits at 1024 columns and rows
+const GLYPH_SIZE: usize = 8;
+const MAX_COLUMNS: usize = 1024;
+const MAX_ROWS: usize = 1024;
+
+const SOURCE: &str = "\
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var text: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var vertices = array<vec2<f32>, 3>(
+        vec2<f32>(-1., 1.),
+        vec2<f32>(3., 1.),
+        vec2<f32>(-1., -3.),
+    );
+    var out: VertexOutput;
+    out.position = vec4<f32>(vertices[vertex_index], 0., 1.);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // every pixel of the text covers 2x2 pixels of the window, 16 pixels in from the top left corner
+    let size = vec2<f32>(textureDimensions(text));
+    let pixel = (in.position.xy - 16.) / 2.;
+
+    // draw a dark box with a little padding behind the text
+    if (any(pixel < vec2<f32>(-4.)) || any(pixel >= size + 4.)) {
+        discard;
+    }
+    var coverage = 0.;
+    if (all(pixel >= vec2<f32>(0.)) && all(pixel < size)) {
+        coverage = textureLoad(text, vec2<i32>(pixel), 0).r;
+    }
+    return mix(vec4<f32>(0., 0., 0., 0.8), vec4<f32>(1.), coverage);
+}\
+";
+
+// draws a block of text over the top left corner of the frame
+pub struct TextOverlay {
+  pipeline: RenderPipeline,
+  layout: BindGroupLayout,
+  // the rasterized text, or `None` while there is nothing to show
+  bind_group: Option<BindGroup>,
+}
+
+impl TextOverlay {
+  pub fn new(device: &Device, format: TextureFormat, samples: u32) -> Self {
+    let shader = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("text overlay"),
+      source: ShaderSource::Wgsl(SOURCE.into()),
+    });
+    let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: Some("text overlay"),
+      entries: &[BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::FRAGMENT,
+        count: None,
+        ty: BindingType::Texture {
+          sample_type: TextureSampleType::Float { filterable: false },
+          view_dimension: TextureViewDimension::D2,
+          multisampled: false,
+        },
+      }],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+      label: Some("text overlay"),
+      bind_group_layouts: &[&layout],
+      push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("text overlay"),
+      layout: Some(&pipeline_layout),
+      vertex: VertexState {
+        module: &shader,
+        entry_point: "vs_main",
+        buffers: &[],
+      },
+      fragment: Some(FragmentState {
+        module: &shader,
+        entry_point: "fs_main",
+        targets: &[Some(ColorTargetState {
+          format,
+          blend: Some(BlendState::ALPHA_BLENDING),
+          write_mask: ColorWrites::ALL,
+        })],
+      }),
+      primitive: PrimitiveState::default(),
+      depth_stencil: None,
+      multisample: MultisampleState {
+        count: samples,
+        ..Default::default()
+      },
+      multiview: None,
+    });
+    TextOverlay { pipeline, layout, bind_group: None }
+  }
+
+  // replace the text being shown, an empty string hides the overlay
+  pub fn set_text(&mut self, device: &Device, queue: &Queue, text: &str) {
+    if text.trim().is_empty() {
+      self.bind_group = None;
+      return;
+    }
+    let (width, height, pixels) = rasterize(text);
+    let texture = device.create_texture_with_data(queue, &TextureDescriptor {
+      label: Some("text overlay"),
+      size: Extent3d { width, height, depth_or_array_layers: 1 },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D2,
+      format: TextureFormat::R8Unorm,
+      usage: TextureUsages::TEXTURE_BINDING,
+    }, &pixels);
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    self.bind_group = Some(device.create_bind_group(&BindGroupDescriptor {
+      label: Some("text overlay"),
+      layout: &self.layout,
+      entries: &[BindGroupEntry {
+        binding: 0,
+        resource: BindingResource::TextureView(&view),
+      }],
+    }));
+  }
+
+  pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+    if let Some(bind_group) = &self.bind_group {
+      render_pass.set_pipeline(&self.pipeline);
+      render_pass.set_bind_group(0, bind_group, &[]);
+      render_pass.draw(0..3, 0..1);
+    }
+  }
+}
+
+// render text with an 8x8 bitmap font into a single channel image, returning its width, height and pixels
+fn rasterize(text: &str) -> (u32, u32, Vec<u8>) {
+  let lines: Vec<Vec<char>> = text.lines()
+    .take(MAX_ROWS)
+    .map(|line| line.replace('\t', "    ").chars().take(MAX_COLUMNS).collect())
+    .collect();
+  let columns = lines.iter().map(|line| line.len()).max().unwrap_or(0).max(1);
+  let width = columns * GLYPH_SIZE;
+  let height = lines.len().max(1) * GLYPH_SIZE;
+
+  let mut pixels = vec![0u8; width * height];
+  for (row, line) in lines.iter().enumerate() {
+    for (column, character) in line.iter().enumerate() {
+      // the basic font only covers ASCII
+      let glyph = BASIC_LEGACY.get(*character as usize).unwrap_or(&BASIC_LEGACY['?' as usize]);
+      for (y, bits) in glyph.iter().enumerate() {
+        for x in 0..GLYPH_SIZE {
+          if bits & (1 << x) != 0 {
+            pixels[(row * GLYPH_SIZE + y) * width + column * GLYPH_SIZE + x] = u8::MAX;
+          }
+        }
+      }
+    }
+  }
+  (width as u32, height as u32, pixels)
+}
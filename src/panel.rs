@@ -0,0 +1,210 @@
+// an egui window in the top right corner with a control for every custom uniform, shown and hidden with F2,
+// and another in the top left with how fast frames are being drawn, shown and hidden with F1
+
+use egui::{Align2, ClippedPrimitive, Color32, Context, DragValue, Pos2, Sense, Shape, Slider, Stroke, TexturesDelta, Vec2};
+use egui_wgpu::renderer::{Renderer, ScreenDescriptor};
+use wgpu::{CommandEncoder, Device, LoadOp, Operations, Queue, RenderPassColorAttachment, RenderPassDescriptor, TextureFormat, TextureView};
+use winit::{event::{ElementState, WindowEvent}, event_loop::EventLoopWindowTarget, window::Window};
+
+use crate::{params::{ParamKind, Params}, stats::{FrameRate, HISTORY}};
+
+// buttons and keys that affect more than the panel, which the event loop carries out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+  TogglePause,
+  Restart,
+  // move time forwards or backwards by this many seconds
+  Seek(f32),
+  // move a single frame forwards while paused
+  Step,
+  // change how many seconds of the shader's time pass for every real one
+  SetSpeed(f32),
+}
+
+// the slowest and fastest time can pass, from the panel or the keyboard
+pub const MIN_SPEED: f32 = 1. / 64.;
+pub const MAX_SPEED: f32 = 64.;
+
+// the frame time at the top of the stats graph, which is two frames at 60fps
+const GRAPH_MAX: f32 = 2. / 60.;
+
+// what's happening while the shader runs, for the panel and the stats overlay to show
+pub struct Status<'a> {
+  pub paused: bool,
+  pub time: f32,
+  pub speed: f32,
+  pub resolution: [f32; 2],
+  pub mouse: [f32; 2],
+  pub frame_rate: &'a FrameRate,
+  // how long the GPU spent on the last frame it was timed on, separately from the CPU and presenting
+  pub gpu: Option<f32>,
+}
+
+pub struct Panel {
+  context: Context,
+  state: egui_winit::State,
+  renderer: Renderer,
+  pub visible: bool,
+  // whether the stats overlay is shown
+  pub stats: bool,
+  // what was laid out this frame, waiting to be drawn over it
+  output: Option<(Vec<ClippedPrimitive>, TexturesDelta, ScreenDescriptor)>,
+}
+
+impl Panel {
+  pub fn new<T>(event_loop: &EventLoopWindowTarget<T>, window: &Window, device: &Device, format: TextureFormat) -> Self {
+    let mut state = egui_winit::State::new(event_loop);
+    state.set_pixels_per_point(window.scale_factor() as f32);
+    state.set_max_texture_side(device.limits().max_texture_dimension_2d as usize);
+    Panel { context: Context::default(), state, renderer: Renderer::new(device, format, None, 1), visible: false, stats: false, output: None }
+  }
+
+  // pass a window event to the panel, returning whether it used it so the shader shouldn't see it too
+  pub fn input(&mut self, event: &WindowEvent) -> bool {
+    // egui only takes its input when it's shown, so anything before then would pile up
+    if !self.visible && !self.stats {
+      return false;
+    }
+    let response = self.state.on_event(&self.context, event);
+    // the shader still needs to know the button was released if it was pressed outside of the panel
+    response.consumed && !matches!(event, WindowEvent::MouseInput { state: ElementState::Released, .. })
+  }
+
+  // lay out this frame's panel, changing any uniforms that were dragged and returning the buttons that were pressed
+  pub fn update(&mut self, window: &Window, params: &mut Params, status: &Status) -> Vec<Action> {
+    self.output = None;
+    if !self.visible && !self.stats {
+      return Vec::new();
+    }
+    let Status { paused, time, speed, .. } = *status;
+    let mut actions = Vec::new();
+    let input = self.state.take_egui_input(window);
+    let output = self.context.run(input, |context| {
+      if self.stats {
+        show_stats(context, status);
+      }
+      if !self.visible {
+        return;
+      }
+      egui::Window::new("Horus").anchor(Align2::RIGHT_TOP, [-16., 16.]).resizable(false).show(context, |ui| {
+        ui.horizontal(|ui| {
+          if ui.button(if paused { "Resume" } else { "Pause" }).clicked() {
+            actions.push(Action::TogglePause);
+          }
+          if paused && ui.button("Step").clicked() {
+            actions.push(Action::Step);
+          }
+          if ui.button("Restart").clicked() {
+            actions.push(Action::Restart);
+          }
+          ui.label(format!("{:.2}s", time));
+        });
+        let mut new_speed = speed;
+        if ui.add(Slider::new(&mut new_speed, MIN_SPEED..=MAX_SPEED).logarithmic(true).text("speed")).changed() {
+          actions.push(Action::SetSpeed(new_speed));
+        }
+        if params.params.is_empty() {
+          return;
+        }
+        ui.separator();
+        let mut changes = Vec::new();
+        for (index, param) in params.params.iter().enumerate() {
+          let mut value = param.value.clone();
+          let changed = match param.kind {
+            ParamKind::Float => ui.add(Slider::new(&mut value[0], param.min..=param.max).text(&param.name)).changed(),
+            ParamKind::Color => ui.horizontal(|ui| {
+              let mut rgb = [value[0], value[1], value[2]];
+              let changed = ui.color_edit_button_rgb(&mut rgb).changed();
+              value.copy_from_slice(&rgb);
+              ui.label(&param.name);
+              changed
+            }).inner,
+            ParamKind::Vec2 | ParamKind::Vec3 | ParamKind::Vec4 => ui.horizontal(|ui| {
+              // dragging across the whole range takes about 200 pixels, like the sliders
+              let speed = (param.max - param.min) / 200.;
+              let mut changed = false;
+              for component in &mut value {
+                changed |= ui.add(DragValue::new(component).speed(speed).clamp_range(param.min..=param.max)).changed();
+              }
+              ui.label(&param.name);
+              changed
+            }).inner,
+          };
+          if changed {
+            changes.push((index, value));
+          }
+        }
+        for (index, value) in changes {
+          params.set(index, &value);
+        }
+        if ui.button("Reset").clicked() {
+          params.reset();
+        }
+      });
+    });
+    self.state.handle_platform_output(window, &self.context, output.platform_output);
+    let screen = ScreenDescriptor { size_in_pixels: [window.inner_size().width, window.inner_size().height], pixels_per_point: self.state.pixels_per_point() };
+    self.output = Some((self.context.tessellate(output.shapes), output.textures_delta, screen));
+    actions
+  }
+
+  // draw what was laid out this frame over `view`
+  pub fn draw(&mut self, device: &Device, queue: &Queue, encoder: &mut CommandEncoder, view: &TextureView) {
+    let Some((primitives, textures, screen)) = self.output.take() else {
+      return;
+    };
+    for (id, delta) in &textures.set {
+      self.renderer.update_texture(device, queue, *id, delta);
+    }
+    // egui's paint callbacks aren't used, so there are never any extra command buffers
+    let _ = self.renderer.update_buffers(device, queue, encoder, &primitives, &screen);
+    {
+      let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("panel"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+          view,
+          resolve_target: None,
+          ops: Operations {
+            load: LoadOp::Load,
+            store: true,
+          },
+        })],
+        depth_stencil_attachment: None,
+      });
+      self.renderer.render(&mut render_pass, &primitives, &screen);
+    }
+    for id in &textures.free {
+      self.renderer.free_texture(id);
+    }
+  }
+}
+
+// the frame rate, how long the GPU took, and a graph of how long each recent frame took, along with the resolution, time and mouse position
+fn show_stats(context: &Context, status: &Status) {
+  egui::Window::new("Stats").anchor(Align2::LEFT_TOP, [16., 16.]).resizable(false).show(context, |ui| {
+    let times = status.frame_rate.times();
+    let last = times.back().copied().unwrap_or(0.);
+    match status.frame_rate.fps() {
+      Some(fps) => ui.label(format!("{:.0} fps, {:.2} ms", fps, last * 1000.)),
+      None => ui.label(format!("{:.2} ms", last * 1000.)),
+    };
+    match status.gpu {
+      Some(gpu) => ui.label(format!("gpu {:.2} ms", gpu)),
+      None => ui.label("gpu time isn't available"),
+    };
+    let (response, painter) = ui.allocate_painter(Vec2::new(240., 60.), Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 0., Color32::from_black_alpha(128));
+    // a line where frames take as long as they do at 60fps
+    let target = rect.bottom() - rect.height() * (1. / 60.) / GRAPH_MAX;
+    painter.hline(rect.x_range(), target, Stroke::new(1., Color32::DARK_GRAY));
+    let step = rect.width() / HISTORY as f32;
+    let points = times.iter().enumerate().map(|(index, time)| {
+      Pos2::new(rect.left() + index as f32 * step, rect.bottom() - (time / GRAPH_MAX).min(1.) * rect.height())
+    }).collect();
+    painter.add(Shape::line(points, Stroke::new(1., Color32::LIGHT_GREEN)));
+    ui.label(format!("{} x {}", status.resolution[0], status.resolution[1]));
+    ui.label(format!("{:.2}s", status.time));
+    ui.label(format!("mouse {:.0}, {:.0}", status.mouse[0], status.mouse[1]));
+  });
+}
@@ -0,0 +1,336 @@
+// extra uniforms declared in a toml file next to the image's shader or in horus.toml, bound to group 2 of every pass
+//
+// [[uniform]]
+// name = "speed"
+// type = "float"
+// default = 1.0
+// min = 0.0
+// max = 4.0
+
+use std::{fs::read_to_string, path::{Path, PathBuf}};
+use serde::Deserialize;
+use wgpu::{BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferUsages, Device, Queue, ShaderStages, util::{BufferInitDescriptor, DeviceExt}};
+
+// the types a parameter can have, colors being an RGB vec3 that's always between 0 and 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamKind {
+  Float,
+  Vec2,
+  Vec3,
+  Vec4,
+  Color,
+}
+
+impl ParamKind {
+  pub fn components(self) -> usize {
+    match self {
+      ParamKind::Float => 1,
+      ParamKind::Vec2 => 2,
+      ParamKind::Vec3 | ParamKind::Color => 3,
+      ParamKind::Vec4 => 4,
+    }
+  }
+
+  // where WGSL and GLSL's std140 layout both place a member of this type
+  fn alignment(self) -> usize {
+    match self {
+      ParamKind::Float => 4,
+      ParamKind::Vec2 => 8,
+      ParamKind::Vec3 | ParamKind::Vec4 | ParamKind::Color => 16,
+    }
+  }
+
+  fn wgsl(self) -> &'static str {
+    match self {
+      ParamKind::Float => "f32",
+      ParamKind::Vec2 => "vec2<f32>",
+      ParamKind::Vec3 | ParamKind::Color => "vec3<f32>",
+      ParamKind::Vec4 => "vec4<f32>",
+    }
+  }
+
+  fn glsl(self) -> &'static str {
+    match self {
+      ParamKind::Float => "float",
+      ParamKind::Vec2 => "vec2",
+      ParamKind::Vec3 | ParamKind::Color => "vec3",
+      ParamKind::Vec4 => "vec4",
+    }
+  }
+
+  fn hlsl(self) -> &'static str {
+    match self {
+      ParamKind::Float => "float",
+      ParamKind::Vec2 => "float2",
+      ParamKind::Vec3 | ParamKind::Color => "float3",
+      ParamKind::Vec4 => "float4",
+    }
+  }
+}
+
+// a single number is the same for every component
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Components {
+  One(f32),
+  Each(Vec<f32>),
+}
+
+// the MIDI controller that sets a float, or one for each component of a vector
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Controllers {
+  One(u8),
+  Each(Vec<u8>),
+}
+
+// a parameter as it's written in the toml file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Declaration {
+  name: String,
+  #[serde(rename = "type")]
+  kind: ParamKind,
+  default: Option<Components>,
+  min: Option<f32>,
+  max: Option<f32>,
+  midi: Option<Controllers>,
+}
+
+// horus.toml can declare uniforms alongside everything else, so the rest of the file is left alone
+#[derive(Deserialize)]
+struct Declarations {
+  #[serde(default)]
+  uniform: Vec<Declaration>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Param {
+  pub name: String,
+  pub kind: ParamKind,
+  pub default: Vec<f32>,
+  pub value: Vec<f32>,
+  pub min: f32,
+  pub max: f32,
+  // the MIDI controller that moves each component between min and max, if any do
+  pub midi: Vec<u8>,
+  // how many bytes into the buffer it's stored
+  offset: usize,
+}
+
+// read the parameters out of a toml file's contents, in the order they're declared
+fn parse(source: &str) -> Result<Vec<Param>, String> {
+  let declarations: Declarations = toml::from_str(source).map_err(|error| error.to_string())?;
+  let mut params: Vec<Param> = Vec::new();
+  let mut offset: usize = 0;
+  for Declaration { name, kind, default, min, max, midi } in declarations.uniform {
+    let is_identifier = name.chars().next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_') && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_identifier {
+      return Err(format!("{:?} can't be used as a uniform's name", name));
+    }
+    if params.iter().any(|param| param.name == name) {
+      return Err(format!("there's more than one uniform called {}", name));
+    }
+    let (min, max) = match kind {
+      ParamKind::Color => (0., 1.),
+      _ => (min.unwrap_or(0.), max.unwrap_or(1.)),
+    };
+    if min > max {
+      return Err(format!("{}'s min is greater than its max", name));
+    }
+    let default = match default {
+      Some(Components::One(value)) => vec![value; kind.components()],
+      Some(Components::Each(values)) if values.len() == kind.components() => values,
+      Some(Components::Each(values)) => return Err(format!("{} has {} components but its default has {}", name, kind.components(), values.len())),
+      None => vec![min; kind.components()],
+    };
+    let default: Vec<f32> = default.iter().map(|value| value.clamp(min, max)).collect();
+    let midi = match midi {
+      None => Vec::new(),
+      Some(Controllers::One(controller)) if kind.components() == 1 => vec![controller],
+      Some(Controllers::Each(controllers)) if controllers.len() == kind.components() => controllers,
+      Some(_) => return Err(format!("{} needs a MIDI controller for each of its {} components", name, kind.components())),
+    };
+    if let Some(controller) = midi.iter().find(|&&controller| controller > 127) {
+      return Err(format!("{} isn't a MIDI controller, they go from 0 to 127", controller));
+    }
+    offset = offset.next_multiple_of(kind.alignment());
+    params.push(Param { name, kind, value: default.clone(), default, min, max, midi, offset });
+    offset += kind.components() * 4;
+  }
+  Ok(params)
+}
+
+// the parameters of the image's shader and the buffer they're uploaded to
+pub struct Params {
+  // where they're declared, which doesn't have to exist
+  pub path: Option<PathBuf>,
+  pub params: Vec<Param>,
+  // why the file last failed to load
+  pub error: Option<String>,
+  buffer: Buffer,
+  layout: BindGroupLayout,
+  bind_group: BindGroup,
+  // whether any values have changed since they were last uploaded
+  changed: bool,
+}
+
+impl Params {
+  // the parameters declared in the toml file at `path`, if there is one
+  pub fn new(device: &Device, path: Option<PathBuf>) -> Self {
+    let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: Some("params"),
+      entries: &[BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+        count: None,
+        ty: BindingType::Buffer {
+          ty: BufferBindingType::Uniform,
+          has_dynamic_offset: false,
+          min_binding_size: None,
+        },
+      }],
+    });
+    let (buffer, bind_group) = create_binding(device, &layout, &[]);
+    let mut params = Params { path, params: Vec::new(), error: None, buffer, layout, bind_group, changed: false };
+    params.reload(device);
+    params
+  }
+
+  // read the file again, keeping the parameters it last declared if it can't be read
+  pub fn reload(&mut self, device: &Device) {
+    let Some(path) = &self.path else {
+      return;
+    };
+    match load(path) {
+      Ok(params) => {
+        if !params.is_empty() {
+          println!("[Horus] Declaring {} uniforms from {}", params.len(), path.display());
+        }
+        self.params = params;
+        self.error = None;
+        (self.buffer, self.bind_group) = create_binding(device, &self.layout, &self.params);
+      }
+      Err(error) => {
+        let error = format!("error: {}", error);
+        println!("[Horus] Unable to load {}\n{}", path.display(), error);
+        self.error = Some(error);
+      }
+    }
+  }
+
+  pub fn bind_group_layout(&self) -> &BindGroupLayout {
+    &self.layout
+  }
+
+  pub fn bind_group(&self) -> &BindGroup {
+    &self.bind_group
+  }
+
+  pub fn index_of(&self, name: &str) -> Option<usize> {
+    self.params.iter().position(|param| param.name == name)
+  }
+
+  // change the value of the parameter at `index`, keeping each component within its range
+  pub fn set(&mut self, index: usize, value: &[f32]) {
+    if let Some(param) = self.params.get_mut(index) {
+      for (component, value) in param.value.iter_mut().zip(value) {
+        *component = value.clamp(param.min, param.max);
+      }
+      self.changed = true;
+    }
+  }
+
+  // whether any values have changed since they were last uploaded, and so need a new frame to show them
+  pub fn has_changed(&self) -> bool {
+    self.changed
+  }
+
+  // whether any parameters follow a MIDI controller
+  pub fn uses_midi(&self) -> bool {
+    self.params.iter().any(|param| !param.midi.is_empty())
+  }
+
+  // move every component that follows `controller` to where `value` is between its min and max
+  #[cfg_attr(not(feature = "midi"), allow(dead_code))]
+  pub fn control(&mut self, controller: u8, value: u8) {
+    for param in &mut self.params {
+      for (component, _) in param.value.iter_mut().zip(&param.midi).filter(|(_, &midi)| midi == controller) {
+        *component = param.min + (param.max - param.min) * value as f32 / 127.;
+        self.changed = true;
+      }
+    }
+  }
+
+  // put every value back to what the file declared
+  pub fn reset(&mut self) {
+    for param in &mut self.params {
+      param.value = param.default.clone();
+    }
+    self.changed = true;
+  }
+
+  // send the values to the buffer if they've changed since the last time
+  pub fn upload(&mut self, queue: &Queue) {
+    if self.changed {
+      queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&pack(&self.params)));
+      self.changed = false;
+    }
+  }
+}
+
+// the uniforms declared at `path`, which there aren't any of if it doesn't exist
+pub fn load(path: &Path) -> Result<Vec<Param>, String> {
+  match read_to_string(path) {
+    Ok(source) => parse(&source),
+    Err(_) if !path.exists() => Ok(Vec::new()),
+    Err(error) => Err(error.to_string()),
+  }
+}
+
+// the declarations that are pasted into WGSL shaders, or nothing if there aren't any
+pub fn wgsl(params: &[Param]) -> Option<String> {
+  let members: Vec<String> = params.iter().map(|param| format!("    {}: {},\n", param.name, param.kind.wgsl())).collect();
+  (!members.is_empty()).then(|| format!("struct Params {{\n{}}};\n\n@group(2) @binding(0)\nvar<uniform> params: Params;\n", members.concat()))
+}
+
+// GLSL reads the members of an unnamed block as globals
+pub fn glsl(params: &[Param]) -> Option<String> {
+  let members: Vec<String> = params.iter().map(|param| format!("    {} {};\n", param.kind.glsl(), param.name)).collect();
+  (!members.is_empty()).then(|| format!("layout(set = 2, binding = 0) uniform Params {{\n{}}};\n", members.concat()))
+}
+
+// HLSL reads the members of a cbuffer as globals too, which DXC lays out the same way as GLSL since it compiles with -fvk-use-gl-layout
+pub fn hlsl(params: &[Param]) -> Option<String> {
+  let members: Vec<String> = params.iter().map(|param| format!("    {} {};\n", param.kind.hlsl(), param.name)).collect();
+  (!members.is_empty()).then(|| format!("cbuffer Params : register(b0, space2) {{\n{}}};\n", members.concat()))
+}
+
+// lay the values out the way shaders expect to find them
+fn pack(params: &[Param]) -> Vec<f32> {
+  let size = params.last().map_or(0, |param| param.offset + param.kind.components() * 4);
+  // a buffer can't be empty, and uniform structs are padded to 16 bytes
+  let mut packed = vec![0.; size.max(1).next_multiple_of(16) / 4];
+  for param in params {
+    packed[param.offset / 4..][..param.value.len()].copy_from_slice(&param.value);
+  }
+  packed
+}
+
+fn create_binding(device: &Device, layout: &BindGroupLayout, params: &[Param]) -> (Buffer, BindGroup) {
+  let buffer = device.create_buffer_init(&BufferInitDescriptor {
+    label: Some("params"),
+    contents: bytemuck::cast_slice(&pack(params)),
+    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+  });
+  let bind_group = device.create_bind_group(&BindGroupDescriptor {
+    label: Some("params"),
+    layout,
+    entries: &[BindGroupEntry {
+      binding: 0,
+      resource: buffer.as_entire_binding(),
+    }],
+  });
+  (buffer, bind_group)
+}
@@ -0,0 +1,700 @@
+// a project is rendered in up to six passes: the offscreen buffers A through D in order, cubemap A, then the image on screen,
+// and can have a sound pass too, which is rendered once into the samples it plays
+
+use std::{fs::{self, read_to_string}, io, path::{Path, PathBuf}};
+use wgpu::{BindGroup, BindGroupLayout, Color, CommandEncoder, Device, LoadOp, Queue, Operations, PipelineLayout, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, Sampler, ShaderModule, TextureFormat, TextureView, TextureViewDimension};
+
+use crate::{
+  channel::{self, Channel, Sampling},
+  params::{self, Params},
+  shader::{self, Define, FragmentShader, ShaderLanguage, VertexShader},
+  shadertoy,
+  timeline::Timeline,
+  Uniforms,
+  UniformsBinding,
+};
+
+// buffers are found next to the main shader by these names, with the same extension
+pub const BUFFER_NAMES: [&str; 4] = ["bufferA", "bufferB", "bufferC", "bufferD"];
+
+// shared code is found next to the main shader by this name, with the same extension
+pub const COMMON_NAME: &str = "common";
+
+// the cubemap pass is found next to the main shader by this name, with the same extension, and read by passing it to a channel
+pub const CUBEMAP_NAME: &str = "cubemapA";
+
+// the sound pass is found next to the main shader by this name, with the same extension
+pub const SOUND_NAME: &str = "sound";
+
+// buffers keep more precision than the surface so they can hold simulation state
+const BUFFER_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+// how many pixels across each face of the cubemap is, same as shadertoy
+const CUBEMAP_SIZE: u32 = 1024;
+
+// where the cubemap and the image come in the order the passes are rendered, which is also where their channels are kept
+const CUBEMAP_PASS: usize = BUFFER_NAMES.len();
+const IMAGE_PASS: usize = CUBEMAP_PASS + 1;
+
+// the sound pass writes the left and right samples of each pixel into red and green
+pub const SOUND_FORMAT: TextureFormat = TextureFormat::Rg32Float;
+
+// how to find and read a project's passes, where any path that's given replaces the file next to the image's shader
+#[derive(Debug, Default)]
+pub struct Sources {
+  // read every pass as shadertoy code
+  pub shadertoy: bool,
+  pub buffers: [Option<PathBuf>; BUFFER_NAMES.len()],
+  pub common: Option<PathBuf>,
+  pub cubemap: Option<PathBuf>,
+  pub sound: Option<PathBuf>,
+  // the toml file custom uniforms are declared in
+  pub uniforms: Option<PathBuf>,
+  // the keyframes the custom uniforms follow
+  pub timeline: Option<PathBuf>,
+  pub defines: Vec<Define>,
+  // where WGSL modules are found besides the image's own directory
+  pub modules: Vec<PathBuf>,
+  // the WGSL vertex shader the image is drawn with, and how many vertices it's drawn with
+  pub vertex: Option<PathBuf>,
+  pub vertices: Option<u32>,
+}
+
+// what a channel reads from
+pub enum ChannelInput {
+  Texture(Channel),
+  // the most recent output of one of the buffers
+  Buffer(usize),
+  // the most recent output of the cubemap pass
+  Cubemap,
+}
+
+// a fragment shader and the pipeline it last compiled into
+pub struct Pass {
+  // where the source is reloaded from, if it came from a file
+  pub path: Option<PathBuf>,
+  pub name: String,
+  source: String,
+  // what the pass is made of instead when it's SPIR-V
+  spirv: Vec<u8>,
+  language: ShaderLanguage,
+  format: TextureFormat,
+  // how many samples each pixel of the target has, which is only ever more than 1 for the image with --msaa
+  pub samples: u32,
+  pub pipeline: Option<RenderPipeline>,
+  // the function the image starts at when --entry picks one
+  pub entry: Option<String>,
+  // why the source last failed to load or compile
+  error: Option<String>,
+}
+
+impl Pass {
+  pub fn new(path: Option<PathBuf>, source: String, shadertoy: bool, format: TextureFormat) -> Self {
+    let name = path.as_ref().map_or("fragment.wgsl".to_string(), |path| path.display().to_string());
+    let mut language = path.as_deref().map_or(ShaderLanguage::Wgsl, ShaderLanguage::from_path);
+    if matches!(language, ShaderLanguage::Wgsl | ShaderLanguage::Glsl) && (shadertoy || language == ShaderLanguage::Glsl) {
+      if shadertoy::is_sound(&source) {
+        language = ShaderLanguage::ShadertoySound;
+      } else if shadertoy::is_cubemap(&source) {
+        language = ShaderLanguage::ShadertoyCubemap;
+      } else if shadertoy || shadertoy::is_shadertoy(&source) {
+        language = ShaderLanguage::Shadertoy;
+      }
+    }
+    Pass { path, name, source, spirv: Vec::new(), language, format, samples: 1, pipeline: None, entry: None, error: None }
+  }
+
+  // read the pass at `path`, which is only text if it isn't SPIR-V
+  pub fn load(path: PathBuf, shadertoy: bool, format: TextureFormat) -> io::Result<Self> {
+    if ShaderLanguage::from_path(&path) == ShaderLanguage::SpirV {
+      let spirv = fs::read(&path)?;
+      return Ok(Pass { spirv, ..Pass::new(Some(path), String::new(), shadertoy, format) });
+    }
+    let source = read_to_string(&path)?;
+    Ok(Pass::new(Some(path), source, shadertoy, format))
+  }
+
+  pub fn is_shadertoy(&self) -> bool {
+    matches!(self.language, ShaderLanguage::Shadertoy | ShaderLanguage::ShadertoyVr)
+  }
+
+  // with --projection, shadertoy images that define `mainVR` are rendered with it instead of `mainImage`, and whether they do
+  pub fn project(&mut self) -> bool {
+    if self.language == ShaderLanguage::Shadertoy && shadertoy::is_vr(&self.source) {
+      self.language = ShaderLanguage::ShadertoyVr;
+    }
+    self.language == ShaderLanguage::ShadertoyVr
+  }
+
+  // replace the source of a pass that didn't come from a file, which takes effect the next time it's compiled
+  pub fn set_source(&mut self, source: String) {
+    self.source = source;
+  }
+
+  // the files the source includes or imports, which it's compiled again along with whenever they change
+  fn includes(&self, modules: &[PathBuf]) -> Vec<PathBuf> {
+    self.path.as_deref().map_or(Vec::new(), |path| shader::includes(path, &self.source, modules))
+  }
+
+  // read the latest version of the source from disk
+  fn reload(&mut self) -> Result<(), String> {
+    match &self.path {
+      Some(path) if self.language == ShaderLanguage::SpirV => self.spirv = fs::read(path).map_err(|error| format!("error: {}", error))?,
+      Some(path) => self.source = read_to_string(path).map_err(|error| format!("error: {}", error))?,
+      None => {}
+    }
+    Ok(())
+  }
+
+  // build a new pipeline from the source, keeping the last one that worked if it doesn't compile
+  #[allow(clippy::too_many_arguments)]
+  fn compile(&mut self, device: &Device, layout: &PipelineLayout, vertex_shader: &ShaderModule, channels: &[TextureViewDimension], common: Option<&Common>, params: &Params, defines: &[Define], modules: &[PathBuf]) -> bool {
+    let mut result = self.create_pipeline(device, layout, vertex_shader, channels, common, params, defines, modules, self.samples > 1);
+    // naga 0.10 can't write gl_SampleID for the GL backend, so there a shadertoy image is run once per pixel like any other shader
+    if result.is_err() && self.samples > 1 && matches!(self.language, ShaderLanguage::Shadertoy | ShaderLanguage::ShadertoyVr) {
+      if let Ok(pipeline) = self.create_pipeline(device, layout, vertex_shader, channels, common, params, defines, modules, false) {
+        println!("[Horus] Unable to run {} once per sample on this backend, so --msaa won't smooth its edges, though --scale 2 will", self.name);
+        result = Ok(pipeline);
+      }
+    }
+    match result {
+      Ok(pipeline) => {
+        self.pipeline = Some(pipeline);
+        self.error = None;
+        true
+      }
+      Err(error) => {
+        println!("[Horus] Unable to compile {}\n{}", self.name, error);
+        self.error = Some(error);
+        false
+      }
+    }
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn create_pipeline(&self, device: &Device, layout: &PipelineLayout, vertex_shader: &ShaderModule, channels: &[TextureViewDimension], common: Option<&Common>, params: &Params, defines: &[Define], modules: &[PathBuf], per_sample: bool) -> Result<RenderPipeline, String> {
+    shader::create_render_pipeline(device, layout, vertex_shader, &FragmentShader {
+      name: &self.name,
+      path: self.path.as_deref(),
+      source: &self.source,
+      spirv: &self.spirv,
+      language: self.language,
+      channels,
+      common: common.map(|common| (common.path.as_path(), common.source.as_str())),
+      params: &params.params,
+      defines,
+      modules,
+      entry: self.entry.as_deref(),
+      per_sample,
+    }, self.format, self.samples)
+  }
+}
+
+// every pass of the project `image` belongs to, checked without a device to compile them on, returning how many didn't pass
+pub fn check(image: Pass, sources: &Sources, channels: &[TextureViewDimension]) -> usize {
+  let buffers = Passes::find_buffers(image.path.as_deref(), sources);
+  let cubemap = Passes::find_cubemap(image.path.as_deref(), sources);
+  let sound = Passes::find_sound(image.path.as_deref(), sources);
+  let common = Common::find(image.path.as_deref(), sources.common.as_deref());
+  let path = sources.uniforms.clone().or_else(|| image.path.as_ref().map(|path| path.with_extension("toml")));
+  let params = match path.as_deref().map(params::load) {
+    Some(Err(error)) => {
+      println!("[Horus] Unable to load {}\nerror: {}", path.unwrap_or_default().display(), error);
+      return 1;
+    }
+    Some(Ok(params)) => params,
+    None => Vec::new(),
+  };
+  let mut failed = 0;
+  for pass in buffers.iter().flatten().chain(cubemap.iter()).chain(sound.iter()).chain(std::iter::once(&image)) {
+    let result = shader::check(&FragmentShader {
+      name: &pass.name,
+      path: pass.path.as_deref(),
+      source: &pass.source,
+      spirv: &pass.spirv,
+      language: pass.language,
+      channels,
+      common: common.as_ref().map(|common| (common.path.as_path(), common.source.as_str())),
+      params: &params,
+      defines: &sources.defines,
+      modules: &sources.modules,
+      entry: pass.entry.as_deref(),
+      per_sample: false,
+    });
+    if let Err(error) = result {
+      println!("[Horus] Unable to compile {}\n{}", pass.name, error);
+      failed += 1;
+    }
+  }
+  if let Some(path) = &sources.vertex {
+    let name = path.display().to_string();
+    let result = read_to_string(path).map_err(|error| format!("error: {}", error)).and_then(|source| shader::check_vertex(&VertexShader {
+      name: &name,
+      path: Some(path),
+      source: &source,
+      params: &params,
+      defines: &sources.defines,
+      modules: &sources.modules,
+    }));
+    if let Err(error) = result {
+      println!("[Horus] Unable to compile {}\n{}", name, error);
+      failed += 1;
+    }
+  }
+  failed
+}
+
+// the shader the image is drawn with in place of the triangle that covers the whole target
+struct Vertex {
+  path: PathBuf,
+  name: String,
+  source: String,
+  // how many vertices the image is drawn with
+  count: u32,
+  // the last version that compiled
+  module: Option<ShaderModule>,
+  error: Option<String>,
+}
+
+impl Vertex {
+  // read the latest version of the vertex shader and compile it, keeping the last one that worked if it doesn't
+  fn compile(&mut self, device: &Device, params: &Params, defines: &[Define], modules: &[PathBuf]) -> bool {
+    let result = read_to_string(&self.path).map_err(|error| format!("error: {}", error)).and_then(|source| {
+      self.source = source;
+      shader::create_vertex_shader(device, &VertexShader {
+        name: &self.name,
+        path: Some(&self.path),
+        source: &self.source,
+        params: &params.params,
+        defines,
+        modules,
+      })
+    });
+    match result {
+      Ok(module) => {
+        self.module = Some(module);
+        self.error = None;
+        true
+      }
+      Err(error) => {
+        println!("[Horus] Unable to compile {}\n{}", self.name, error);
+        self.error = Some(error);
+        false
+      }
+    }
+  }
+}
+
+// code that is pasted in front of every pass, like shadertoy's common tab
+struct Common {
+  path: PathBuf,
+  name: String,
+  source: String,
+}
+
+impl Common {
+  // the common file that sits next to the image's shader, if there is one
+  fn find(image: Option<&Path>, path: Option<&Path>) -> Option<Self> {
+    let path = match (path, image) {
+      (Some(path), _) => path.to_path_buf(),
+      (None, Some(image)) => image.with_file_name(COMMON_NAME).with_extension(image.extension().unwrap_or_default()),
+      (None, None) => return None,
+    };
+    let source = read_to_string(&path).ok()?;
+    println!("[Horus] Sharing {} with every pass", path.display());
+    Some(Common { name: path.display().to_string(), path, source })
+  }
+}
+
+// an offscreen pass that alternates between two textures so it can read what it rendered last frame
+pub struct Buffer {
+  pub pass: Pass,
+  // rendered into on even and odd frames respectively
+  targets: [Channel; 2],
+}
+
+// a pass that renders each face of a cubemap, alternating between two of them like a buffer
+pub struct Cubemap {
+  pub pass: Pass,
+  targets: [(Channel, Vec<TextureView>); 2],
+  // each face renders at the cubemap's resolution with its own offset, so it has its own copy of the uniforms
+  uniforms: Vec<UniformsBinding>,
+}
+
+// every pass of a project along with the channels they read from
+pub struct Passes {
+  buffers: Vec<Option<Buffer>>,
+  cubemap: Option<Cubemap>,
+  pub image: Pass,
+  pub sound: Option<Pass>,
+  // whether the sound pass has been rebuilt since its samples were last rendered
+  sound_changed: bool,
+  common: Option<Common>,
+  pub params: Params,
+  timeline: Option<Timeline>,
+  defines: Vec<Define>,
+  modules: Vec<PathBuf>,
+  vertex: Option<Vertex>,
+  inputs: Vec<ChannelInput>,
+  // each channel is read the same way whatever it's reading
+  samplers: Vec<Sampler>,
+  // stands in for buffers and the cubemap when the project doesn't have them
+  empty: Channel,
+  empty_cube: Channel,
+  layout: BindGroupLayout,
+  // each pass' channels on even and odd frames
+  bind_groups: Vec<[BindGroup; 2]>,
+}
+
+impl Passes {
+  // load the buffers, common code and custom uniforms that sit next to the image's shader, unless `sources` says they're somewhere else
+  pub fn new(device: &Device, queue: &Queue, image: Pass, sources: &Sources, inputs: Vec<ChannelInput>, width: u32, height: u32) -> Self {
+    let buffers = Self::find_buffers(image.path.as_deref(), sources);
+    let cubemap = Self::find_cubemap(image.path.as_deref(), sources).map(|pass| Cubemap {
+      pass,
+      targets: [Channel::cube_target(device, CUBEMAP_SIZE, BUFFER_FORMAT), Channel::cube_target(device, CUBEMAP_SIZE, BUFFER_FORMAT)],
+      uniforms: (0..6).map(|_| UniformsBinding::new(device, &Uniforms::new(CUBEMAP_SIZE, CUBEMAP_SIZE))).collect(),
+    });
+    let sound = Self::find_sound(image.path.as_deref(), sources);
+    let common = Common::find(image.path.as_deref(), sources.common.as_deref());
+    let params = Params::new(device, sources.uniforms.clone().or_else(|| image.path.as_ref().map(|path| path.with_extension("toml"))));
+    // a timeline is only looked for next to the shader when it isn't asked for, so one that's asked for and missing is an error
+    let timeline = sources.timeline.clone().or_else(|| image.path.as_ref().map(|path| path.with_extension("timeline.toml")).filter(|path| path.is_file())).map(Timeline::new);
+    let empty = Channel::empty(device, queue);
+    let empty_cube = Channel::empty_cube(device, queue);
+    let buffers = buffers.into_iter().map(|pass| pass.map(|pass| Buffer {
+      pass,
+      targets: [Channel::target(device, width, height, BUFFER_FORMAT), Channel::target(device, width, height, BUFFER_FORMAT)],
+    })).collect();
+    let vertex = sources.vertex.clone().map(|path| {
+      println!("[Horus] Drawing the image with {}", path.display());
+      Vertex { name: path.display().to_string(), path, source: String::new(), count: sources.vertices.unwrap_or(3), module: None, error: None }
+    });
+    let layout = channel::create_bind_group_layout(device, &Self::dimensions_of(&inputs));
+    let samplers = inputs.iter().map(|_| channel::create_sampler(device, Sampling::default())).collect();
+    let mut passes = Passes { buffers, cubemap, image, sound, sound_changed: false, common, params, timeline, defines: sources.defines.clone(), modules: sources.modules.clone(), vertex, inputs, samplers, empty, empty_cube, layout, bind_groups: Vec::new() };
+    passes.bind_groups = passes.create_bind_groups(device);
+    passes
+  }
+
+  // the buffers that sit next to the image's shader, or wherever `sources` says they are
+  fn find_buffers(image: Option<&Path>, sources: &Sources) -> Vec<Option<Pass>> {
+    BUFFER_NAMES.iter().zip(&sources.buffers).map(|(name, configured)| {
+      let path = match (configured, image) {
+        (Some(path), _) => path.clone(),
+        (None, Some(image)) => image.with_file_name(name).with_extension(image.extension().unwrap_or_default()),
+        (None, None) => return None,
+      };
+      if Some(path.as_path()) == image {
+        return None;
+      }
+      // buffers that were asked for by name complain if they're missing, instead of just being left out
+      let pass = match Pass::load(path.clone(), sources.shadertoy, BUFFER_FORMAT) {
+        Ok(pass) => pass,
+        Err(error) if configured.is_some() => {
+          println!("[Horus] Unable to read {}: {}", path.display(), error);
+          return None;
+        }
+        Err(_) => return None,
+      };
+      println!("[Horus] Rendering {} into {}", path.display(), name);
+      Some(pass)
+    }).collect()
+  }
+
+  // the cubemap pass that sits next to the image's shader, or wherever `sources` says it is
+  fn find_cubemap(image: Option<&Path>, sources: &Sources) -> Option<Pass> {
+    let path = match (&sources.cubemap, image) {
+      (Some(path), _) => path.clone(),
+      (None, Some(image)) => image.with_file_name(CUBEMAP_NAME).with_extension(image.extension().unwrap_or_default()),
+      (None, None) => return None,
+    };
+    if Some(path.as_path()) == image {
+      return None;
+    }
+    // a cubemap that was asked for by name complains if it's missing, instead of just being left out
+    match Pass::load(path.clone(), sources.shadertoy, BUFFER_FORMAT) {
+      Ok(pass) => {
+        println!("[Horus] Rendering {} into {}", path.display(), CUBEMAP_NAME);
+        Some(pass)
+      }
+      Err(error) if sources.cubemap.is_some() => {
+        println!("[Horus] Unable to read {}: {}", path.display(), error);
+        None
+      }
+      Err(_) => None,
+    }
+  }
+
+  // the sound pass that sits next to the image's shader, or wherever `sources` says it is
+  fn find_sound(image: Option<&Path>, sources: &Sources) -> Option<Pass> {
+    let path = Self::sound_path(image, sources)?;
+    match Pass::load(path.clone(), sources.shadertoy, SOUND_FORMAT) {
+      Ok(pass) => {
+        println!("[Horus] Rendering {} into the audio", path.display());
+        Some(pass)
+      }
+      Err(error) => {
+        println!("[Horus] Unable to read {}: {}", path.display(), error);
+        None
+      }
+    }
+  }
+
+  // where the sound pass is read from, if there's one to read, without reading it
+  pub fn sound_path(image: Option<&Path>, sources: &Sources) -> Option<PathBuf> {
+    match (&sources.sound, image) {
+      (Some(path), _) => Some(path.clone()),
+      (None, Some(image)) => Some(image.with_file_name(SOUND_NAME).with_extension(image.extension().unwrap_or_default())).filter(|path| path.is_file() && path != image),
+      (None, None) => None,
+    }
+  }
+
+  // whether each channel is bound as a 2D texture or a cubemap
+  fn dimensions_of(inputs: &[ChannelInput]) -> Vec<TextureViewDimension> {
+    inputs.iter().map(|input| match input {
+      ChannelInput::Texture(channel) => channel.dimension,
+      ChannelInput::Buffer(_) => TextureViewDimension::D2,
+      ChannelInput::Cubemap => TextureViewDimension::Cube,
+    }).collect()
+  }
+
+  pub fn bind_group_layout(&self) -> &BindGroupLayout {
+    &self.layout
+  }
+
+  // every file the passes are read from, including the ones they include or import
+  pub fn paths(&self) -> Vec<PathBuf> {
+    let passes = self.buffers.iter().flatten().map(|buffer| &buffer.pass).chain(self.cubemap.iter().map(|cubemap| &cubemap.pass)).chain(self.sound.iter()).chain(std::iter::once(&self.image));
+    let mut paths: Vec<PathBuf> = passes.flat_map(|pass| pass.path.clone().into_iter().chain(pass.includes(&self.modules))).collect();
+    paths.extend(self.common.iter().flat_map(|common| std::iter::once(common.path.clone()).chain(shader::includes(&common.path, &common.source, &self.modules))));
+    paths.extend(self.vertex.iter().flat_map(|vertex| std::iter::once(vertex.path.clone()).chain(shader::includes(&vertex.path, &vertex.source, &self.modules))));
+    paths.extend(self.params.path.clone());
+    paths.extend(self.timeline.as_ref().map(|timeline| timeline.path.clone()));
+    let mut unique = Vec::new();
+    for path in paths {
+      if !unique.contains(&path) {
+        unique.push(path);
+      }
+    }
+    unique
+  }
+
+  // compile every pass whose source is at one of `paths`, or every pass if `paths` is `None` or includes the common file
+  // or the extra uniforms, returning whether any of their pipelines were rebuilt
+  pub fn compile(&mut self, device: &Device, layout: &PipelineLayout, vertex_shader: &ShaderModule, mut paths: Option<&[PathBuf]>) -> bool {
+    let reloading = paths.is_some();
+    if let (Some(common), Some(changed)) = (&mut self.common, paths) {
+      if changed.contains(&common.path) || shader::includes(&common.path, &common.source, &self.modules).iter().any(|path| changed.contains(path)) {
+        match read_to_string(&common.path) {
+          Ok(source) => common.source = source,
+          Err(error) => println!("[Horus] Unable to read {}\nerror: {}", common.name, error),
+        }
+        paths = None;
+      }
+    }
+    // the extra uniforms' buffer is rebuilt to fit, and the bind group layout stays the same no matter what they are
+    if paths.is_some_and(|changed| self.params.path.as_ref().is_some_and(|path| changed.contains(path))) {
+      self.params.reload(device);
+      paths = None;
+    }
+    // the keyframes don't change the shaders, so nothing is rebuilt for them
+    if let (Some(timeline), Some(changed)) = (&mut self.timeline, paths) {
+      if changed.contains(&timeline.path) {
+        timeline.reload();
+        println!("[Horus] Reloaded {}", timeline.path.display());
+      }
+    }
+    // the image is drawn with the vertex shader, so it's rebuilt whenever the vertex shader is
+    let mut vertex_changed = false;
+    if let Some(vertex) = &mut self.vertex {
+      if paths.is_none_or(|changed| changed.contains(&vertex.path) || shader::includes(&vertex.path, &vertex.source, &self.modules).iter().any(|path| changed.contains(path))) {
+        vertex_changed = vertex.compile(device, &self.params, &self.defines, &self.modules);
+        if vertex_changed && reloading {
+          println!("[Horus] Reloaded {}", vertex.name);
+        }
+      }
+    }
+
+    let dimensions = Self::dimensions_of(&self.inputs);
+    let Passes { buffers, cubemap, image, sound, sound_changed, common, params, defines, modules, vertex, .. } = self;
+    let mut rebuilt = false;
+    let passes = buffers.iter_mut().flatten().map(|buffer| (&mut buffer.pass, false, false))
+      .chain(cubemap.iter_mut().map(|cubemap| (&mut cubemap.pass, false, false)))
+      .chain(sound.iter_mut().map(|pass| (pass, false, true)))
+      .chain(std::iter::once((image, true, false)));
+    for (pass, is_image, is_sound) in passes {
+      let changed = (is_image && vertex_changed) || match (paths, &pass.path) {
+        (None, _) => true,
+        (Some(paths), Some(path)) => paths.contains(path) || pass.includes(modules).iter().any(|include| paths.contains(include)),
+        (Some(_), None) => false,
+      };
+      if !changed {
+        continue;
+      }
+      // the image waits for its vertex shader to compile, and the other passes always cover their whole target
+      let vertex_shader = match (is_image, vertex.as_ref()) {
+        (true, Some(Vertex { module: Some(module), .. })) => module,
+        (true, Some(_)) => continue,
+        (false, _) | (true, None) => vertex_shader,
+      };
+      match pass.reload() {
+        Ok(()) => if pass.compile(device, layout, vertex_shader, &dimensions, common.as_ref(), params, defines, modules) {
+          // the sound is rendered again on its own, so the buffers and image don't start over for it
+          if is_sound {
+            *sound_changed = true;
+            if reloading {
+              println!("[Horus] Reloaded {}", pass.name);
+            }
+            continue;
+          }
+          rebuilt = true;
+          if reloading {
+            println!("[Horus] Reloaded {}", pass.name);
+          }
+        },
+        Err(error) => {
+          println!("[Horus] Unable to read {}\n{}", pass.name, error);
+          pass.error = Some(error);
+        }
+      }
+    }
+    rebuilt
+  }
+
+  // every error that is stopping a pass from being up to date
+  pub fn errors(&self) -> String {
+    self.buffers.iter().flatten().map(|buffer| &buffer.pass).chain(self.cubemap.iter().map(|cubemap| &cubemap.pass)).chain(self.sound.iter()).chain(std::iter::once(&self.image))
+      .filter_map(|pass| pass.error.as_deref())
+      .chain(self.vertex.as_ref().and_then(|vertex| vertex.error.as_deref()))
+      .chain(self.params.error.as_deref())
+      .chain(self.timeline.as_ref().and_then(|timeline| timeline.error.as_deref()))
+      .collect::<Vec<_>>()
+      .join("\n\n")
+  }
+
+  // move the custom uniforms along the timeline to `time`, which they have to be uploaded after
+  pub fn animate(&mut self, time: f32) {
+    if let Some(timeline) = &mut self.timeline {
+      timeline.apply(&mut self.params, time);
+    }
+  }
+
+  // whether the sound pass has been rebuilt since this was last asked, so its samples have to be rendered again
+  pub fn take_sound_changed(&mut self) -> bool {
+    std::mem::take(&mut self.sound_changed)
+  }
+
+  // read from `channel` at `index` instead of whatever was there, which has to be a 2D texture like the one it replaces
+  pub fn set_channel(&mut self, device: &Device, index: usize, channel: Channel) {
+    self.inputs[index] = ChannelInput::Texture(channel);
+    self.bind_groups = self.create_bind_groups(device);
+  }
+
+  // read each channel the way `sampling` says to, in order
+  pub fn set_sampling(&mut self, device: &Device, sampling: &[Sampling]) {
+    self.samplers = sampling.iter().map(|sampling| channel::create_sampler(device, *sampling)).collect();
+    self.bind_groups = self.create_bind_groups(device);
+  }
+
+  // buffers render at the same resolution as the window, which clears them, and the cubemap is cleared along with them
+  pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+    for buffer in self.buffers.iter_mut().flatten() {
+      buffer.targets = [Channel::target(device, width, height, BUFFER_FORMAT), Channel::target(device, width, height, BUFFER_FORMAT)];
+    }
+    if let Some(cubemap) = &mut self.cubemap {
+      cubemap.targets = [Channel::cube_target(device, CUBEMAP_SIZE, BUFFER_FORMAT), Channel::cube_target(device, CUBEMAP_SIZE, BUFFER_FORMAT)];
+    }
+    self.bind_groups = self.create_bind_groups(device);
+  }
+
+  // the cubemap's faces see the same uniforms as the other passes, apart from their resolution and where they are, which is
+  // a face further across for each one as if they were laid side by side
+  pub fn upload_uniforms(&self, queue: &Queue, uniforms: &Uniforms) {
+    let Some(cubemap) = &self.cubemap else {
+      return;
+    };
+    for (face, binding) in cubemap.uniforms.iter().enumerate() {
+      let uniforms = Uniforms { resolution: [CUBEMAP_SIZE as f32; 2], offset: [(face as u32 * CUBEMAP_SIZE) as f32, 0.], ..*uniforms };
+      queue.write_buffer(&binding.buffer, 0, bytemuck::bytes_of(&uniforms));
+    }
+  }
+
+  // buffer N and the cubemap are written to target `frame % 2`, so passes that come after them this frame read that target
+  // while they and the passes before them read the other one, which holds what they rendered last frame
+  fn create_bind_groups(&self, device: &Device) -> Vec<[BindGroup; 2]> {
+    (0..=IMAGE_PASS).map(|pass| [0, 1].map(|parity| {
+      let channels: Vec<&Channel> = self.inputs.iter().map(|input| match input {
+        ChannelInput::Texture(channel) => channel,
+        ChannelInput::Buffer(buffer) => match &self.buffers[*buffer] {
+          Some(source) => &source.targets[if *buffer < pass { parity } else { 1 - parity }],
+          None => &self.empty,
+        },
+        ChannelInput::Cubemap => match &self.cubemap {
+          Some(cubemap) => &cubemap.targets[if CUBEMAP_PASS < pass { parity } else { 1 - parity }].0,
+          None => &self.empty_cube,
+        },
+      }).collect();
+      channel::create_bind_group(device, &self.layout, &channels, Some(&self.samplers))
+    })).collect()
+  }
+
+  // how many vertices the image is drawn with
+  pub fn vertices(&self) -> u32 {
+    self.vertex.as_ref().map_or(3, |vertex| vertex.count)
+  }
+
+  // the image's channels for this frame
+  pub fn image_bind_group(&self, frame: u64) -> &BindGroup {
+    &self.bind_groups[IMAGE_PASS][(frame % 2) as usize]
+  }
+
+  // render every buffer that has compiled into this frame's target, and then each face of the cubemap if it has
+  pub fn render_buffers(&self, encoder: &mut CommandEncoder, uniforms: &BindGroup, frame: u64) {
+    let parity = (frame % 2) as usize;
+    for (index, buffer) in self.buffers.iter().enumerate() {
+      let Some(Buffer { pass: Pass { pipeline: Some(pipeline), .. }, targets }) = buffer else {
+        continue;
+      };
+      let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some(BUFFER_NAMES[index]),
+        color_attachments: &[Some(RenderPassColorAttachment {
+          view: targets[parity].view(),
+          resolve_target: None,
+          ops: Operations {
+            load: LoadOp::Clear(Color::TRANSPARENT),
+            store: true,
+          },
+        })],
+        depth_stencil_attachment: None,
+      });
+      render_pass.set_pipeline(pipeline);
+      render_pass.set_bind_group(0, uniforms, &[]);
+      render_pass.set_bind_group(1, &self.bind_groups[index][parity], &[]);
+      render_pass.set_bind_group(2, self.params.bind_group(), &[]);
+      render_pass.draw(0..3, 0..1);
+    }
+    let Some(Cubemap { pass: Pass { pipeline: Some(pipeline), .. }, targets, uniforms }) = &self.cubemap else {
+      return;
+    };
+    for (face, uniforms) in targets[parity].1.iter().zip(uniforms) {
+      let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some(CUBEMAP_NAME),
+        color_attachments: &[Some(RenderPassColorAttachment {
+          view: face,
+          resolve_target: None,
+          ops: Operations {
+            load: LoadOp::Clear(Color::TRANSPARENT),
+            store: true,
+          },
+        })],
+        depth_stencil_attachment: None,
+      });
+      render_pass.set_pipeline(pipeline);
+      render_pass.set_bind_group(0, &uniforms.bind_group, &[]);
+      render_pass.set_bind_group(1, &self.bind_groups[CUBEMAP_PASS][parity], &[]);
+      render_pass.set_bind_group(2, self.params.bind_group(), &[]);
+      render_pass.draw(0..3, 0..1);
+    }
+  }
+}
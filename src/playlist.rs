@@ -0,0 +1,71 @@
+// a directory without a horus.toml is a playlist, which runs each of the shaders in it for --cycle seconds before moving on to the
+// next, or whenever Page Up and Page Down skip back and forth
+
+use std::{collections::hash_map::RandomState, fs::read_dir, hash::BuildHasher, path::{Path, PathBuf}, time::{Duration, Instant}};
+
+use crate::pass::{BUFFER_NAMES, COMMON_NAME, CUBEMAP_NAME, SOUND_NAME};
+
+pub const EXTENSIONS: [&str; 6] = ["wgsl", "frag", "glsl", "fs", "hlsl", "spv"];
+
+pub struct Playlist {
+  shaders: Vec<PathBuf>,
+  index: usize,
+  // how long each shader runs for
+  interval: Duration,
+  // when the current shader started running
+  started: Instant,
+}
+
+impl Playlist {
+  // every shader in `directory` in alphabetical order, or in a different random order every time if `shuffle` says so
+  pub fn find(directory: &Path, seconds: f32, shuffle: bool) -> Result<Self, String> {
+    let mut shaders = shaders_in(directory)?;
+    if shaders.is_empty() {
+      return Err(format!("There aren't any shaders or a horus.toml in {}", directory.display()));
+    }
+    if shuffle {
+      // every RandomState is seeded differently, which is all the randomness a playlist needs
+      let mut state = RandomState::new().hash_one(directory) | 1;
+      for index in (1..shaders.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        shaders.swap(index, (state % (index as u64 + 1)) as usize);
+      }
+    }
+    println!("[Horus] Cycling through {} shaders in {} every {}s{}", shaders.len(), directory.display(), seconds, if shuffle { " in a random order" } else { "" });
+    Ok(Playlist { shaders, index: 0, interval: Duration::from_secs_f32(seconds), started: Instant::now() })
+  }
+
+  pub fn current(&self) -> &Path {
+    &self.shaders[self.index]
+  }
+
+  // move `by` shaders forwards or backwards, going around to the other end of the playlist, and start its countdown over
+  pub fn skip(&mut self, by: isize) -> &Path {
+    self.index = (self.index as isize + by).rem_euclid(self.shaders.len() as isize) as usize;
+    self.started = Instant::now();
+    self.current()
+  }
+
+  // whether the current shader has run for long enough
+  pub fn is_due(&self) -> bool {
+    self.shaders.len() > 1 && self.started.elapsed() >= self.interval
+  }
+}
+
+// the shaders in `directory` in alphabetical order, leaving out the buffers, cubemap, common code and sound that are loaded along with them
+pub fn shaders_in(directory: &Path) -> Result<Vec<PathBuf>, String> {
+  let entries = read_dir(directory).map_err(|error| format!("Unable to read {}: {}", directory.display(), error))?;
+  let mut shaders: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).filter(|path| {
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    path.is_file() && is_shader(path) && stem != COMMON_NAME && stem != CUBEMAP_NAME && stem != SOUND_NAME && !BUFFER_NAMES.contains(&stem)
+  }).collect();
+  shaders.sort();
+  Ok(shaders)
+}
+
+// whether `path` has the extension of a language Horus runs
+pub fn is_shader(path: &Path) -> bool {
+  path.extension().and_then(|extension| extension.to_str()).is_some_and(|extension| EXTENSIONS.contains(&extension))
+}
@@ -0,0 +1,65 @@
+// `horus new` sets up a directory with everything a project needs, which `horus` then runs from inside it
+
+use std::{fs::{create_dir, File}, io::Write, path::Path};
+
+use crate::{config, pass::BUFFER_NAMES, template::Template};
+
+// the starter shader's name inside a new project
+const SHADER_NAME: &str = "main.wgsl";
+
+// the channels come first, followed by the rest of the file
+const CONFIG_CHANNELS: &str = "\
+shader = \"main.wgsl\"
+
+# channels read images from the assets folder, or anything built in like \"bufferA\" or \"noise-rgba-256\"
+";
+const CONFIG_SOURCE: &str = "
+[window]
+width = 700
+height = 700
+
+[render]
+output = \"frames\"
+
+# custom uniforms can be changed from the F2 panel while the shader runs
+# [[uniform]]
+# name = \"speed\"
+# type = \"float\"
+# default = 1.0
+# max = 4.0
+";
+
+// screenshots are saved next to wherever Horus was run from, with the shader's name and the time they were taken
+pub const GITIGNORE_SOURCE: &str = "\
+/main-*.png
+/*.mp4
+/*.gif
+/frames/
+";
+
+// create a project directory called `name` from `template`, without touching one that's already there
+pub fn create(name: &Path, template: Template) -> Result<(), String> {
+  create_dir(name).map_err(|error| format!("Unable to create {}: {}", name.display(), error))?;
+  create_file(&name.join(SHADER_NAME), template.shader())?;
+  if let Some(buffer) = template.buffer() {
+    create_file(&name.join(BUFFER_NAMES[0]).with_extension("wgsl"), buffer)?;
+  }
+  let channel0 = match template.channel0() {
+    Some(channel) => format!("channel0 = {:?}\n", channel),
+    None => "# channel0 = \"assets/image.png\"\n".to_string(),
+  };
+  create_file(&name.join(config::NAME), &format!("{}{}{}", CONFIG_CHANNELS, channel0, CONFIG_SOURCE))?;
+  create_file(&name.join(".gitignore"), GITIGNORE_SOURCE)?;
+  // git doesn't keep empty directories
+  let assets = name.join("assets");
+  create_dir(&assets).map_err(|error| format!("Unable to create {}: {}", assets.display(), error))?;
+  create_file(&assets.join(".gitkeep"), "")?;
+  println!("[Horus] Created {}, which \"horus {}\" runs", name.display(), name.display());
+  Ok(())
+}
+
+// write `source` to a new file at `path`, refusing to replace one that's already there
+pub fn create_file(path: &Path, source: &str) -> Result<(), String> {
+  let mut file = File::options().write(true).create_new(true).open(path).map_err(|error| format!("Unable to create {}: {}", path.display(), error))?;
+  file.write_all(source.as_bytes()).map_err(|error| format!("Unable to write to {}: {}", path.display(), error))
+}
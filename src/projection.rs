@@ -0,0 +1,204 @@
+// with --projection, the image renders each face of a cubemap around the viewer, which is then warped into an equirectangular
+// panorama or a domemaster, the circular fisheye that planetarium projectors and dome players take
+//
+// the faces are rendered like a cubemap pass': each is a square `resolution` wide, as if they were laid side by side with
+// `offset.x / resolution.x` saying which one it is in the order +X, -X, +Y, -Y, +Z, -Z, and the viewer faces -Z with +Y up
+
+use wgpu::{BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferUsages, Color, ColorTargetState, ColorWrites, CommandEncoder, Device, FragmentState, LoadOp, MultisampleState, Operations, PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages, TextureFormat, TextureView, TextureViewDimension, VertexState, util::{BufferInitDescriptor, DeviceExt}};
+
+use crate::{channel::{self, Channel}, cli, msaa::Multisample, overlay::TextOverlay, pass::Passes, render_frame, Uniforms, UniformsBinding};
+
+const SOURCE: &str = "\
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var cube: texture_cube<f32>;
+@group(0) @binding(1)
+var cube_sampler: sampler;
+// the size of what the cubemap is warped into
+@group(1) @binding(0)
+var<uniform> size: vec4<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var vertices = array<vec2<f32>, 3>(
+        vec2<f32>(-1., 1.),
+        vec2<f32>(3., 1.),
+        vec2<f32>(-1., -3.),
+    );
+    var out: VertexOutput;
+    out.position = vec4<f32>(vertices[vertex_index], 0., 1.);
+    out.uv = vertices[vertex_index] * vec2<f32>(0.5, -0.5) + 0.5;
+    return out;
+}
+
+// longitude goes all the way around from left to right with the front in the middle, and latitude from straight up to straight down
+@fragment
+fn fs_equirect(in: VertexOutput) -> @location(0) vec4<f32> {
+    let longitude = (in.uv.x - 0.5) * 6.2831853;
+    let latitude = (0.5 - in.uv.y) * 3.1415927;
+    let direction = vec3<f32>(cos(latitude) * sin(longitude), sin(latitude), -cos(latitude) * cos(longitude));
+    return textureSample(cube, cube_sampler, direction);
+}
+
+// the largest circle that fits is the top half, with straight up in the middle, the horizon around the edge and the front at the bottom
+@fragment
+fn fs_dome(in: VertexOutput) -> @location(0) vec4<f32> {
+    let spot = (in.position.xy - size.xy * 0.5) / (min(size.x, size.y) * 0.5) * vec2<f32>(1., -1.);
+    let radius = length(spot);
+    let around = select(spot / radius, vec2<f32>(0.), radius == 0.);
+    let angle = radius * 1.5707963;
+    let color = textureSample(cube, cube_sampler, vec3<f32>(sin(angle) * around.x, cos(angle), sin(angle) * around.y));
+    return select(color, vec4<f32>(0., 0., 0., 1.), radius > 1.);
+}\
+";
+
+pub struct Projection {
+  format: TextureFormat,
+  // how many pixels are along each side of every face
+  size: u32,
+  cube: Channel,
+  faces: Vec<TextureView>,
+  // the uniforms each face is rendered with, which only differ in their offset
+  uniforms: Vec<UniformsBinding>,
+  layout: BindGroupLayout,
+  bind_group: BindGroup,
+  size_layout: BindGroupLayout,
+  size_bind_group: BindGroup,
+  pipeline: RenderPipeline,
+}
+
+impl Projection {
+  // a cubemap big enough to be warped into a `width` by `height` frame of `format` by `projection`
+  pub fn new(device: &Device, format: TextureFormat, projection: cli::Projection, width: u32, height: u32) -> Self {
+    let shader = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("projection"),
+      source: ShaderSource::Wgsl(SOURCE.into()),
+    });
+    let layout = channel::create_bind_group_layout(device, &[TextureViewDimension::Cube]);
+    let size_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: Some("projection size"),
+      entries: &[BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::FRAGMENT,
+        count: None,
+        ty: BindingType::Buffer {
+          ty: BufferBindingType::Uniform,
+          has_dynamic_offset: false,
+          min_binding_size: None,
+        },
+      }],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+      label: Some("projection"),
+      bind_group_layouts: &[&layout, &size_layout],
+      push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("projection"),
+      layout: Some(&pipeline_layout),
+      vertex: VertexState {
+        module: &shader,
+        entry_point: "vs_main",
+        buffers: &[],
+      },
+      fragment: Some(FragmentState {
+        module: &shader,
+        entry_point: match projection {
+          cli::Projection::Equirect => "fs_equirect",
+          cli::Projection::Dome => "fs_dome",
+        },
+        targets: &[Some(ColorTargetState {
+          format,
+          blend: None,
+          write_mask: ColorWrites::ALL,
+        })],
+      }),
+      primitive: PrimitiveState::default(),
+      depth_stencil: None,
+      multisample: MultisampleState::default(),
+      multiview: None,
+    });
+    let size = face_size(device, width, height);
+    let (cube, faces) = Channel::cube_target(device, size, format);
+    let bind_group = channel::create_bind_group(device, &layout, &[&cube], None);
+    let uniforms = (0..6).map(|_| UniformsBinding::new(device, &Uniforms::new(size, size))).collect();
+    let size_bind_group = create_size_bind_group(device, &size_layout, width, height);
+    Projection { format, size, cube, faces, uniforms, layout, bind_group, size_layout, size_bind_group, pipeline }
+  }
+
+  // match the cubemap to a frame that's now `width` by `height`
+  pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+    self.size = face_size(device, width, height);
+    (self.cube, self.faces) = Channel::cube_target(device, self.size, self.format);
+    self.bind_group = channel::create_bind_group(device, &self.layout, &[&self.cube], None);
+    self.size_bind_group = create_size_bind_group(device, &self.size_layout, width, height);
+  }
+
+  // the resolution the shader renders each face at
+  pub fn size(&self) -> (u32, u32) {
+    (self.size, self.size)
+  }
+
+  // give each face the same uniforms as the main ones, offset to where it is along the row of faces
+  pub fn upload_uniforms(&self, queue: &Queue, uniforms: &Uniforms) {
+    for (face, binding) in self.uniforms.iter().enumerate() {
+      let face = Uniforms { resolution: [self.size as f32; 2], offset: [(face as u32 * self.size) as f32, 0.], ..*uniforms };
+      queue.write_buffer(&binding.buffer, 0, bytemuck::bytes_of(&face));
+    }
+  }
+
+  // render the buffers, unless they're holding still, and then the image into every face
+  pub fn render(&self, encoder: &mut CommandEncoder, multisample: Option<&Multisample>, passes: &Passes, frame: u64, buffers: bool, clear: Color) {
+    for (face, (view, uniforms)) in self.faces.iter().zip(&self.uniforms).enumerate() {
+      render_frame(encoder, view, multisample, passes, &uniforms.bind_group, frame, buffers && face == 0, clear, None, None);
+    }
+  }
+
+  // warp the cubemap into `view`, with the text overlay drawn on top at its own resolution
+  pub fn draw(&self, encoder: &mut CommandEncoder, view: &TextureView, text_overlay: Option<&TextOverlay>) {
+    let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+      label: Some("projection"),
+      color_attachments: &[Some(RenderPassColorAttachment {
+        view,
+        resolve_target: None,
+        ops: Operations {
+          load: LoadOp::Clear(Color::TRANSPARENT),
+          store: true,
+        },
+      })],
+      depth_stencil_attachment: None,
+    });
+    render_pass.set_pipeline(&self.pipeline);
+    render_pass.set_bind_group(0, &self.bind_group, &[]);
+    render_pass.set_bind_group(1, &self.size_bind_group, &[]);
+    render_pass.draw(0..3, 0..1);
+    if let Some(text_overlay) = text_overlay {
+      text_overlay.draw(&mut render_pass);
+    }
+  }
+}
+
+// faces half as wide as the frame's shortest side come out at about a pixel per pixel, within the largest size a texture can be
+fn face_size(device: &Device, width: u32, height: u32) -> u32 {
+  (width.min(height) / 2).clamp(1, device.limits().max_texture_dimension_2d)
+}
+
+fn create_size_bind_group(device: &Device, layout: &BindGroupLayout, width: u32, height: u32) -> BindGroup {
+  let buffer: Buffer = device.create_buffer_init(&BufferInitDescriptor {
+    label: Some("projection size"),
+    contents: bytemuck::cast_slice(&[width as f32, height as f32, 0., 0.]),
+    usage: BufferUsages::UNIFORM,
+  });
+  device.create_bind_group(&BindGroupDescriptor {
+    label: Some("projection size"),
+    layout,
+    entries: &[BindGroupEntry {
+      binding: 0,
+      resource: buffer.as_entire_binding(),
+    }],
+  })
+}
@@ -0,0 +1,268 @@
+// with --remote, a WebSocket server takes JSON commands like `{"command": "uniform", "name": "speed", "value": 2}` from browser panels,
+// phones and other tools, and answers each one with `{"ok": true}` or `{"error": "..."}` once the shader has carried it out
+//
+// it only listens on this computer unless --remote-host says otherwise, and turns away web pages from anywhere else, since a page
+// in any browser tab could otherwise pause the shader or fill the working directory with screenshots
+//
+// with --stdin, the same commands are read a line at a time from standard input, like `uniform speed 2`, for editor plugins and scripts
+
+use std::{io::BufRead, net::{IpAddr, TcpListener, TcpStream}, str::FromStr, sync::{atomic::{AtomicUsize, Ordering}, mpsc::{self, Receiver, Sender}, Arc}, thread, time::Duration};
+use serde::Deserialize;
+use tungstenite::{handshake::server::{ErrorResponse, Request as Handshake, Response}, http::StatusCode, Message};
+
+// how many connections are answered at once, past which any more are closed as soon as they're made
+const MAX_CONNECTIONS: usize = 8;
+// how long a connection has to finish its handshake, so ones that never do don't hold onto one of those places
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// what a remote can ask the running shader to do
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum RemoteCommand {
+  // set one of the custom uniforms to a number, or a list of them for vectors and colors
+  Uniform { name: String, value: Components },
+  Pause,
+  Resume,
+  // jump to this many seconds in
+  Time { seconds: f32 },
+  Speed { speed: f32 },
+  Restart,
+  // compile every pass again, as if they'd all been saved
+  Reload,
+  Screenshot,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Components {
+  Number(f32),
+  List(Vec<f32>),
+}
+
+impl Components {
+  pub fn to_vec(&self) -> Vec<f32> {
+    match self {
+      Components::Number(value) => vec![*value],
+      Components::List(values) => values.clone(),
+    }
+  }
+}
+
+// a command along with where to send whether it worked
+pub struct Request {
+  pub command: RemoteCommand,
+  pub reply: Sender<Result<(), String>>,
+}
+
+pub struct Remote {
+  sender: Sender<Request>,
+  requests: Receiver<Request>,
+}
+
+impl Remote {
+  // commands only come in once Horus is listening for them somewhere
+  pub fn new() -> Self {
+    let (sender, requests) = mpsc::channel();
+    Remote { sender, requests }
+  }
+
+  pub fn listen(&self, host: IpAddr, port: u16) -> Result<(), String> {
+    let listener = TcpListener::bind((host, port)).map_err(|error| error.to_string())?;
+    let sender = self.sender.clone();
+    let connections = Arc::new(AtomicUsize::new(0));
+    thread::spawn(move || {
+      for stream in listener.incoming().flatten() {
+        if connections.load(Ordering::SeqCst) >= MAX_CONNECTIONS {
+          continue;
+        }
+        connections.fetch_add(1, Ordering::SeqCst);
+        let (sender, connections) = (sender.clone(), connections.clone());
+        thread::spawn(move || {
+          serve(stream, sender);
+          connections.fetch_sub(1, Ordering::SeqCst);
+        });
+      }
+    });
+    match host {
+      host if host.is_loopback() => println!("[Horus] Listening for WebSocket commands on ws://localhost:{}", port),
+      host if host.is_unspecified() => println!("[Horus] Listening for WebSocket commands on port {} of every network interface, where anyone on the network can send them", port),
+      host => println!("[Horus] Listening for WebSocket commands on ws://{}, where anyone on the network can send them", std::net::SocketAddr::from((host, port))),
+    }
+    Ok(())
+  }
+
+  // take a command from each line of standard input, printing why any of them didn't work
+  pub fn read_stdin(&self) {
+    let sender = self.sender.clone();
+    thread::spawn(move || {
+      for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else {
+          return;
+        };
+        // blank lines and comments are left alone, so scripts can be piped in as they are
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+          continue;
+        }
+        let result = match line.parse() {
+          Ok(command) => match request(&sender, command) {
+            Some(result) => result,
+            None => return,
+          },
+          Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+          println!("[Horus] Unable to carry out \"{}\": {}", line, error);
+        }
+      }
+    });
+  }
+
+  // every command that came in since the last frame
+  pub fn requests(&self) -> impl Iterator<Item = Request> + '_ {
+    self.requests.try_iter()
+  }
+}
+
+impl Default for Remote {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+// hand `command` to the event loop and wait for it to be carried out, unless the event loop has stopped listening
+fn request(sender: &Sender<Request>, command: RemoteCommand) -> Option<Result<(), String>> {
+  let (reply, result) = mpsc::channel();
+  sender.send(Request { command, reply }).ok()?;
+  // the event loop carries out commands at the start of each frame, so this only takes long if the window is stuck
+  Some(result.recv_timeout(Duration::from_secs(5)).unwrap_or(Err("the shader didn't answer in time".to_string())))
+}
+
+// the commands typed into standard input, which are the JSON commands' names followed by their values
+impl FromStr for RemoteCommand {
+  type Err = String;
+
+  fn from_str(line: &str) -> Result<Self, String> {
+    let mut words = line.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let rest: Vec<&str> = words.collect();
+    let numbers = |words: &[&str]| words.iter().map(|word| word.parse::<f32>().map_err(|_| format!("{} isn't a number", word))).collect::<Result<Vec<_>, _>>();
+    let number = || match numbers(&rest)?[..] {
+      [number] => Ok(number),
+      _ => Err(format!("{} takes a single number", name)),
+    };
+    match (name, rest.as_slice()) {
+      ("uniform", [uniform, values @ ..]) if !values.is_empty() => Ok(RemoteCommand::Uniform { name: uniform.to_string(), value: Components::List(numbers(values)?) }),
+      ("uniform", _) => Err("uniform takes a name followed by a number for each component, like \"uniform speed 2\"".to_string()),
+      ("time", _) => Ok(RemoteCommand::Time { seconds: number()? }),
+      ("speed", _) => Ok(RemoteCommand::Speed { speed: number()? }),
+      ("pause", []) => Ok(RemoteCommand::Pause),
+      ("resume", []) => Ok(RemoteCommand::Resume),
+      ("restart", []) => Ok(RemoteCommand::Restart),
+      ("reload", []) => Ok(RemoteCommand::Reload),
+      ("screenshot", []) => Ok(RemoteCommand::Screenshot),
+      ("pause" | "resume" | "restart" | "reload" | "screenshot", _) => Err(format!("{} doesn't take anything after it", name)),
+      _ => Err("the commands are uniform, pause, resume, time, speed, restart, reload and screenshot".to_string()),
+    }
+  }
+}
+
+// answer the commands from one connection until it closes, or the event loop has stopped listening
+fn serve(stream: TcpStream, sender: Sender<Request>) {
+  let _ = stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT));
+  let Ok(mut socket) = tungstenite::accept_hdr(stream, check_origin) else {
+    return;
+  };
+  // commands can be as far apart as they like once it's connected
+  let _ = socket.get_ref().set_read_timeout(None);
+  loop {
+    let text = match socket.read() {
+      Ok(Message::Text(text)) => text,
+      Ok(Message::Close(_)) | Err(_) => return,
+      Ok(_) => continue,
+    };
+    let result = match serde_json::from_str::<RemoteCommand>(&text) {
+      Ok(command) => match request(&sender, command) {
+        Some(result) => result,
+        None => return,
+      },
+      Err(error) => Err(format!("unable to read the command: {}", error)),
+    };
+    let answer = match result {
+      Ok(()) => serde_json::json!({ "ok": true }),
+      Err(error) => serde_json::json!({ "error": error }),
+    };
+    if socket.send(Message::Text(answer.to_string())).is_err() {
+      return;
+    }
+  }
+}
+
+// browsers let any page open a WebSocket to anywhere, saying which page it was in its Origin, so only pages from this computer or
+// from the address Horus was reached at are let in. Programs that aren't browsers don't send an Origin and are let in as they are.
+// Horus has to be reached at localhost or an address rather than some other name, so a page can't point a domain of its own at it
+#[allow(clippy::result_large_err)]
+fn check_origin(request: &Handshake, response: Response) -> Result<Response, ErrorResponse> {
+  let header = |name: &str| request.headers().get(name).and_then(|value| value.to_str().ok());
+  let host = header("host").map(hostname);
+  let host_allowed = host.is_some_and(|host| host == "localhost" || host.parse::<IpAddr>().is_ok());
+  let origin_allowed = match header("origin") {
+    None => true,
+    Some(origin) => {
+      let origin = hostname(origin.split_once("://").map_or(origin, |(_, rest)| rest));
+      origin == "localhost" || origin.parse::<IpAddr>().is_ok_and(|address| address.is_loopback()) || Some(origin) == host
+    }
+  };
+  if host_allowed && origin_allowed {
+    return Ok(response);
+  }
+  let mut error = ErrorResponse::new(Some("Horus only takes commands from pages on this computer or the address it's reached at".to_string()));
+  *error.status_mut() = StatusCode::FORBIDDEN;
+  Err(error)
+}
+
+// the name or address in `authority` without its port or path, or the brackets around an IPv6 address
+fn hostname(authority: &str) -> &str {
+  let authority = authority.split('/').next().unwrap_or_default();
+  match authority.strip_prefix('[') {
+    Some(address) => address.split(']').next().unwrap_or_default(),
+    None => authority.split(':').next().unwrap_or_default(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn allowed(host: &str, origin: Option<&str>) -> bool {
+    let mut request = Handshake::builder().header("host", host);
+    if let Some(origin) = origin {
+      request = request.header("origin", origin);
+    }
+    check_origin(&request.body(()).unwrap(), Response::new(())).is_ok()
+  }
+
+  #[test]
+  fn lets_in_programs_and_local_pages() {
+    assert!(allowed("localhost:9001", None));
+    assert!(allowed("127.0.0.1:9001", Some("http://localhost:8000")));
+    assert!(allowed("[::1]:9001", Some("http://[::1]:8000")));
+    assert!(allowed("192.168.1.5:9001", Some("http://192.168.1.5:8000")));
+  }
+
+  #[test]
+  fn turns_away_other_pages() {
+    assert!(!allowed("127.0.0.1:9001", Some("https://example.com")));
+    assert!(!allowed("127.0.0.1:9001", Some("null")));
+    assert!(!allowed("192.168.1.5:9001", Some("http://192.168.1.9")));
+    // a domain that was pointed at this computer
+    assert!(!allowed("example.com:9001", Some("http://example.com")));
+  }
+
+  #[test]
+  fn hostname_drops_ports_and_brackets() {
+    assert_eq!(hostname("localhost:9001"), "localhost");
+    assert_eq!(hostname("[::1]:9001"), "::1");
+    assert_eq!(hostname("example.com/panel"), "example.com");
+  }
+}
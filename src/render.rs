@@ -0,0 +1,309 @@
+// `horus render` draws frames into an offscreen texture and saves them, without ever opening a window
+
+use std::{fs::create_dir_all, path::PathBuf};
+use chrono::{DateTime, Local};
+use image::{imageops, ImageBuffer, Pixel, Rgba, Rgba32FImage, RgbaImage};
+use clap::{Args, ValueEnum};
+use serde::Deserialize;
+use wgpu::{Color, CommandEncoderDescriptor, Device, DeviceDescriptor, ErrorFilter, Extent3d, Features, Instance, Limits, Queue, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor};
+
+use crate::{capture::{self, Readback}, cli::Options, clock, gpu, live::LiveChannels, msaa::{self, Multisample}, pass::Passes, projection::Projection, replay::Replay, scale::Canvas, sound, create_pipeline_layout, create_vertex_shader, load_passes, render_frame, upload_uniforms, Uniforms, UniformsBinding};
+
+// frames are written as if they were shown on an sRGB surface, so they match what the window shows
+pub const FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+// or with --linear, as the values the shader wrote, like a linear surface shows them
+pub const LINEAR_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+
+// 16-bit PNGs and EXRs are rendered into half floats, which keep whatever the shader writes
+pub const FLOAT_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+// how `horus render` saves each frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FrameFormat {
+  #[default]
+  #[value(help = "8-bit PNGs, which look the same as the window")]
+  Png,
+  #[value(help = "16-bit PNGs, which look the same as the window but can be graded without banding")]
+  Png16,
+  #[value(help = "OpenEXR images of the linear colors the shader wrote, which aren't clamped between 0 and 1")]
+  Exr,
+}
+
+impl FrameFormat {
+  pub fn texture_format(self, linear: bool) -> TextureFormat {
+    match self {
+      FrameFormat::Png if linear => LINEAR_FORMAT,
+      FrameFormat::Png => FORMAT,
+      FrameFormat::Png16 | FrameFormat::Exr => FLOAT_FORMAT,
+    }
+  }
+
+  fn extension(self) -> &'static str {
+    match self {
+      FrameFormat::Png | FrameFormat::Png16 => "png",
+      FrameFormat::Exr => "exr",
+    }
+  }
+}
+
+// options that only apply to `horus render`
+#[derive(Args, Debug)]
+pub struct RenderOptions {
+  // these aren't given defaults here so that horus.toml can fill in whichever ones aren't passed
+  #[arg(long, value_parser = clap::value_parser!(u32).range(1..), help = "The width of each frame in pixels [default: 1920]")]
+  pub width: Option<u32>,
+  #[arg(long, value_parser = clap::value_parser!(u32).range(1..), help = "The height of each frame in pixels [default: 1080]")]
+  pub height: Option<u32>,
+  #[arg(long, help = "How many frames to render [default: 1, --duration seconds of them, or every frame of --replay-input]")]
+  pub frames: Option<u32>,
+  #[arg(long, value_parser = clap::value_parser!(u32).range(1..), help = "How many frames make up a second of the shader's time [default: 60]")]
+  pub fps: Option<u32>,
+  #[arg(short, long, value_name = "DIRECTORY", help = "The directory frames are saved into [default: .]")]
+  pub output: Option<PathBuf>,
+  #[arg(long, value_name = "SIZE", value_parser = clap::value_parser!(u32).range(1..), help = "Render each frame in tiles at most this many pixels on a side, which are stitched together, for frames bigger than the GPU can render at once")]
+  pub tile: Option<u32>,
+  #[arg(long, value_enum, help = "How each frame is saved [default: png]")]
+  pub format: Option<FrameFormat>,
+}
+
+pub async fn render(options: Options, render: RenderOptions) -> Result<(), String> {
+  let replay = options.replay_input.as_deref().map(Replay::load).transpose()?;
+  let (width, height, fps) = (render.width.unwrap_or(1920), render.height.unwrap_or(1080), render.fps.unwrap_or(60));
+  // --duration renders that many seconds of frames, and a replay renders every frame up to the end of the input by default
+  let frames = render.frames.unwrap_or_else(|| match (options.duration, &replay) {
+    (Some(seconds), _) => ((seconds * fps as f32).round() as u32).max(1),
+    (None, Some(replay)) => (replay.duration() / options.speed.unwrap_or(1.) * fps as f32) as u32 + 1,
+    (None, None) => 1,
+  });
+  // horus.toml could still have set any of them to 0
+  if width == 0 || height == 0 || fps == 0 || render.tile == Some(0) {
+    return Err("--width, --height, --fps and --tile must be greater than 0".to_string());
+  }
+  let output = render.output.unwrap_or_else(|| PathBuf::from("."));
+  let (device, queue) = request_device(&options).await?;
+  let format = render.format.unwrap_or_default();
+  // EXRs keep every value the shader wrote, however bright
+  let options = match format {
+    FrameFormat::Exr => Options { hdr: false, tonemap: None, ..options },
+    _ => options,
+  };
+  let mut offscreen = Offscreen::new(&device, &queue, &options, width, height, fps, render.tile, format.texture_format(options.linear))?;
+  if let Some(replay) = replay {
+    offscreen.set_replay(replay);
+  }
+
+  create_dir_all(&output).map_err(|error| format!("Unable to create {}: {}", output.display(), error))?;
+  let digits = capture::sequence_digits(frames);
+  for frame in 0..frames {
+    let path = capture::sequence_path(&output, frame, digits).with_extension(format.extension());
+    let saved = match format {
+      FrameFormat::Png => offscreen.next_frame()?.save(&path),
+      FrameFormat::Png16 => to_png16(&offscreen.next_float_frame()?, options.linear).save(&path),
+      FrameFormat::Exr => offscreen.next_float_frame()?.save(&path),
+    };
+    saved.map_err(|error| format!("Unable to save {}: {}", path.display(), error))?;
+  }
+  println!("[Horus] Rendered {} frames into {}", frames, output.display());
+  Ok(())
+}
+
+// a shader rendering one frame after another into a texture, which each frame is read back from
+pub struct Offscreen<'a> {
+  device: &'a Device,
+  queue: &'a Queue,
+  target: Texture,
+  view: TextureView,
+  canvas: Option<Canvas>,
+  projection: Option<Projection>,
+  multisample: Option<Multisample>,
+  live: LiveChannels,
+  passes: Passes,
+  uniforms: Uniforms,
+  uniforms_binding: UniformsBinding,
+  start: DateTime<Local>,
+  width: u32,
+  height: u32,
+  // the size of the target, which each frame is rendered a piece at a time into when it's smaller than the frame
+  tile_width: u32,
+  tile_height: u32,
+  format: TextureFormat,
+  fps: u32,
+  speed: f32,
+  // with --capture-alpha, whatever the shader doesn't draw over is left transparent
+  clear: Color,
+  // input recorded with --record-input, which the mouse and keyboard follow
+  replay: Option<Replay>,
+  frame: u32,
+}
+
+impl<'a> Offscreen<'a> {
+  // load the shader `options` runs, which renders `width` by `height` frames that are each 1 / `fps` seconds apart into `format`,
+  // in tiles at most `tile` pixels on a side if it's given
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(device: &'a Device, queue: &'a Queue, options: &Options, width: u32, height: u32, fps: u32, tile: Option<u32>, format: TextureFormat) -> Result<Self, String> {
+    // the tiles' edges wouldn't line up with the pixels of a canvas that's stretched over them
+    if tile.is_some() && options.scale.is_some_and(|scale| scale != 1.) {
+      return Err("--tile doesn't work along with --scale".to_string());
+    }
+    // or the canvas --hdr renders into
+    if tile.is_some() && options.tonemap().is_some() {
+      return Err("--tile doesn't work along with --hdr".to_string());
+    }
+    // every tile would need the whole cubemap
+    if tile.is_some() && options.projection.is_some() {
+      return Err("--tile doesn't work along with --projection".to_string());
+    }
+    let (tile_width, tile_height) = tile.map_or((width, height), |tile| (width.min(tile), height.min(tile)));
+    let target = device.create_texture(&TextureDescriptor {
+      label: Some("render target"),
+      size: Extent3d { width: tile_width, height: tile_height, depth_or_array_layers: 1 },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D2,
+      format,
+      usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+    });
+    let view = target.create_view(&TextureViewDescriptor::default());
+
+    // frames can be supersampled with --scale 2, or rendered at a lower resolution and stretched, and are tonemapped from a canvas with --hdr
+    let canvas_scale = options.scale.filter(|scale| *scale != 1.).or(options.tonemap().map(|_| 1.));
+    let canvas = canvas_scale.map(|scale| Canvas::new(device, format, scale, width, height, options.tonemap()));
+    let (scaled_width, scaled_height) = canvas.as_ref().map_or((width, height), Canvas::size);
+    let image_format = canvas.as_ref().map_or(format, Canvas::format);
+    // a projection's cubemap is warped into the frame, or into the canvas
+    let projection = options.projection.map(|kind| Projection::new(device, image_format, kind, scaled_width, scaled_height));
+    let (scaled_width, scaled_height) = projection.as_ref().map_or((scaled_width, scaled_height), Projection::size);
+
+    let vertex_shader = create_vertex_shader(device);
+    // nobody can type while rendering, so the keyboard texture stays empty
+    let mut live = LiveChannels::new(device, &options.channel_names(), options.audio.as_deref());
+    // buffers are as big as the whole frame even when it's tiled, since every tile reads all of them
+    device.push_error_scope(ErrorFilter::Validation);
+    let passes = load_passes(device, queue, options, &mut live, image_format, scaled_width, scaled_height);
+    if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+      return Err(format!("Unable to render {}x{} frames on this GPU, since the buffers have to fit in a single texture: {}", scaled_width, scaled_height, error));
+    }
+    let mut passes = passes?;
+    let multisample = msaa::create(device, image_format, options.msaa, scaled_width.min(tile_width), scaled_height.min(tile_height));
+    passes.image.samples = multisample.as_ref().map_or(1, Multisample::samples);
+    let mut uniforms = Uniforms::new(scaled_width, scaled_height);
+    uniforms.sample_rate = live.sample_rate() as f32;
+    // renders are the same every time, so the seed is only ever random in the window
+    uniforms.set_seed(options.seed.unwrap_or(0));
+    let uniforms_binding = UniformsBinding::new(device, &uniforms);
+    let layout = create_pipeline_layout(device, &uniforms_binding, &passes);
+    passes.compile(device, &layout, &vertex_shader, None);
+    if !passes.errors().is_empty() {
+      return Err("Unable to render a shader that doesn't compile".to_string());
+    }
+    // the audio channel and the beat follow the sound pass, which is never played out loud here
+    if passes.take_sound_changed() {
+      sound::play(device, queue, &passes, &mut live, options.audio.as_deref());
+    }
+    Ok(Offscreen { device, queue, target, view, canvas, projection, multisample, live, passes, uniforms, uniforms_binding, start: if options.deterministic { clock::fixed_start() } else { Local::now() }, width, height, tile_width, tile_height, format, fps, speed: options.speed.unwrap_or(1.), clear: if options.capture_alpha { Color::TRANSPARENT } else { Color::BLACK }, replay: None, frame: 0 })
+  }
+
+  // shaders that read the date render the same frames whenever they're started from the same one
+  pub fn set_start(&mut self, start: DateTime<Local>) {
+    self.start = start;
+  }
+
+  pub fn set_replay(&mut self, replay: Replay) {
+    self.replay = Some(replay);
+  }
+
+  // render the next frame and wait for it to be read back
+  pub fn next_frame(&mut self) -> Result<RgbaImage, String> {
+    self.render_next(|readback, device| readback.into_image(device))
+  }
+
+  // the next frame with every value as the shader wrote it, which needs the target to be FLOAT_FORMAT
+  pub fn next_float_frame(&mut self) -> Result<Rgba32FImage, String> {
+    self.render_next(|readback, device| readback.into_float_image(device))
+  }
+
+  fn render_next<P: Pixel>(&mut self, read: impl Fn(Readback, &Device) -> Result<ImageBuffer<P, Vec<P::Subpixel>>, String>) -> Result<ImageBuffer<P, Vec<P::Subpixel>>, String> {
+    // time advances by exactly one frame at a time, however long each frame takes to render
+    let uniforms = &mut self.uniforms;
+    uniforms.time = self.frame as f32 / self.fps as f32 * self.speed;
+    uniforms.time_delta = self.speed / self.fps as f32;
+    uniforms.set_date(self.start);
+    uniforms.frame = self.frame as i32;
+    match self.replay.as_mut().map(|replay| replay.advance(uniforms.time)) {
+      Some(Some(input)) => input.apply(uniforms, self.live.keyboard()),
+      // a click only counts as new on the first frame it's seen
+      Some(None) => uniforms.mouse_click[3] = -uniforms.mouse_click[3].abs(),
+      None => {}
+    }
+    self.live.update(self.queue, uniforms.time);
+    self.live.end_frame();
+    [uniforms.beat, uniforms.bpm] = self.live.beat();
+    self.passes.animate(uniforms.time);
+    self.passes.params.upload(self.queue);
+
+    let tiled = self.tile_width < self.width || self.tile_height < self.height;
+    let mut image = ImageBuffer::new(if tiled { self.width } else { 0 }, if tiled { self.height } else { 0 });
+    for y in (0..self.height).step_by(self.tile_height as usize) {
+      for x in (0..self.width).step_by(self.tile_width as usize) {
+        self.uniforms.offset = [x as f32, y as f32];
+        // nobody can touch the screen while rendering either
+        upload_uniforms(self.queue, &self.uniforms_binding, &self.passes, &self.uniforms, &bytemuck::Zeroable::zeroed());
+        if let Some(projection) = &self.projection {
+          projection.upload_uniforms(self.queue, &self.uniforms);
+        }
+        let mut encoder = self.device.create_command_encoder(&CommandEncoderDescriptor { label: None });
+        let image_view = self.canvas.as_ref().map_or(&self.view, Canvas::view);
+        // the buffers only render once a frame, before the first tile
+        let viewport = tiled.then_some([-(x as f32), -(y as f32), self.width as f32, self.height as f32]);
+        match &self.projection {
+          Some(projection) => {
+            projection.render(&mut encoder, self.multisample.as_ref(), &self.passes, self.frame as u64, true, self.clear);
+            projection.draw(&mut encoder, image_view, None);
+          }
+          None => render_frame(&mut encoder, image_view, self.multisample.as_ref(), &self.passes, &self.uniforms_binding.bind_group, self.frame as u64, x == 0 && y == 0, self.clear, viewport, None),
+        }
+        if let Some(canvas) = &self.canvas {
+          canvas.draw(&mut encoder, &self.view, None);
+        }
+        let readback = Readback::new(self.device, &mut encoder, &self.target, self.tile_width, self.tile_height, self.format)?;
+        self.queue.submit(std::iter::once(encoder.finish()));
+        let piece = read(readback, self.device)?;
+        if !tiled {
+          self.frame += 1;
+          return Ok(piece);
+        }
+        // tiles along the right and bottom edges hang over them
+        imageops::replace(&mut image, &piece, x as i64, y as i64);
+      }
+    }
+    self.frame += 1;
+    Ok(image)
+  }
+}
+
+// any adapter will do since there's no surface to present to, as long as it's on the backend that was asked for
+pub async fn request_device(options: &Options) -> Result<(Device, Queue), String> {
+  let instance = Instance::new(options.backends());
+  let adapter = gpu::request_adapter(&instance, options, None).await?;
+  adapter.request_device(&DeviceDescriptor {
+    features: Features::empty(),
+    limits: Limits::default(),
+    label: None,
+  }, None).await.map_err(|error| error.to_string())
+}
+
+// 16-bit PNGs are encoded into sRGB like the 8-bit ones unless they're `linear`, with 256 times as many steps between each of their values
+fn to_png16(image: &Rgba32FImage, linear: bool) -> ImageBuffer<Rgba<u16>, Vec<u16>> {
+  let encode = |value: f32| match value {
+    _ if linear => value,
+    value if value <= 0.0031308 => value * 12.92,
+    value => 1.055 * value.powf(1. / 2.4) - 0.055,
+  };
+  let values = image.pixels().flat_map(|pixel| {
+    let [r, g, b, a] = pixel.0;
+    [encode(r), encode(g), encode(b), a]
+  }).map(|value| (value.clamp(0., 1.) * u16::MAX as f32).round() as u16).collect();
+  ImageBuffer::from_raw(image.width(), image.height(), values).unwrap_or_default()
+}
@@ -0,0 +1,123 @@
+// --record-input saves the mouse, the scroll wheel, the keys and the seed the shader saw on every frame of a session, along with when it saw them, and --replay-input
+// feeds them back in at the same times, so an interactive performance can be rendered again afterwards with `horus render`
+//
+// each line of the file is one frame, like {"time":1.5,"resolution":[700,700],"mouse":[350,120],"mouse_click":[350,120,340,-100],"keys":[[65,true]],"seed":0.25,"scroll":[0,3]}
+
+use std::{fs::{read_to_string, File}, io::{BufWriter, Write}, path::{Path, PathBuf}};
+use serde::{Deserialize, Serialize};
+
+use crate::{keyboard::Keyboard, Uniforms};
+
+// the input to a single frame
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputFrame {
+  pub time: f32,
+  // what the mouse is relative to, since replays are usually rendered at a different size to the window they were recorded in
+  pub resolution: [f32; 2],
+  pub mouse: [f32; 2],
+  pub mouse_click: [f32; 4],
+  // the javascript key codes that went down or came up since the last frame, and whether they're held
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub keys: Vec<(usize, bool)>,
+  // the seed uniform, which N rolls again while the session runs, and which files saved before it was recorded leave alone
+  #[serde(default)]
+  pub seed: Option<f32>,
+  // how far the mouse wheel has scrolled, which files saved before it was recorded leave at 0
+  #[serde(default)]
+  pub scroll: Option<[f32; 2]>,
+}
+
+impl InputFrame {
+  pub fn new(uniforms: &Uniforms, keys: Vec<(usize, bool)>) -> Self {
+    InputFrame { time: uniforms.time, resolution: uniforms.resolution, mouse: uniforms.mouse, mouse_click: uniforms.mouse_click, keys, seed: Some(uniforms.seed), scroll: Some(uniforms.scroll) }
+  }
+
+  // move the mouse to where it was, stretched over `uniforms.resolution`, and press the keys that were pressed
+  pub fn apply(&self, uniforms: &mut Uniforms, keyboard: &mut Keyboard) {
+    let scale = [uniforms.resolution[0] / self.resolution[0], uniforms.resolution[1] / self.resolution[1]];
+    uniforms.mouse = [self.mouse[0] * scale[0], self.mouse[1] * scale[1]];
+    // the click's signs say whether the button is held, which scaling keeps
+    uniforms.mouse_click = [0, 1, 2, 3].map(|index| self.mouse_click[index] * scale[index % 2]);
+    for (code, held) in &self.keys {
+      keyboard.press(*code, *held);
+    }
+    if let Some(seed) = self.seed {
+      uniforms.seed = seed;
+    }
+    if let Some(scroll) = self.scroll {
+      uniforms.scroll = scroll;
+    }
+  }
+}
+
+// writes a frame of input at a time as the session runs
+pub struct InputRecorder {
+  path: PathBuf,
+  file: BufWriter<File>,
+}
+
+impl InputRecorder {
+  pub fn create(path: &Path) -> Result<Self, String> {
+    let file = File::create(path).map_err(|error| error.to_string())?;
+    Ok(InputRecorder { path: path.to_path_buf(), file: BufWriter::new(file) })
+  }
+
+  pub fn write(&mut self, frame: &InputFrame) -> Result<(), String> {
+    serde_json::to_writer(&mut self.file, frame).map_err(|error| error.to_string())?;
+    writeln!(self.file).map_err(|error| error.to_string())
+  }
+
+  // make sure every frame is on disk, returning where they were saved
+  pub fn finish(mut self) -> Result<PathBuf, String> {
+    self.file.flush().map_err(|error| error.to_string())?;
+    Ok(self.path)
+  }
+}
+
+// a recorded session, played back by the time each frame was at
+pub struct Replay {
+  frames: Vec<InputFrame>,
+  // the next frame that hasn't been reached yet
+  next: usize,
+}
+
+impl Replay {
+  pub fn load(path: &Path) -> Result<Self, String> {
+    let source = read_to_string(path).map_err(|error| format!("Unable to read {}: {}", path.display(), error))?;
+    let frames = source.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).map(|(index, line)| {
+      serde_json::from_str(line).map_err(|error| format!("Unable to load line {} of {}: {}", index + 1, path.display(), error))
+    }).collect::<Result<Vec<InputFrame>, String>>()?;
+    if frames.is_empty() {
+      return Err(format!("There's no input recorded in {}", path.display()));
+    }
+    Ok(Replay { frames, next: 0 })
+  }
+
+  // how long the session ran for
+  pub fn duration(&self) -> f32 {
+    self.frames.last().map_or(0., |frame| frame.time)
+  }
+
+  pub fn is_done(&self) -> bool {
+    self.next == self.frames.len()
+  }
+
+  // the input at `time`, which is wherever the mouse was at the last frame before it along with every key pressed since the last call,
+  // or `None` if no frame has been reached since then
+  pub fn advance(&mut self, time: f32) -> Option<InputFrame> {
+    let start = self.next;
+    while self.frames.get(self.next).is_some_and(|frame| frame.time <= time) {
+      self.next += 1;
+    }
+    let passed = &self.frames[start..self.next];
+    let mut frame = passed.last()?.clone();
+    frame.keys = passed.iter().flat_map(|frame| frame.keys.iter().copied()).collect();
+    Some(frame)
+  }
+
+  // go back to the beginning, for when time does
+  pub fn restart(&mut self) {
+    self.next = 0;
+  }
+}
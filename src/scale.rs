@@ -0,0 +1,339 @@
+// with --scale, the image pass renders into a texture at a multiple of the window's resolution that's then stretched over the window,
+// so heavy shaders can run at a fraction of the pixels and simple ones can be supersampled
+//
+// the magnifier draws the window through a canvas too, zoomed in on part of it with each of the shader's pixels shown as a square,
+// and --hdr renders into one made of half floats, which is tonemapped as it's drawn into the window
+
+use wgpu::{AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferBindingType, BufferUsages, Color, ColorTargetState, ColorWrites, CommandEncoder, Device, Extent3d, FilterMode, FragmentState, LoadOp, Queue, MultisampleState, Operations, PipelineLayoutDescriptor, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension, VertexState, util::{BufferInitDescriptor, DeviceExt}};
+
+use crate::{cli::Tonemap, overlay::TextOverlay};
+
+const SOURCE: &str = "\
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var canvas: texture_2d<f32>;
+@group(0) @binding(1)
+var canvas_sampler: sampler;
+// the center of the part of the canvas that's shown, followed by how far it's zoomed in
+@group(0) @binding(2)
+var<uniform> magnify: vec4<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var vertices = array<vec2<f32>, 3>(
+        vec2<f32>(-1., 1.),
+        vec2<f32>(3., 1.),
+        vec2<f32>(-1., -3.),
+    );
+    var out: VertexOutput;
+    out.position = vec4<f32>(vertices[vertex_index], 0., 1.);
+    out.uv = vertices[vertex_index] * vec2<f32>(0.5, -0.5) + 0.5;
+    return out;
+}
+
+fn shown(in: VertexOutput) -> vec4<f32> {
+    return textureSample(canvas, canvas_sampler, magnify.xy + (in.uv - 0.5) / magnify.z);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return shown(in);
+}
+
+@fragment
+fn fs_clamp(in: VertexOutput) -> @location(0) vec4<f32> {
+    return clamp(shown(in), vec4<f32>(0.), vec4<f32>(1.));
+}
+
+@fragment
+fn fs_reinhard(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = shown(in);
+    let rgb = max(color.rgb, vec3<f32>(0.));
+    return vec4<f32>(rgb / (1. + rgb), clamp(color.a, 0., 1.));
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+@fragment
+fn fs_aces(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = shown(in);
+    let rgb = max(color.rgb, vec3<f32>(0.));
+    let mapped = rgb * (2.51 * rgb + 0.03) / (rgb * (2.43 * rgb + 0.59) + 0.14);
+    return vec4<f32>(clamp(mapped, vec3<f32>(0.), vec3<f32>(1.)), clamp(color.a, 0., 1.));
+}\
+";
+
+// the half floats --hdr renders into, like the buffers
+const HDR_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+// the most the magnifier zooms in, where each pixel covers 64x64 of the window's
+pub const MAX_ZOOM: f32 = 64.;
+
+pub struct Canvas {
+  scale: f32,
+  // the texture's format, which is only the window's without --hdr
+  format: TextureFormat,
+  // the largest either side of the texture can be
+  max_size: u32,
+  width: u32,
+  height: u32,
+  // the window's size, which the magnifier zooms and pans in
+  window_width: u32,
+  window_height: u32,
+  center: [f32; 2],
+  zoom: f32,
+  magnify: Buffer,
+  texture: Texture,
+  view: TextureView,
+  pipeline: RenderPipeline,
+  layout: BindGroupLayout,
+  // neighbouring pixels are blended together, which averages them when supersampling
+  sampler: Sampler,
+  bind_group: BindGroup,
+  // zoomed in pixels are left as squares instead
+  nearest_bind_group: BindGroup,
+}
+
+impl Canvas {
+  // a canvas `scale` times the size of a `width` by `height` window, which renders to `format`, after `tonemap` if it's in HDR
+  pub fn new(device: &Device, format: TextureFormat, scale: f32, width: u32, height: u32, tonemap: Option<Tonemap>) -> Self {
+    let shader = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("canvas"),
+      source: ShaderSource::Wgsl(SOURCE.into()),
+    });
+    let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: Some("canvas"),
+      entries: &[
+        BindGroupLayoutEntry {
+          binding: 0,
+          visibility: ShaderStages::FRAGMENT,
+          count: None,
+          ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: true },
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+          },
+        },
+        BindGroupLayoutEntry {
+          binding: 1,
+          visibility: ShaderStages::FRAGMENT,
+          count: None,
+          ty: BindingType::Sampler(SamplerBindingType::Filtering),
+        },
+        BindGroupLayoutEntry {
+          binding: 2,
+          visibility: ShaderStages::FRAGMENT,
+          count: None,
+          ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+          },
+        },
+      ],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+      label: Some("canvas"),
+      bind_group_layouts: &[&layout],
+      push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("canvas"),
+      layout: Some(&pipeline_layout),
+      vertex: VertexState {
+        module: &shader,
+        entry_point: "vs_main",
+        buffers: &[],
+      },
+      fragment: Some(FragmentState {
+        module: &shader,
+        entry_point: match tonemap {
+          None => "fs_main",
+          Some(Tonemap::Clamp) => "fs_clamp",
+          Some(Tonemap::Reinhard) => "fs_reinhard",
+          Some(Tonemap::Aces) => "fs_aces",
+        },
+        targets: &[Some(ColorTargetState {
+          format,
+          blend: None,
+          write_mask: ColorWrites::ALL,
+        })],
+      }),
+      primitive: PrimitiveState::default(),
+      depth_stencil: None,
+      multisample: MultisampleState::default(),
+      multiview: None,
+    });
+    let sampler = device.create_sampler(&SamplerDescriptor {
+      label: Some("canvas"),
+      address_mode_u: AddressMode::ClampToEdge,
+      address_mode_v: AddressMode::ClampToEdge,
+      mag_filter: FilterMode::Linear,
+      min_filter: FilterMode::Linear,
+      ..Default::default()
+    });
+    let (center, zoom) = ([0.5; 2], 1.);
+    let magnify = device.create_buffer_init(&BufferInitDescriptor {
+      label: Some("canvas magnify"),
+      contents: bytemuck::cast_slice(&[center[0], center[1], zoom, 0.]),
+      usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    });
+    let max_size = device.limits().max_texture_dimension_2d;
+    let (window_width, window_height) = (width, height);
+    let (width, height) = scaled_size(scale, max_size, width, height);
+    let format = if tonemap.is_some() { HDR_FORMAT } else { format };
+    let (texture, view, [bind_group, nearest_bind_group]) = create_texture(device, &layout, [&sampler, &create_nearest_sampler(device)], &magnify, format, width, height);
+    Canvas { scale, format, max_size, width, height, window_width, window_height, center, zoom, magnify, texture, view, pipeline, layout, sampler, bind_group, nearest_bind_group }
+  }
+
+  // match the canvas to a window that's now `width` by `height`
+  pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+    (self.window_width, self.window_height) = (width, height);
+    (self.width, self.height) = scaled_size(self.scale, self.max_size, width, height);
+    (self.texture, self.view, [self.bind_group, self.nearest_bind_group]) = create_texture(device, &self.layout, [&self.sampler, &create_nearest_sampler(device)], &self.magnify, self.format, self.width, self.height);
+  }
+
+  // zoom in by `factor`, or out if it's below 1, keeping the pixel under `position` in the window where it is
+  pub fn zoom(&mut self, queue: &Queue, position: [f32; 2], factor: f32) {
+    let uv = self.window_uv(position);
+    let pinned = self.canvas_uv(position);
+    self.zoom = (self.zoom * factor).clamp(1., MAX_ZOOM);
+    self.center = [pinned[0] - (uv[0] - 0.5) / self.zoom, pinned[1] - (uv[1] - 0.5) / self.zoom];
+    self.set_view(queue, self.center, self.zoom);
+  }
+
+  // move what's shown along with a drag of `delta` across the window
+  pub fn pan(&mut self, queue: &Queue, delta: [f32; 2]) {
+    let center = [self.center[0] - delta[0] / self.window_width as f32 / self.zoom, self.center[1] - delta[1] / self.window_height as f32 / self.zoom];
+    self.set_view(queue, center, self.zoom);
+  }
+
+  // show the whole canvas again
+  pub fn reset_view(&mut self, queue: &Queue) {
+    self.set_view(queue, [0.5; 2], 1.);
+  }
+
+  pub fn is_zoomed(&self) -> bool {
+    self.zoom > 1.
+  }
+
+  // the pixel of the canvas that's shown at `position` in the window, in the pixels the shader renders
+  pub fn pixel_at(&self, position: [f32; 2]) -> [f32; 2] {
+    let uv = self.canvas_uv(position);
+    [uv[0] * self.width as f32, uv[1] * self.height as f32]
+  }
+
+  fn window_uv(&self, position: [f32; 2]) -> [f32; 2] {
+    [position[0] / self.window_width as f32, position[1] / self.window_height as f32]
+  }
+
+  fn canvas_uv(&self, position: [f32; 2]) -> [f32; 2] {
+    let uv = self.window_uv(position);
+    [self.center[0] + (uv[0] - 0.5) / self.zoom, self.center[1] + (uv[1] - 0.5) / self.zoom]
+  }
+
+  // the window never shows anything past the canvas's edges
+  fn set_view(&mut self, queue: &Queue, center: [f32; 2], zoom: f32) {
+    let half = 0.5 / zoom;
+    self.center = center.map(|side| side.clamp(half, 1. - half));
+    self.zoom = zoom;
+    queue.write_buffer(&self.magnify, 0, bytemuck::cast_slice(&[self.center[0], self.center[1], self.zoom, 0.]));
+  }
+
+  pub fn scale(&self) -> f32 {
+    self.scale
+  }
+
+  // the resolution the shader renders at
+  pub fn size(&self) -> (u32, u32) {
+    (self.width, self.height)
+  }
+
+  // the format the shader renders into
+  pub fn format(&self) -> TextureFormat {
+    self.format
+  }
+
+  // what the shader renders into instead of the window
+  pub fn view(&self) -> &TextureView {
+    &self.view
+  }
+
+  // the frame the shader rendered, which can be copied out of it
+  pub fn texture(&self) -> &Texture {
+    &self.texture
+  }
+
+  // stretch the canvas over `view`, with the text overlay drawn on top at the window's own resolution
+  pub fn draw(&self, encoder: &mut CommandEncoder, view: &TextureView, text_overlay: Option<&TextOverlay>) {
+    let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+      label: Some("canvas"),
+      color_attachments: &[Some(RenderPassColorAttachment {
+        view,
+        resolve_target: None,
+        ops: Operations {
+          load: LoadOp::Clear(Color::TRANSPARENT),
+          store: true,
+        },
+      })],
+      depth_stencil_attachment: None,
+    });
+    render_pass.set_pipeline(&self.pipeline);
+    render_pass.set_bind_group(0, if self.is_zoomed() { &self.nearest_bind_group } else { &self.bind_group }, &[]);
+    render_pass.draw(0..3, 0..1);
+    if let Some(text_overlay) = text_overlay {
+      text_overlay.draw(&mut render_pass);
+    }
+  }
+}
+
+// `width` by `height` times `scale`, within the largest size a texture can be
+fn scaled_size(scale: f32, max_size: u32, width: u32, height: u32) -> (u32, u32) {
+  let scaled = |side: u32| ((side as f32 * scale).round() as u32).clamp(1, max_size);
+  (scaled(width), scaled(height))
+}
+
+fn create_nearest_sampler(device: &Device) -> Sampler {
+  device.create_sampler(&SamplerDescriptor {
+    label: Some("canvas nearest"),
+    address_mode_u: AddressMode::ClampToEdge,
+    address_mode_v: AddressMode::ClampToEdge,
+    ..Default::default()
+  })
+}
+
+// the canvas's texture, along with a bind group for it with each of `samplers`
+fn create_texture(device: &Device, layout: &BindGroupLayout, samplers: [&Sampler; 2], magnify: &Buffer, format: TextureFormat, width: u32, height: u32) -> (Texture, TextureView, [BindGroup; 2]) {
+  let texture = device.create_texture(&TextureDescriptor {
+    label: Some("canvas"),
+    size: Extent3d { width, height, depth_or_array_layers: 1 },
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: TextureDimension::D2,
+    format,
+    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC,
+  });
+  let view = texture.create_view(&TextureViewDescriptor::default());
+  let bind_groups = samplers.map(|sampler| device.create_bind_group(&BindGroupDescriptor {
+    label: Some("canvas"),
+    layout,
+    entries: &[
+      BindGroupEntry {
+        binding: 0,
+        resource: BindingResource::TextureView(&view),
+      },
+      BindGroupEntry {
+        binding: 1,
+        resource: BindingResource::Sampler(sampler),
+      },
+      BindGroupEntry {
+        binding: 2,
+        resource: magnify.as_entire_binding(),
+      },
+    ],
+  }));
+  (texture, view, bind_groups)
+}
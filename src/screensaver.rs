@@ -0,0 +1,136 @@
+// with --screensaver, the shader fills the screen without a cursor and Horus exits as soon as anyone touches the mouse or keyboard,
+// so a bundled project renamed to .scr can be installed as a Windows screensaver, or run by xscreensaver and the like anywhere else
+//
+// Windows runs screensavers with /s to show them, /p and a window to preview them inside of, and /c or nothing at all to configure them
+
+use std::ffi::OsString;
+use winit::{dpi::PhysicalPosition, event::{ElementState, KeyboardInput, WindowEvent}, window::WindowBuilder};
+
+// how many pixels the cursor has to move to wake the computer up, so a bumped desk doesn't
+const WAKE_DISTANCE: f64 = 8.;
+
+// what Windows asked a screensaver to do
+pub enum Request {
+  Show,
+  // the window handle of the preview in the screensaver settings
+  Preview(isize),
+  Configure,
+}
+
+// what the arguments Horus was run with ask of it as a screensaver, if they're any of the ones Windows passes
+pub fn request(args: &[OsString]) -> Option<Request> {
+  if !cfg!(target_os = "windows") {
+    return None;
+  }
+  let is_screensaver = args.first().is_some_and(|program| program.to_string_lossy().to_lowercase().ends_with(".scr"));
+  let arguments: Vec<String> = args.iter().skip(1).map(|argument| argument.to_string_lossy().to_lowercase()).collect();
+  // the window can come after a colon or as the next argument
+  let (flag, window) = match arguments.first() {
+    Some(first) if first.starts_with('/') => match first[1..].split_once(':') {
+      Some((flag, window)) => (flag.to_string(), Some(window.to_string())),
+      None => (first[1..].to_string(), arguments.get(1).cloned()),
+    },
+    None if is_screensaver => return Some(Request::Configure),
+    _ => return None,
+  };
+  match flag.as_str() {
+    "s" => Some(Request::Show),
+    "p" => window.and_then(|window| window.parse().ok()).map(Request::Preview),
+    "c" => Some(Request::Configure),
+    _ => None,
+  }
+}
+
+// the arguments that run the screensaver `request` asks for, after the program's own name
+pub fn arguments(program: OsString, request: &Request) -> Vec<OsString> {
+  let mut arguments = vec![program, "--screensaver".into()];
+  if let Request::Preview(window) = request {
+    arguments.extend(["--preview".into(), window.to_string().into()]);
+  }
+  arguments
+}
+
+// there's nothing to set up besides the horus.toml of the project the screensaver was bundled from
+pub fn configure() {
+  let message = "This screensaver doesn't have any settings. Change the horus.toml of the project it was bundled from and bundle it again instead.";
+  #[cfg(target_os = "windows")]
+  win32::message(message);
+  #[cfg(not(target_os = "windows"))]
+  println!("[Horus] {}", message);
+}
+
+// a borderless window filling the preview in Windows' screensaver settings
+pub fn preview(builder: WindowBuilder, parent: isize) -> Result<WindowBuilder, String> {
+  #[cfg(target_os = "windows")]
+  {
+    use winit::platform::windows::WindowBuilderExtWindows;
+    let (width, height) = win32::client_size(parent)?;
+    Ok(builder.with_parent_window(parent).with_decorations(false).with_position(PhysicalPosition::new(0, 0)).with_inner_size(winit::dpi::PhysicalSize::new(width, height)))
+  }
+  #[cfg(not(target_os = "windows"))]
+  {
+    let _ = (builder, parent);
+    Err("Screensaver previews only work on Windows".to_string())
+  }
+}
+
+#[derive(Default)]
+pub struct Screensaver {
+  // where the cursor was when the screensaver started
+  cursor: Option<PhysicalPosition<f64>>,
+}
+
+impl Screensaver {
+  // whether `event` is someone coming back to the computer, which closes the screensaver
+  pub fn wakes(&mut self, event: &WindowEvent) -> bool {
+    match event {
+      WindowEvent::KeyboardInput { input: KeyboardInput { state: ElementState::Pressed, .. }, .. } => true,
+      WindowEvent::MouseInput { state: ElementState::Pressed, .. } | WindowEvent::MouseWheel { .. } | WindowEvent::Touch(_) => true,
+      // the first position is wherever the cursor already was as the window opened
+      WindowEvent::CursorMoved { position, .. } => match self.cursor {
+        Some(start) => (position.x - start.x).abs() + (position.y - start.y).abs() > WAKE_DISTANCE,
+        None => {
+          self.cursor = Some(*position);
+          false
+        }
+      },
+      _ => false,
+    }
+  }
+}
+
+#[cfg(target_os = "windows")]
+mod win32 {
+  #[repr(C)]
+  #[derive(Default)]
+  struct Rect {
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+  }
+
+  const MB_ICONINFORMATION: u32 = 0x40;
+
+  #[link(name = "user32")]
+  extern "system" {
+    fn GetClientRect(window: isize, rect: *mut Rect) -> i32;
+    fn MessageBoxW(window: isize, text: *const u16, caption: *const u16, kind: u32) -> i32;
+  }
+
+  fn wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain(Some(0)).collect()
+  }
+
+  pub fn client_size(window: isize) -> Result<(u32, u32), String> {
+    let mut rect = Rect::default();
+    if unsafe { GetClientRect(window, &mut rect) } == 0 {
+      return Err("Unable to find the window to preview the screensaver in".to_string());
+    }
+    Ok(((rect.right - rect.left).max(1) as u32, (rect.bottom - rect.top).max(1) as u32))
+  }
+
+  pub fn message(text: &str) {
+    unsafe { MessageBoxW(0, wide(text).as_ptr(), wide("Horus").as_ptr(), MB_ICONINFORMATION) };
+  }
+}
@@ -0,0 +1,643 @@
+use std::{borrow::Cow, collections::BTreeMap, fs::{self, read_to_string}, path::{Path, PathBuf}, process::Command};
+use naga::{
+  front::{glsl, spv, wgsl},
+  valid::{Capabilities, ValidationFlags, Validator},
+  Span,
+};
+use wgpu::{Device, ErrorFilter, FragmentState, MultisampleState, PipelineLayout, PrimitiveState, RenderPipeline, RenderPipelineDescriptor, ShaderModule, ShaderModuleDescriptor, ShaderSource, TextureFormat, TextureViewDimension, VertexState};
+
+use crate::{module::{self, Import}, params::{self, Param}, shadertoy};
+
+// a line starting with this pastes in another file
+const INCLUDE: &str = "#include";
+
+// languages that a fragment shader can be written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderLanguage {
+  Wgsl,
+  Glsl,
+  // GLSL that only defines shadertoy's `mainImage`
+  Shadertoy,
+  // GLSL that only defines shadertoy's `mainSound`
+  ShadertoySound,
+  // GLSL that only defines shadertoy's `mainCubemap`
+  ShadertoyCubemap,
+  // shadertoy images that define `mainVR` too, which it's called in place of with --projection
+  ShadertoyVr,
+  // compiled ahead of time by glslc, dxc or anything else, which nothing can be pasted into
+  SpirV,
+  // compiled into SPIR-V by DXC, since naga can't read HLSL
+  Hlsl,
+}
+
+impl ShaderLanguage {
+  // guess the language from the file extension, falling back to WGSL
+  pub fn from_path(path: &Path) -> Self {
+    match path.extension().and_then(|extension| extension.to_str()) {
+      Some("frag" | "glsl" | "fs") => ShaderLanguage::Glsl,
+      Some("spv") => ShaderLanguage::SpirV,
+      Some("hlsl") => ShaderLanguage::Hlsl,
+      _ => ShaderLanguage::Wgsl,
+    }
+  }
+
+  // GLSL has no named entry points, naga always calls it `main`, and SPIR-V's is whatever its compiler called it
+  fn entry_point(self) -> Option<&'static str> {
+    match self {
+      ShaderLanguage::Wgsl => Some("fs_main"),
+      ShaderLanguage::Glsl | ShaderLanguage::Shadertoy | ShaderLanguage::ShadertoySound | ShaderLanguage::ShadertoyCubemap | ShaderLanguage::ShadertoyVr | ShaderLanguage::Hlsl => Some("main"),
+      ShaderLanguage::SpirV => None,
+    }
+  }
+}
+
+// a constant that --define declares before the shader's code, so one shader can build several variants of itself
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Define {
+  pub name: String,
+  // a name by itself is true in WGSL and 1 in GLSL, so it works with `if` and `#ifdef` alike
+  pub value: Option<String>,
+}
+
+impl Define {
+  pub fn new(name: &str, value: Option<String>) -> Result<Self, String> {
+    let is_identifier = name.chars().next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_') && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_identifier {
+      return Err(format!("{:?} can't be used as a constant's name", name));
+    }
+    if value.as_ref().is_some_and(|value| value.trim().is_empty()) {
+      return Err(format!("{} needs a value after the =, or nothing at all to be true", name));
+    }
+    Ok(Define { name: name.to_string(), value })
+  }
+
+  // naga declares constants with `let` outside of functions
+  fn wgsl(&self) -> String {
+    format!("let {} = {};\n", self.name, self.value.as_deref().unwrap_or("true"))
+  }
+
+  // HLSL has the same preprocessor as GLSL
+  fn glsl(&self) -> String {
+    format!("#define {} {}\n", self.name, self.value.as_deref().unwrap_or("1"))
+  }
+}
+
+// a fragment shader as written by the user, along with what it needs to know to compile
+pub struct FragmentShader<'a> {
+  // the name shown in error messages
+  pub name: &'a str,
+  // where includes are found relative to, if it came from a file
+  pub path: Option<&'a Path>,
+  pub source: &'a str,
+  // what SPIR-V is made of instead of a source
+  pub spirv: &'a [u8],
+  pub language: ShaderLanguage,
+  // whether each channel is bound as a 2D texture or a cubemap
+  pub channels: &'a [TextureViewDimension],
+  // the path and source of the common file that is shared by every pass
+  pub common: Option<(&'a Path, &'a str)>,
+  // the extra uniforms declared next to the image's shader
+  pub params: &'a [Param],
+  pub defines: &'a [Define],
+  // where WGSL modules are found besides the shader's own directory
+  pub modules: &'a [PathBuf],
+  // the function to start at instead of the language's usual one
+  pub entry: Option<&'a str>,
+  // whether a shadertoy image is run once per sample instead of once per pixel, which it is with --msaa
+  pub per_sample: bool,
+}
+
+impl FragmentShader<'_> {
+  // the name of the function the shader starts at, or `None` for SPIR-V's first fragment shader
+  fn entry_point(&self) -> Option<&str> {
+    self.entry.or(self.language.entry_point())
+  }
+
+  // the complete source that gets compiled, which may include code from Horus, the common file and the files they include
+  fn prepare(&self) -> Result<SourceMap, String> {
+    let mut prepared = SourceMap::default();
+    let wgsl = (params::wgsl(self.params), self.defines.iter().map(Define::wgsl).collect::<String>());
+    let glsl = (params::glsl(self.params), self.defines.iter().map(Define::glsl).collect::<String>());
+    let hlsl = (params::hlsl(self.params), glsl.1.clone());
+    // the defines and extra uniforms are declared before the common file so it can use them too
+    let common = |prepared: &mut SourceMap, (params, defines): &(Option<String>, String)| {
+      if !defines.is_empty() {
+        prepared.push(None, 1, defines);
+      }
+      if let Some(params) = params {
+        prepared.push(None, 1, params);
+      }
+      match self.common {
+        Some((path, source)) => prepared.push_file(&path.display().to_string(), Some(path), 1, source),
+        None => Ok(()),
+      }
+    };
+    match self.language {
+      ShaderLanguage::Wgsl => {
+        prepared.modules = Some(module::find(Some(&directory_of(self.path)), self.modules));
+        common(&mut prepared, &wgsl)?;
+        prepared.push_file(self.name, self.path, 1, self.source)?;
+      }
+      // `#version` has to come first in GLSL
+      ShaderLanguage::Glsl => match self.source.split_once('\n').filter(|(first, _)| first.trim_start().starts_with("#version")) {
+        Some((version, rest)) => {
+          prepared.push(Some(self.name), 1, version);
+          common(&mut prepared, &glsl)?;
+          prepared.push_file(self.name, self.path, 2, rest)?;
+        }
+        None => {
+          common(&mut prepared, &glsl)?;
+          prepared.push_file(self.name, self.path, 1, self.source)?;
+        }
+      },
+      ShaderLanguage::Hlsl => {
+        common(&mut prepared, &hlsl)?;
+        prepared.push_file(self.name, self.path, 1, self.source)?;
+      }
+      ShaderLanguage::SpirV => {}
+      ShaderLanguage::Shadertoy | ShaderLanguage::ShadertoySound | ShaderLanguage::ShadertoyCubemap | ShaderLanguage::ShadertoyVr => {
+        prepared.push(None, 1, &shadertoy::header(self.channels));
+        common(&mut prepared, &glsl)?;
+        prepared.push_file(self.name, self.path, 1, self.source)?;
+        if matches!(self.language, ShaderLanguage::ShadertoyCubemap | ShaderLanguage::ShadertoyVr) {
+          prepared.push(None, 1, shadertoy::CUBE_DIRECTION);
+        }
+        let footer = match self.language {
+          ShaderLanguage::ShadertoySound => shadertoy::SOUND_FOOTER,
+          ShaderLanguage::ShadertoyCubemap => shadertoy::CUBEMAP_FOOTER,
+          ShaderLanguage::ShadertoyVr => shadertoy::VR_FOOTER,
+          _ => shadertoy::FOOTER,
+        };
+        match self.per_sample {
+          true => prepared.push(None, 1, &shadertoy::per_sample(footer)),
+          false => prepared.push(None, 1, footer),
+        }
+      }
+    }
+    Ok(prepared)
+  }
+}
+
+// a WGSL vertex shader that replaces the triangle covering the whole target, so the image can be drawn with geometry of its own
+pub struct VertexShader<'a> {
+  pub name: &'a str,
+  pub path: Option<&'a Path>,
+  pub source: &'a str,
+  pub params: &'a [Param],
+  pub defines: &'a [Define],
+  pub modules: &'a [PathBuf],
+}
+
+impl VertexShader<'_> {
+  // the same constants and custom uniforms are declared in front of it as in front of the image, but not the common file
+  fn prepare(&self) -> Result<SourceMap, String> {
+    let mut prepared = SourceMap { modules: Some(module::find(Some(&directory_of(self.path)), self.modules)), ..Default::default() };
+    let defines: String = self.defines.iter().map(Define::wgsl).collect();
+    if !defines.is_empty() {
+      prepared.push(None, 1, &defines);
+    }
+    if let Some(params) = params::wgsl(self.params) {
+      prepared.push(None, 1, &params);
+    }
+    prepared.push_file(self.name, self.path, 1, self.source)?;
+    Ok(prepared)
+  }
+}
+
+// source stitched together from several pieces, remembering where each line came from
+#[derive(Default)]
+struct SourceMap {
+  source: String,
+  // the file each piece came from, or `None` for lines Horus inserted, the line it started at in that file and how many lines it has
+  pieces: Vec<(Option<String>, usize, usize)>,
+  // every file that's been pasted in, which aren't pasted in again
+  included: Vec<PathBuf>,
+  // the files being pasted in right now, each included by the one before it
+  including: Vec<PathBuf>,
+  // the WGSL modules that can be imported by their import paths, or `None` if the language doesn't have modules
+  modules: Option<BTreeMap<String, PathBuf>>,
+}
+
+impl SourceMap {
+  fn push(&mut self, name: Option<&str>, first_line: usize, text: &str) {
+    self.source.push_str(text);
+    if !text.ends_with('\n') {
+      self.source.push('\n');
+    }
+    let lines = text.lines().count().max(1);
+    self.pieces.push((name.map(str::to_string), first_line, lines));
+  }
+
+  // push the source of the file called `name`, starting from `first_line`, with each `#include "path"` line replaced by the file at
+  // that path relative to it, which only happens the first time the file is included so libraries can include each other,
+  // and each `#import` line replaced by the module it names the same way
+  fn push_file(&mut self, name: &str, path: Option<&Path>, first_line: usize, source: &str) -> Result<(), String> {
+    let canonical = path.map(|path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+    if let Some(canonical) = &canonical {
+      if !self.included.contains(canonical) {
+        self.included.push(canonical.clone());
+      }
+      self.including.push(canonical.clone());
+    }
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let (imported, rewritten) = self.resolve_imports(name, first_line, &lines)?;
+    let rewritten: Vec<&str> = rewritten.split_inclusive('\n').collect();
+    let mut start = 0;
+    for (index, line) in lines.iter().enumerate() {
+      let location = format!("{}:{}", name, first_line + index);
+      let line = line.trim_start();
+      if self.modules.is_some() && line.starts_with(module::DEFINE_IMPORT_PATH) {
+        if start < index {
+          self.push(Some(name), first_line + start, &rewritten[start..index].concat());
+        }
+        start = index + 1;
+        continue;
+      }
+      if let Some(module) = imported.iter().find(|(line, _)| *line == index).map(|(_, module)| module.clone()) {
+        if start < index {
+          self.push(Some(name), first_line + start, &rewritten[start..index].concat());
+        }
+        start = index + 1;
+        let canonical = module.canonicalize().unwrap_or_else(|_| module.clone());
+        self.paste(&module, canonical, "import", &location)?;
+        continue;
+      }
+      let Some(include) = line.strip_prefix(INCLUDE) else {
+        continue;
+      };
+      let include = include.trim().strip_prefix('"').and_then(|include| include.strip_suffix('"')).ok_or(format!("error: #include takes a path in quotes, like #include \"lib/sdf.wgsl\"\n  --> {}", location))?;
+      if start < index {
+        self.push(Some(name), first_line + start, &rewritten[start..index].concat());
+      }
+      start = index + 1;
+
+      let included = path.and_then(Path::parent).unwrap_or(Path::new("")).join(include);
+      let canonical = included.canonicalize().map_err(|error| format!("error: unable to include {}: {}\n  --> {}", included.display(), error, location))?;
+      self.paste(&included, canonical, "include", &location)?;
+    }
+    if start < lines.len() || lines.is_empty() {
+      self.push(Some(name), first_line + start, &rewritten[start..].concat());
+    }
+    if canonical.is_some() {
+      self.including.pop();
+    }
+    Ok(())
+  }
+
+  // paste in the file at `path` because of the line at `location`, unless it already has been
+  fn paste(&mut self, path: &Path, canonical: PathBuf, verb: &str, location: &str) -> Result<(), String> {
+    if self.including.contains(&canonical) {
+      let chain: Vec<String> = self.including.iter().chain([&canonical]).map(|path| path.display().to_string()).collect();
+      return Err(format!("error: {} {}s itself through {}\n  --> {}", path.display(), verb, chain.join(" -> "), location));
+    }
+    if self.included.contains(&canonical) {
+      return Ok(());
+    }
+    let source = read_to_string(&canonical).map_err(|error| format!("error: unable to {} {}: {}\n  --> {}", verb, path.display(), error, location))?;
+    self.push_file(&path.display().to_string(), Some(path), 1, &source)
+  }
+
+  // the module each `#import` line in `lines` names, by the index of the line, along with the lines once everything from those modules
+  // is renamed to what it's pasted in as, and everything the file declares is too if it's a module itself
+  fn resolve_imports(&self, name: &str, first_line: usize, lines: &[&str]) -> Result<(Vec<(usize, PathBuf)>, String), String> {
+    let Some(modules) = &self.modules else {
+      return Ok((Vec::new(), lines.concat()));
+    };
+    let location = |index: usize| format!("{}:{}", name, first_line + index);
+    let (mut imported, mut imports, mut own) = (Vec::new(), Vec::new(), None);
+    // the directives are left out of what's renamed, but still take up their lines
+    let mut code = String::new();
+    for (index, line) in lines.iter().enumerate() {
+      let directive = line.trim_start();
+      if let Some(path) = directive.strip_prefix(module::DEFINE_IMPORT_PATH).map(str::trim) {
+        if !module::is_path(path) {
+          return Err(format!("error: #define_import_path takes names separated by ::, like #define_import_path sdf::shapes\n  --> {}", location(index)));
+        }
+        own = Some(path.to_string());
+      } else if let Some(conditional) = module::CONDITIONALS.iter().find(|conditional| directive.split_whitespace().next() == Some(**conditional)) {
+        return Err(format!("error: {} isn't supported in WGSL, since Horus' modules only have #define_import_path and #import\n  --> {}", conditional, location(index)));
+      } else if let Some(rest) = directive.strip_prefix(module::IMPORT) {
+        let import = Import::parse(rest).map_err(|error| format!("error: {}\n  --> {}", error, location(index)))?;
+        let path = modules.get(&import.path).ok_or(format!("error: there's no module called {}, which is a .wgsl file starting with #define_import_path {} next to the shader or in a --modules directory\n  --> {}", import.path, import.path, location(index)))?;
+        let source = read_to_string(path).map_err(|error| format!("error: unable to import {}: {}\n  --> {}", path.display(), error, location(index)))?;
+        imported.push((index, path.clone()));
+        imports.push((import, module::declarations(&source)));
+      } else {
+        code.push_str(line.trim_end_matches('\n'));
+      }
+      code.push('\n');
+    }
+    let own = own.map(|path| (module::declarations(&code), path));
+    let rewritten = module::rewrite(&code, &imports, own.as_ref().map(|(names, path)| (path.as_str(), names.as_slice())))
+      .map_err(|(index, error)| format!("error: {}\n  --> {}", error, location(index)))?;
+    Ok((imported, rewritten))
+  }
+
+  // the file and line that a line of the stitched source came from, or `None` if Horus inserted it
+  fn locate(&self, line: usize) -> Option<(&str, usize)> {
+    let mut start = 1;
+    for (name, first_line, lines) in &self.pieces {
+      if line < start + lines {
+        return name.as_deref().map(|name| (name, first_line + line - start));
+      }
+      start += lines;
+    }
+    None
+  }
+}
+
+// every file that `path`, whose contents are `source`, includes or imports from `modules`, along with the files they include in turn
+pub fn includes(path: &Path, source: &str, modules: &[PathBuf]) -> Vec<PathBuf> {
+  let mut prepared = SourceMap::default();
+  if ShaderLanguage::from_path(path) == ShaderLanguage::Wgsl {
+    prepared.modules = Some(module::find(Some(&directory_of(Some(path))), modules));
+  }
+  // whatever was included before an error still counts
+  let _ = prepared.push_file("", Some(path), 1, source);
+  prepared.included.into_iter().skip(1).collect()
+}
+
+// the directory a shader at `path` is in, which is the current one for a bare filename or a shader that didn't come from a file
+fn directory_of(path: Option<&Path>) -> PathBuf {
+  match path.and_then(Path::parent) {
+    Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+    _ => PathBuf::from("."),
+  }
+}
+
+// a compilation error along with the parts of the source it refers to
+struct Diagnostic {
+  message: String,
+  labels: Vec<(Span, String)>,
+}
+
+impl Diagnostic {
+  // render the error like rustc does, pointing at the file the user wrote and hiding any lines that Horus inserted
+  fn format(&self, prepared: &SourceMap) -> String {
+    let mut output = format!("error: {}", self.message);
+    for (span, label) in self.labels.iter().filter(|(span, _)| span.is_defined()) {
+      let location = span.location(&prepared.source);
+      let Some((name, line_number)) = prepared.locate(location.line_number as usize) else {
+        continue;
+      };
+      let line = prepared.source.lines().nth(location.line_number as usize - 1).unwrap_or_default();
+      let gutter = " ".repeat(line_number.to_string().len());
+      let column = location.line_position as usize;
+      let width = line.chars().count().saturating_sub(column - 1).min(location.length as usize).max(1);
+      output.push_str(&format!("\n{}--> {}:{}:{}", gutter, name, line_number, column));
+      output.push_str(&format!("\n{} |", gutter));
+      output.push_str(&format!("\n{} | {}", line_number, line));
+      output.push_str(&format!("\n{} | {}{} {}", gutter, " ".repeat(column - 1), "^".repeat(width), label));
+    }
+    output
+  }
+}
+
+// parse and validate with naga, which knows where in the source each error comes from, returning the name of the fragment shader
+fn validate(fragment: &FragmentShader, prepared: &SourceMap, spirv: &[u8]) -> Result<String, String> {
+  let language = fragment.language;
+  if fragment.entry.is_some() && matches!(language, ShaderLanguage::Glsl | ShaderLanguage::Shadertoy | ShaderLanguage::ShadertoySound | ShaderLanguage::ShadertoyCubemap | ShaderLanguage::ShadertoyVr) {
+    return Err("error: GLSL always starts at main, so --entry only works with WGSL, HLSL and SPIR-V".to_string());
+  }
+  let module = match language {
+    ShaderLanguage::Wgsl => parse_wgsl(prepared)?,
+    ShaderLanguage::Glsl | ShaderLanguage::Shadertoy | ShaderLanguage::ShadertoySound | ShaderLanguage::ShadertoyCubemap | ShaderLanguage::ShadertoyVr => {
+      glsl::Parser::default().parse(&glsl::Options::from(naga::ShaderStage::Fragment), &prepared.source).map_err(|errors| Diagnostic {
+        message: errors.iter().map(|error| error.kind.to_string()).collect::<Vec<_>>().join("\n"),
+        labels: errors.iter().map(|error| (error.meta, error.kind.to_string())).collect(),
+      }.format(prepared))?
+    }
+    ShaderLanguage::SpirV | ShaderLanguage::Hlsl => parse_spirv(fragment.name, spirv)?,
+  };
+  let fragments: Vec<&str> = module.entry_points.iter().filter(|entry| entry.stage == naga::ShaderStage::Fragment).map(|entry| entry.name.as_str()).collect();
+  let entry_point = fragments.iter().find(|entry| fragment.entry_point().is_none_or(|name| **entry == name));
+  let Some(entry_point) = entry_point else {
+    return Err(match (fragment.entry_point(), fragments.as_slice()) {
+      (Some(name), []) => format!("error: there's no fragment shader called `{}`", name),
+      (Some(name), others) => format!("error: there's no fragment shader called `{}`, only `{}`", name, others.join("`, `")),
+      (None, _) => format!("error: {} doesn't have a fragment shader in it", fragment.name),
+    });
+  };
+  validate_module(&module, prepared)?;
+  Ok(entry_point.to_string())
+}
+
+// parse and validate the vertex shader with naga, which has to be called `vs_main`
+fn validate_vertex(prepared: &SourceMap) -> Result<(), String> {
+  let module = parse_wgsl(prepared)?;
+  if !module.entry_points.iter().any(|entry| entry.stage == naga::ShaderStage::Vertex && entry.name == "vs_main") {
+    return Err("error: there's no vertex shader called `vs_main`".to_string());
+  }
+  validate_module(&module, prepared)
+}
+
+fn parse_wgsl(prepared: &SourceMap) -> Result<naga::Module, String> {
+  wgsl::parse_str(&prepared.source).map_err(|error| Diagnostic {
+    message: error.message().to_string(),
+    labels: error.labels().map(|(span, label)| (span.into(), label.to_string())).collect(),
+  }.format(prepared))
+}
+
+fn validate_module(module: &naga::Module, prepared: &SourceMap) -> Result<(), String> {
+  Validator::new(ValidationFlags::all(), Capabilities::all()).validate(module).map(|_| ()).map_err(|error| Diagnostic {
+    message: describe(&error),
+    labels: error.spans().cloned().collect(),
+  }.format(prepared))
+}
+
+// parse SPIR-V the same way wgpu reads it
+fn parse_spirv(name: &str, spirv: &[u8]) -> Result<naga::Module, String> {
+  if !spirv.len().is_multiple_of(4) {
+    return Err(format!("error: {} isn't SPIR-V, which is made of 4 byte words", name));
+  }
+  let options = spv::Options { adjust_coordinate_space: false, strict_capabilities: true, block_ctx_dump_prefix: None };
+  spv::parse_u8_slice(spirv, &options).map_err(|error| format!("error: {} isn't valid SPIR-V: {}", name, error))
+}
+
+// what a shader that's made of SPIR-V is made of, which HLSL is compiled into first
+fn spirv<'a>(fragment: &FragmentShader<'a>, prepared: &SourceMap) -> Result<Cow<'a, [u8]>, String> {
+  match fragment.language {
+    ShaderLanguage::Hlsl => compile_hlsl(prepared, fragment.entry_point().unwrap_or("main")).map(Cow::Owned),
+    _ => Ok(Cow::Borrowed(fragment.spirv)),
+  }
+}
+
+// compile HLSL into SPIR-V with DXC starting at `entry`, pointing its errors back at the files they came from
+fn compile_hlsl(prepared: &SourceMap, entry: &str) -> Result<Vec<u8>, String> {
+  let directory = std::env::temp_dir().join(format!("horus-hlsl-{}", std::process::id()));
+  fs::create_dir_all(&directory).map_err(|error| format!("error: unable to create {}: {}", directory.display(), error))?;
+  let (input, output) = (directory.join("shader.hlsl"), directory.join("shader.spv"));
+  fs::write(&input, &prepared.source).map_err(|error| format!("error: unable to write {}: {}", input.display(), error))?;
+  let result = Command::new("dxc").args(["-T", "ps_6_0", "-E", entry, "-spirv", "-fvk-use-gl-layout", "-Fo"]).arg(&output).arg(&input).output();
+  let compiled = fs::read(&output);
+  let _ = fs::remove_dir_all(&directory);
+  let result = result.map_err(|error| format!("error: unable to start dxc, which comes with the Vulkan SDK and compiles HLSL: {}", error))?;
+  if !result.status.success() {
+    // DXC names the temporary file and its lines, like shader.hlsl:12:5: error: ...
+    let input = input.display().to_string();
+    let errors: Vec<String> = String::from_utf8_lossy(&result.stderr).lines().map(|line| {
+      let Some((number, rest)) = line.strip_prefix(&input).and_then(|rest| rest.strip_prefix(':')).and_then(|rest| rest.split_once(':')) else {
+        return line.to_string();
+      };
+      match number.parse().ok().and_then(|number| prepared.locate(number)) {
+        Some((name, number)) => format!("{}:{}:{}", name, number, rest),
+        None => rest.trim_start_matches(|character: char| character.is_ascii_digit() || character == ':').trim_start().to_string(),
+      }
+    }).collect();
+    return Err(errors.join("\n").trim().to_string());
+  }
+  compiled.map_err(|error| format!("error: unable to read what dxc compiled: {}", error))
+}
+
+// the interesting detail is usually at the bottom of the chain
+fn describe(error: &dyn std::error::Error) -> String {
+  let mut message = error.to_string();
+  let mut cause = error.source();
+  while let Some(inner) = cause {
+    message.push_str(&format!(": {}", inner));
+    cause = inner.source();
+  }
+  message
+}
+
+// parse and validate the fragment shader with naga alone, which catches everything but what only wgpu can know about, like mismatched bindings
+pub fn check(fragment: &FragmentShader) -> Result<(), String> {
+  let prepared = fragment.prepare()?;
+  validate(fragment, &prepared, &spirv(fragment, &prepared)?).map(|_| ())
+}
+
+// parse and validate the vertex shader with naga alone
+pub fn check_vertex(vertex: &VertexShader) -> Result<(), String> {
+  validate_vertex(&vertex.prepare()?)
+}
+
+// compile the vertex shader the image is drawn with instead of the triangle covering the whole target
+pub fn create_vertex_shader(device: &Device, vertex: &VertexShader) -> Result<ShaderModule, String> {
+  let prepared = vertex.prepare()?;
+  validate_vertex(&prepared)?;
+  device.push_error_scope(ErrorFilter::Validation);
+  let module = device.create_shader_module(ShaderModuleDescriptor {
+    label: None,
+    source: ShaderSource::Wgsl(Cow::Borrowed(&prepared.source)),
+  });
+  match pollster::block_on(device.pop_error_scope()) {
+    Some(error) => Err(format!("error: {}", error)),
+    None => Ok(module),
+  }
+}
+
+// compile the fragment shader and build a pipeline that renders it across the whole surface
+pub fn create_render_pipeline(device: &Device, layout: &PipelineLayout, vertex_shader: &ShaderModule, fragment: &FragmentShader, format: TextureFormat, samples: u32) -> Result<RenderPipeline, String> {
+  let language = fragment.language;
+  let prepared = fragment.prepare()?;
+  let fragment_source = &prepared.source;
+  let spirv = spirv(fragment, &prepared)?;
+  let entry_point = validate(fragment, &prepared, &spirv)?;
+
+  let source = match language {
+    ShaderLanguage::Wgsl => ShaderSource::Wgsl(Cow::Borrowed(fragment_source)),
+    ShaderLanguage::Glsl | ShaderLanguage::Shadertoy | ShaderLanguage::ShadertoySound | ShaderLanguage::ShadertoyCubemap | ShaderLanguage::ShadertoyVr => ShaderSource::Glsl {
+      shader: Cow::Borrowed(fragment_source),
+      stage: naga::ShaderStage::Fragment,
+      defines: Default::default(),
+    },
+    // SPIR-V is little endian, which parse_spirv already checked is made of whole words
+    ShaderLanguage::SpirV | ShaderLanguage::Hlsl => ShaderSource::SpirV(spirv.chunks_exact(4).map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]])).collect()),
+  };
+
+  // anything naga didn't catch, such as mismatched uniforms, is reported by wgpu
+  device.push_error_scope(ErrorFilter::Validation);
+  let fragment_shader = device.create_shader_module(ShaderModuleDescriptor {
+    label: None,
+    source,
+  });
+  let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+    label: None,
+    layout: Some(layout),
+    vertex: VertexState {
+      module: vertex_shader,
+      entry_point: "vs_main",
+      buffers: &[],
+    },
+    fragment: Some(FragmentState {
+      module: &fragment_shader,
+      entry_point: &entry_point,
+      targets: &[Some(format.into())],
+    }),
+    primitive: PrimitiveState::default(),
+    depth_stencil: None,
+    multisample: MultisampleState {
+      count: samples,
+      ..Default::default()
+    },
+    multiview: None,
+  });
+  match pollster::block_on(device.pop_error_scope()) {
+    Some(error) => Err(format!("error: {}", error)),
+    None => Ok(render_pipeline),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // a directory of its own for each test, holding `files`
+  fn directory(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("horus-test-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    for (name, source) in files {
+      fs::write(directory.join(name), source).unwrap();
+    }
+    directory
+  }
+
+  #[test]
+  fn locates_lines_across_pieces() {
+    let mut prepared = SourceMap::default();
+    prepared.push(None, 1, "inserted\n");
+    prepared.push(Some("image.glsl"), 1, "one\ntwo\n");
+    prepared.push(Some("image.glsl"), 4, "four");
+    assert_eq!(prepared.locate(1), None);
+    assert_eq!(prepared.locate(2), Some(("image.glsl", 1)));
+    assert_eq!(prepared.locate(3), Some(("image.glsl", 2)));
+    assert_eq!(prepared.locate(4), Some(("image.glsl", 4)));
+    assert_eq!(prepared.locate(5), None);
+  }
+
+  #[test]
+  fn locates_lines_after_an_include() {
+    let directory = directory("locate", &[("lib.glsl", "float a;\nfloat b;\n")]);
+    let path = directory.join("image.glsl");
+    let mut prepared = SourceMap::default();
+    prepared.push_file("image.glsl", Some(&path), 1, "first\n#include \"lib.glsl\"\nthird\n").unwrap();
+    let lib = directory.join("lib.glsl").display().to_string();
+    assert_eq!(prepared.locate(1), Some(("image.glsl", 1)));
+    assert_eq!(prepared.locate(2), Some((lib.as_str(), 1)));
+    assert_eq!(prepared.locate(3), Some((lib.as_str(), 2)));
+    assert_eq!(prepared.locate(4), Some(("image.glsl", 3)));
+    fs::remove_dir_all(directory).unwrap();
+  }
+
+  #[test]
+  fn detects_include_cycles() {
+    let directory = directory("cycle", &[("a.glsl", "#include \"b.glsl\"\n"), ("b.glsl", "#include \"a.glsl\"\n")]);
+    let path = directory.join("a.glsl");
+    let error = SourceMap::default().push_file("a.glsl", Some(&path), 1, "#include \"b.glsl\"\n").unwrap_err();
+    assert!(error.contains("includes itself through"), "{}", error);
+    // the files in the cycle are still watched, so fixing either one recompiles the shader
+    assert_eq!(includes(&path, "#include \"b.glsl\"\n", &[]), vec![directory.join("b.glsl").canonicalize().unwrap()]);
+    fs::remove_dir_all(directory).unwrap();
+  }
+
+  #[test]
+  fn includes_each_file_once() {
+    let directory = directory("once", &[("a.glsl", "#include \"c.glsl\"\n"), ("b.glsl", "#include \"c.glsl\"\n"), ("c.glsl", "float c;\n")]);
+    let path = directory.join("image.glsl");
+    let mut prepared = SourceMap::default();
+    prepared.push_file("image.glsl", Some(&path), 1, "#include \"a.glsl\"\n#include \"b.glsl\"\n").unwrap();
+    assert_eq!(prepared.source.matches("float c;").count(), 1);
+    fs::remove_dir_all(directory).unwrap();
+  }
+}
@@ -0,0 +1,183 @@
+// Shadertoy shaders only define `mainImage`, or `mainSound` and `mainCubemap` in their other tabs, so they are wrapped in a small GLSL harness
+// that declares Horus' uniforms and channels under Shadertoy's names and calls them from `main`, along with `mainVR` when there is one
+
+use wgpu::TextureViewDimension;
+
+const UNIFORMS: &str = "\
+#version 450
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    vec2 horus_mouse;
+    vec2 horus_resolution;
+    float horus_time;
+    float horus_time_delta;
+    int horus_frame;
+    float horus_sample_rate;
+    vec4 horus_mouse_click;
+    vec4 horus_date;
+    vec2 horus_offset;
+    float horus_beat;
+    float horus_bpm;
+    vec4 horus_font;
+    float horus_seed;
+    vec2 horus_scroll;
+};
+
+layout(set = 0, binding = 1) uniform Touches {
+    vec4 horus_touches[4];
+    int horus_touch_count;
+};
+
+";
+
+const DEFINES: &str = "
+// shadertoy's origin is the bottom left corner, Horus' is the top left
+// iMouse is flipped to match, keeping the signs that say whether the button is held, and stays zero until the first click
+vec4 horus_imouse() {
+    if (horus_mouse_click == vec4(0.0)) {
+        return vec4(0.0);
+    }
+    float y = horus_resolution.y - horus_mouse_click.y;
+    float w = sign(horus_mouse_click.w) * (horus_resolution.y - abs(horus_mouse_click.w));
+    return vec4(horus_mouse_click.x, y, horus_mouse_click.z, w);
+}
+
+// the fingers on a touchscreen are flipped the same way, and any past the ones that are down are zero
+vec4 horus_itouch(int index) {
+    if (index < 0 || index >= horus_touch_count) {
+        return vec4(0.0);
+    }
+    vec4 touch = horus_touches[index];
+    return vec4(touch.x, horus_resolution.y - touch.y, touch.z, horus_resolution.y - touch.w);
+}
+
+#define iTime horus_time
+#define iTimeDelta horus_time_delta
+#define iFrame horus_frame
+#define iSampleRate horus_sample_rate
+#define iDate horus_date
+#define iResolution vec3(horus_resolution, 1.0)
+#define iMouse horus_imouse()
+#define iBeat horus_beat
+#define iBpm horus_bpm
+#define iFont horus_font
+#define iSeed horus_seed
+#define iScroll horus_scroll
+#define iTouch(index) horus_itouch(index)
+#define iTouchCount horus_touch_count
+
+layout(location = 0) out vec4 horus_color;
+
+";
+
+pub const FOOTER: &str = "
+
+void main() {
+    vec2 position = gl_FragCoord.xy + horus_offset;
+    vec2 fragCoord = vec2(position.x, horus_resolution.y - position.y);
+    mainImage(horus_color, fragCoord);
+}
+";
+
+// with --msaa the image reads which sample it's running for, which makes it run once per sample with gl_FragCoord at each of them, so its
+// edges are smoothed without the shader doing anything itself. It's only read then, since not every GL backend has gl_SampleID
+pub fn per_sample(footer: &str) -> String {
+  footer.replacen("void main() {\n", "void main() {\n    float horus_sample_index = float(gl_SampleID);\n", 1)
+}
+
+// a sound shader is rendered 512 samples to a row, with each pixel's left and right samples in red and green
+pub const SOUND_FOOTER: &str = "
+
+void main() {
+    int horus_sample = int(gl_FragCoord.y + horus_offset.y) * 512 + int(gl_FragCoord.x);
+    horus_color = vec4(mainSound(horus_sample, float(horus_sample) / horus_sample_rate), 0.0, 1.0);
+}
+";
+
+// a cubemap is rendered a face at a time as if they were laid side by side, so the face is how many faces across the offset is,
+// and each pixel's ray points from the center through it in the direction that samples it back
+pub const CUBE_DIRECTION: &str = "
+
+vec3 horus_cube_direction() {
+    int face = int(horus_offset.x / horus_resolution.x + 0.5);
+    vec2 uv = gl_FragCoord.xy / horus_resolution * 2.0 - 1.0;
+    vec3 direction = vec3(-uv.x, -uv.y, -1.0);
+    if (face == 0) {
+        direction = vec3(1.0, -uv.y, -uv.x);
+    } else if (face == 1) {
+        direction = vec3(-1.0, -uv.y, uv.x);
+    } else if (face == 2) {
+        direction = vec3(uv.x, 1.0, uv.y);
+    } else if (face == 3) {
+        direction = vec3(uv.x, -1.0, -uv.y);
+    } else if (face == 4) {
+        direction = vec3(uv.x, -uv.y, 1.0);
+    }
+    return normalize(direction);
+}
+";
+
+pub const CUBEMAP_FOOTER: &str = "
+void main() {
+    vec2 fragCoord = vec2(gl_FragCoord.x, horus_resolution.y - gl_FragCoord.y);
+    mainCubemap(horus_color, fragCoord, vec3(0.0), horus_cube_direction());
+}
+";
+
+// with --projection the image is rendered into a cubemap too, which `mainVR` is given the viewer's rays through
+pub const VR_FOOTER: &str = "
+void main() {
+    vec2 fragCoord = vec2(gl_FragCoord.x, horus_resolution.y - gl_FragCoord.y);
+    mainVR(horus_color, fragCoord, vec3(0.0), horus_cube_direction());
+}
+";
+
+// shadertoy code defines `mainImage` but never `main`
+pub fn is_shadertoy(source: &str) -> bool {
+  source.contains("mainImage") && !defines_main(source)
+}
+
+// shadertoy's sound tab defines `mainSound` instead
+pub fn is_sound(source: &str) -> bool {
+  source.contains("mainSound") && !defines_main(source)
+}
+
+// and its cubemap tab defines `mainCubemap`
+pub fn is_cubemap(source: &str) -> bool {
+  source.contains("mainCubemap") && !defines_main(source)
+}
+
+// and images made for VR define `mainVR` next to `mainImage`
+pub fn is_vr(source: &str) -> bool {
+  source.contains("mainVR") && !defines_main(source)
+}
+
+fn defines_main(source: &str) -> bool {
+  source.match_indices("void main").any(|(index, name)| source[index + name.len()..].trim_start().starts_with('('))
+}
+
+// everything that comes before the user's code, and the common file if there is one,, with each channel declared as a 2D texture, a
+// cubemap or a volume
+pub fn header(channels: &[TextureViewDimension]) -> String {
+  let mut header = UNIFORMS.to_string();
+  for (index, dimension) in channels.iter().enumerate() {
+    let texture = match dimension {
+      TextureViewDimension::Cube => "textureCube",
+      TextureViewDimension::D3 => "texture3D",
+      _ => "texture2D",
+    };
+    header.push_str(&format!("layout(set = 1, binding = {}) uniform {} horus_channel{}_texture;\n", index * 2, texture, index));
+    header.push_str(&format!("layout(set = 1, binding = {}) uniform sampler horus_channel{}_sampler;\n", index * 2 + 1, index));
+  }
+  header.push('\n');
+  for (index, dimension) in channels.iter().enumerate() {
+    let sampler = match dimension {
+      TextureViewDimension::Cube => "samplerCube",
+      TextureViewDimension::D3 => "sampler3D",
+      _ => "sampler2D",
+    };
+    header.push_str(&format!("#define iChannel{0} {1}(horus_channel{0}_texture, horus_channel{0}_sampler)\n", index, sampler));
+  }
+  header.push_str(DEFINES);
+  header
+}
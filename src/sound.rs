@@ -0,0 +1,102 @@
+// shadertoy's sound tab: a pass that works out the left and right samples for any moment, which is rendered 512 samples to a row
+// into a float texture up front and read back to be played like an audio file
+//
+// GLSL sound shaders define `vec2 mainSound(int samp, float time)`, and WGSL ones write the left and right samples into red and
+// green, where the pixel at `position.xy` holds sample `(position.y + offset.y) * 512 + position.x` at `sample_rate`
+
+use std::path::Path;
+use wgpu::{Color, CommandEncoderDescriptor, Device, Extent3d, LoadOp, Operations, Queue, RenderPassColorAttachment, RenderPassDescriptor, TextureDescriptor, TextureDimension, TextureUsages, TextureViewDescriptor};
+
+use crate::{capture::Readback, live::LiveChannels, pass::{Passes, SOUND_FORMAT}, Uniforms, UniformsBinding};
+
+// how many samples are in each row, and how many rows are rendered at a time
+const WIDTH: u32 = 512;
+const ROWS: u32 = 512;
+
+// the same length and rate shadertoy plays sound shaders at
+const DURATION: u32 = 180;
+pub const SAMPLE_RATE: u32 = 44100;
+
+// every sample the sound pass makes, interleaved left and right, or `None` if there isn't one that has compiled
+pub fn render(device: &Device, queue: &Queue, passes: &Passes) -> Option<Result<Vec<f32>, String>> {
+  let pipeline = passes.sound.as_ref()?.pipeline.as_ref()?;
+  let texture = device.create_texture(&TextureDescriptor {
+    label: Some("sound"),
+    size: Extent3d { width: WIDTH, height: ROWS, depth_or_array_layers: 1 },
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: TextureDimension::D2,
+    format: SOUND_FORMAT,
+    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+  });
+  let view = texture.create_view(&TextureViewDescriptor::default());
+  let mut uniforms = Uniforms::new(WIDTH, ROWS);
+  uniforms.sample_rate = SAMPLE_RATE as f32;
+  let binding = UniformsBinding::new(device, &uniforms);
+
+  let length = (DURATION * SAMPLE_RATE) as usize;
+  let mut samples = Vec::with_capacity(length * 2);
+  // each block is read back before the next is rendered into the same texture
+  for block in 0..(length as u32).div_ceil(WIDTH * ROWS) {
+    uniforms.offset = [0., (block * ROWS) as f32];
+    queue.write_buffer(&binding.buffer, 0, bytemuck::bytes_of(&uniforms));
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: Some("sound") });
+    {
+      let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("sound"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+          view: &view,
+          resolve_target: None,
+          ops: Operations {
+            load: LoadOp::Clear(Color::TRANSPARENT),
+            store: true,
+          },
+        })],
+        depth_stencil_attachment: None,
+      });
+      render_pass.set_pipeline(pipeline);
+      render_pass.set_bind_group(0, &binding.bind_group, &[]);
+      render_pass.set_bind_group(1, passes.image_bind_group(0), &[]);
+      render_pass.set_bind_group(2, passes.params.bind_group(), &[]);
+      render_pass.draw(0..3, 0..1);
+    }
+    let readback = match Readback::new(device, &mut encoder, &texture, WIDTH, ROWS, SOUND_FORMAT) {
+      Ok(readback) => readback,
+      Err(error) => return Some(Err(error)),
+    };
+    queue.submit(Some(encoder.finish()));
+    match readback.into_samples(device) {
+      Ok(block) => samples.extend(block),
+      Err(error) => return Some(Err(error)),
+    }
+  }
+  samples.truncate(length * 2);
+  Some(Ok(samples))
+}
+
+// render the sound pass and have `live` play it in place of an audio file, unless `audio` is a file to play instead, returning
+// whether there's new sound to play
+pub fn play(device: &Device, queue: &Queue, passes: &Passes, live: &mut LiveChannels, audio: Option<&Path>) -> bool {
+  let Some(sound) = &passes.sound else {
+    return false;
+  };
+  if let Some(audio) = audio {
+    println!("[Horus] Playing {} instead of {}", audio.display(), sound.name);
+    return false;
+  }
+  if !cfg!(feature = "audio") {
+    println!("[Horus] Unable to play {} since Horus was built without the audio feature", sound.name);
+    return false;
+  }
+  match render(device, queue, passes) {
+    Some(Ok(samples)) => {
+      live.set_sound(samples, SAMPLE_RATE);
+      true
+    }
+    Some(Err(error)) => {
+      println!("[Horus] Unable to render {}: {}", sound.name, error);
+      false
+    }
+    None => false,
+  }
+}
@@ -0,0 +1,473 @@
+// with --spout, every frame is shared as a Spout sender that Resolume, OBS, TouchDesigner and other Windows programs can receive
+//
+// a Spout sender is a Direct3D 11 texture shared between processes, which receivers find by looking its name up in a list of senders in
+// shared memory, next to another block of shared memory named after it that holds the texture's handle, size and format. wgpu can't
+// hand out its own textures, so each frame is read back the same as it is for recordings and written into the shared texture, which
+// receivers then read without it leaving the GPU. Direct3D and the kernel are loaded when Horus starts sending rather than linked,
+// the same as NDI's runtime, so there's nothing to install and Horus still runs on other platforms
+
+#[cfg(feature = "spout")]
+use std::{ffi::{c_char, c_void, CString}, mem::size_of, ptr};
+#[cfg(feature = "spout")]
+use libloading::Library;
+use image::RgbaImage;
+
+#[cfg(feature = "spout")]
+type Handle = isize;
+
+// the shared memory every Spout program reads, and the mutexes they lock it with are named after it
+#[cfg(feature = "spout")]
+const SENDER_NAMES: &str = "SpoutSenderNames";
+#[cfg(feature = "spout")]
+const ACTIVE_SENDER: &str = "ActiveSenderName";
+// every name takes up this many bytes, in the list of senders and in the active sender
+#[cfg(feature = "spout")]
+const NAME_LENGTH: usize = 256;
+// how many senders the list has room for when Horus is the first program to make it
+#[cfg(feature = "spout")]
+const MAX_SENDERS: usize = 64;
+// how long to wait for another program to be done with the shared memory or the texture before giving up on it
+#[cfg(feature = "spout")]
+const LOCK_TIMEOUT_MS: u32 = 100;
+
+#[cfg(feature = "spout")]
+const INVALID_HANDLE: Handle = -1;
+#[cfg(feature = "spout")]
+const PAGE_READWRITE: u32 = 0x4;
+#[cfg(feature = "spout")]
+const FILE_MAP_ALL_ACCESS: u32 = 0xF001F;
+#[cfg(feature = "spout")]
+const WAIT_OBJECT_0: u32 = 0;
+// a program that closed while it held the mutex doesn't stop anyone else from taking it
+#[cfg(feature = "spout")]
+const WAIT_ABANDONED: u32 = 0x80;
+
+#[cfg(feature = "spout")]
+const D3D_DRIVER_TYPE_HARDWARE: i32 = 1;
+#[cfg(feature = "spout")]
+const D3D11_SDK_VERSION: u32 = 7;
+#[cfg(feature = "spout")]
+const D3D11_BIND_SHADER_RESOURCE: u32 = 0x8;
+#[cfg(feature = "spout")]
+const D3D11_BIND_RENDER_TARGET: u32 = 0x20;
+#[cfg(feature = "spout")]
+const D3D11_RESOURCE_MISC_SHARED: u32 = 0x2;
+// DXGI_FORMAT_B8G8R8A8_UNORM, the format every version of Spout can receive
+#[cfg(feature = "spout")]
+const FORMAT_BGRA: u32 = 87;
+#[cfg(feature = "spout")]
+const IID_DXGI_RESOURCE: Guid = Guid(0x035F3AB4, 0x482E, 0x4E50, [0xB4, 0x1F, 0x8A, 0x7F, 0x8B, 0xD8, 0x96, 0x0B]);
+
+// where the methods that are called are in the vtables of ID3D11Device, ID3D11DeviceContext and IDXGIResource, counting IUnknown's
+#[cfg(feature = "spout")]
+const QUERY_INTERFACE: usize = 0;
+#[cfg(feature = "spout")]
+const RELEASE: usize = 2;
+#[cfg(feature = "spout")]
+const CREATE_TEXTURE_2D: usize = 5;
+#[cfg(feature = "spout")]
+const GET_SHARED_HANDLE: usize = 8;
+#[cfg(feature = "spout")]
+const UPDATE_SUBRESOURCE: usize = 48;
+#[cfg(feature = "spout")]
+const FLUSH: usize = 111;
+
+#[cfg(feature = "spout")]
+#[repr(C)]
+struct Guid(u32, u16, u16, [u8; 8]);
+
+#[cfg(feature = "spout")]
+#[repr(C)]
+struct TextureDesc {
+  width: u32,
+  height: u32,
+  mip_levels: u32,
+  array_size: u32,
+  format: u32,
+  sample_count: u32,
+  sample_quality: u32,
+  usage: u32,
+  bind_flags: u32,
+  cpu_access_flags: u32,
+  misc_flags: u32,
+}
+
+// what receivers read from the shared memory named after the sender
+#[cfg(feature = "spout")]
+#[repr(C)]
+struct TextureInfo {
+  share_handle: u32,
+  width: u32,
+  height: u32,
+  format: u32,
+  usage: u32,
+  description: [u8; 256],
+  partner_id: u32,
+}
+
+#[cfg(feature = "spout")]
+#[repr(C)]
+struct MemoryInfo {
+  base_address: *mut c_void,
+  allocation_base: *mut c_void,
+  allocation_protect: u32,
+  partition_id: u16,
+  region_size: usize,
+  state: u32,
+  protect: u32,
+  kind: u32,
+}
+
+// the parts of kernel32 that shared memory and mutexes are made with
+#[cfg(feature = "spout")]
+struct Kernel {
+  create_file_mapping: unsafe extern "system" fn(Handle, *const c_void, u32, u32, u32, *const c_char) -> Handle,
+  map_view: unsafe extern "system" fn(Handle, u32, u32, u32, usize) -> *mut u8,
+  unmap_view: unsafe extern "system" fn(*const u8) -> i32,
+  virtual_query: unsafe extern "system" fn(*const c_void, *mut MemoryInfo, usize) -> usize,
+  create_mutex: unsafe extern "system" fn(*const c_void, i32, *const c_char) -> Handle,
+  wait: unsafe extern "system" fn(Handle, u32) -> u32,
+  release_mutex: unsafe extern "system" fn(Handle) -> i32,
+  close_handle: unsafe extern "system" fn(Handle) -> i32,
+  // the functions above live in the library, so it's dropped last
+  _library: Library,
+}
+
+#[cfg(feature = "spout")]
+impl Kernel {
+  unsafe fn load() -> Result<Self, String> {
+    let library = Library::new("kernel32.dll").map_err(|error| error.to_string())?;
+    Ok(Kernel {
+      create_file_mapping: *library.get(b"CreateFileMappingA\0").map_err(|error| error.to_string())?,
+      map_view: *library.get(b"MapViewOfFile\0").map_err(|error| error.to_string())?,
+      unmap_view: *library.get(b"UnmapViewOfFile\0").map_err(|error| error.to_string())?,
+      virtual_query: *library.get(b"VirtualQuery\0").map_err(|error| error.to_string())?,
+      create_mutex: *library.get(b"CreateMutexA\0").map_err(|error| error.to_string())?,
+      wait: *library.get(b"WaitForSingleObject\0").map_err(|error| error.to_string())?,
+      release_mutex: *library.get(b"ReleaseMutex\0").map_err(|error| error.to_string())?,
+      close_handle: *library.get(b"CloseHandle\0").map_err(|error| error.to_string())?,
+      _library: library,
+    })
+  }
+
+  // a mutex that every program asking for `name` shares, or 0 if it can't be made
+  unsafe fn mutex(&self, name: &str) -> Handle {
+    CString::new(name).map_or(0, |name| (self.create_mutex)(ptr::null(), 0, name.as_ptr()))
+  }
+
+  // whether `mutex` was taken, which it always is if there isn't one
+  unsafe fn lock(&self, mutex: Handle) -> bool {
+    mutex == 0 || matches!((self.wait)(mutex, LOCK_TIMEOUT_MS), WAIT_OBJECT_0 | WAIT_ABANDONED)
+  }
+
+  unsafe fn unlock(&self, mutex: Handle) {
+    if mutex != 0 {
+      (self.release_mutex)(mutex);
+    }
+  }
+}
+
+// a named block of shared memory, along with the mutex Spout programs lock it with
+// it lasts for as long as any program has it open, so it's kept open for as long as the sender is
+#[cfg(feature = "spout")]
+struct SharedMemory {
+  mapping: Handle,
+  view: *mut u8,
+  size: usize,
+  mutex: Handle,
+}
+
+#[cfg(feature = "spout")]
+impl SharedMemory {
+  // open the shared memory called `name`, making it `size` bytes long if no other program has made it yet
+  unsafe fn open(kernel: &Kernel, name: &str, size: usize) -> Result<Self, String> {
+    let c_name = CString::new(name).map_err(|error| error.to_string())?;
+    let mapping = (kernel.create_file_mapping)(INVALID_HANDLE, ptr::null(), PAGE_READWRITE, 0, size as u32, c_name.as_ptr());
+    if mapping == 0 {
+      return Err(format!("unable to open the shared memory {}", name));
+    }
+    let view = (kernel.map_view)(mapping, FILE_MAP_ALL_ACCESS, 0, 0, 0);
+    if view.is_null() {
+      (kernel.close_handle)(mapping);
+      return Err(format!("unable to map the shared memory {}", name));
+    }
+    // shared memory that another program made first can be a different size to the one that was asked for
+    let mut info: MemoryInfo = std::mem::zeroed();
+    let size = match (kernel.virtual_query)(view as *const c_void, &mut info, size_of::<MemoryInfo>()) {
+      0 => size,
+      _ => info.region_size,
+    };
+    Ok(SharedMemory { mapping, view, size, mutex: kernel.mutex(&format!("{}_mutex", name)) })
+  }
+
+  unsafe fn bytes(&mut self) -> &mut [u8] {
+    std::slice::from_raw_parts_mut(self.view, self.size)
+  }
+
+  unsafe fn close(&self, kernel: &Kernel) {
+    (kernel.unmap_view)(self.view);
+    (kernel.close_handle)(self.mapping);
+    if self.mutex != 0 {
+      (kernel.close_handle)(self.mutex);
+    }
+  }
+}
+
+pub struct SpoutSender {
+  #[cfg(feature = "spout")]
+  name: String,
+  #[cfg(feature = "spout")]
+  kernel: Kernel,
+  #[cfg(feature = "spout")]
+  device: *mut c_void,
+  #[cfg(feature = "spout")]
+  context: *mut c_void,
+  // the shared texture, which is made again whenever the frames change size
+  #[cfg(feature = "spout")]
+  texture: *mut c_void,
+  #[cfg(feature = "spout")]
+  size: (u32, u32),
+  #[cfg(feature = "spout")]
+  names: SharedMemory,
+  #[cfg(feature = "spout")]
+  active: SharedMemory,
+  #[cfg(feature = "spout")]
+  info: Option<SharedMemory>,
+  // receivers hold this while they read the texture, so a frame isn't written into it halfway through
+  #[cfg(feature = "spout")]
+  access: Handle,
+  // each frame's pixels turned into BGRA
+  #[cfg(feature = "spout")]
+  pixels: Vec<u8>,
+  // Direct3D's functions are called through the device, so the library is dropped last
+  #[cfg(feature = "spout")]
+  _d3d11: Library,
+}
+
+impl SpoutSender {
+  // start sending a Spout sender called `name`, which receivers list by that name
+  #[cfg_attr(not(feature = "spout"), allow(unused_variables))]
+  pub fn new(name: &str) -> Result<Self, String> {
+    #[cfg(not(feature = "spout"))]
+    return Err("Horus was built without the spout feature".to_string());
+    #[cfg(feature = "spout")]
+    {
+      if !cfg!(target_os = "windows") {
+        return Err("Spout only works on Windows".to_string());
+      }
+      if name.is_empty() || name.len() >= NAME_LENGTH || name.contains('\0') {
+        return Err(format!("Spout's names have to be between 1 and {} bytes long", NAME_LENGTH - 1));
+      }
+      unsafe {
+        let kernel = Kernel::load()?;
+        let d3d11 = Library::new("d3d11.dll").map_err(|error| error.to_string())?;
+        let create_device = *d3d11.get::<unsafe extern "system" fn(*mut c_void, i32, *mut c_void, u32, *const i32, u32, u32, *mut *mut c_void, *mut i32, *mut *mut c_void) -> i32>(b"D3D11CreateDevice\0").map_err(|error| error.to_string())?;
+        let (mut device, mut context, mut level) = (ptr::null_mut(), ptr::null_mut(), 0);
+        if create_device(ptr::null_mut(), D3D_DRIVER_TYPE_HARDWARE, ptr::null_mut(), 0, ptr::null(), 0, D3D11_SDK_VERSION, &mut device, &mut level, &mut context) < 0 {
+          return Err("Direct3D 11 isn't available on this GPU".to_string());
+        }
+        let names = match SharedMemory::open(&kernel, SENDER_NAMES, MAX_SENDERS * NAME_LENGTH) {
+          Ok(names) => names,
+          Err(error) => {
+            release(context);
+            release(device);
+            return Err(error);
+          }
+        };
+        let active = match SharedMemory::open(&kernel, ACTIVE_SENDER, NAME_LENGTH) {
+          Ok(active) => active,
+          Err(error) => {
+            names.close(&kernel);
+            release(context);
+            release(device);
+            return Err(error);
+          }
+        };
+        let access = kernel.mutex(&format!("{}_SpoutAccessMutex", name));
+        // the sender is only listed once its texture exists, so everything it's made of is cleaned up by dropping it until then
+        let mut sender = SpoutSender { name: name.to_string(), kernel, device, context, texture: ptr::null_mut(), size: (0, 0), names, active, info: None, access, pixels: Vec::new(), _d3d11: d3d11 };
+        sender.info = Some(SharedMemory::open(&sender.kernel, name, size_of::<TextureInfo>())?);
+        sender.register()?;
+        Ok(sender)
+      }
+    }
+  }
+
+  // share `frame` with every receiver
+  #[cfg_attr(not(feature = "spout"), allow(unused_variables))]
+  pub fn send(&mut self, frame: &RgbaImage) {
+    #[cfg(feature = "spout")]
+    unsafe {
+      let (width, height) = frame.dimensions();
+      if self.size != (width, height) {
+        if let Err(error) = self.create_texture(width, height) {
+          println!("[Horus] Unable to share a {}x{} frame over Spout: {}", width, height, error);
+          return;
+        }
+      }
+      self.pixels.clear();
+      self.pixels.extend(frame.pixels().flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]]));
+      // a frame that a receiver is still reading the last one of is skipped rather than holding up the window
+      if !self.kernel.lock(self.access) {
+        return;
+      }
+      let update: unsafe extern "system" fn(*mut c_void, *mut c_void, u32, *const c_void, *const c_void, u32, u32) = method(self.context, UPDATE_SUBRESOURCE);
+      update(self.context, self.texture, 0, ptr::null(), self.pixels.as_ptr() as *const c_void, width * 4, 0);
+      let flush: unsafe extern "system" fn(*mut c_void) = method(self.context, FLUSH);
+      flush(self.context);
+      self.kernel.unlock(self.access);
+    }
+  }
+}
+
+#[cfg(feature = "spout")]
+impl SpoutSender {
+  // add the sender to the list receivers choose from, and make it the active one if nothing else is
+  unsafe fn register(&mut self) -> Result<(), String> {
+    if !self.kernel.lock(self.names.mutex) {
+      return Err("another program is holding onto the list of Spout senders".to_string());
+    }
+    let bytes = self.names.bytes();
+    let mut names = read_names(bytes);
+    let result = if names.contains(&self.name) {
+      Err(format!("there's already a Spout sender called {}", self.name))
+    } else if (names.len() + 1) * NAME_LENGTH > bytes.len() {
+      Err("the list of Spout senders is full".to_string())
+    } else {
+      // Spout keeps the list in order
+      names.push(self.name.clone());
+      names.sort();
+      write_names(bytes, &names);
+      Ok(names)
+    };
+    self.kernel.unlock(self.names.mutex);
+    let names = result?;
+    if self.kernel.lock(self.active.mutex) {
+      let active = self.active.bytes();
+      if read_names(active).first().is_none_or(|active| !names.contains(active)) {
+        write_names(active, std::slice::from_ref(&self.name));
+      }
+      self.kernel.unlock(self.active.mutex);
+    }
+    Ok(())
+  }
+
+  // make the shared texture `width` by `height` pixels and tell receivers where it is
+  unsafe fn create_texture(&mut self, width: u32, height: u32) -> Result<(), String> {
+    if !self.texture.is_null() {
+      release(self.texture);
+      self.texture = ptr::null_mut();
+      self.size = (0, 0);
+    }
+    let desc = TextureDesc {
+      width,
+      height,
+      mip_levels: 1,
+      array_size: 1,
+      format: FORMAT_BGRA,
+      sample_count: 1,
+      sample_quality: 0,
+      usage: 0,
+      bind_flags: D3D11_BIND_SHADER_RESOURCE | D3D11_BIND_RENDER_TARGET,
+      cpu_access_flags: 0,
+      misc_flags: D3D11_RESOURCE_MISC_SHARED,
+    };
+    let create: unsafe extern "system" fn(*mut c_void, *const TextureDesc, *const c_void, *mut *mut c_void) -> i32 = method(self.device, CREATE_TEXTURE_2D);
+    let mut texture = ptr::null_mut();
+    if create(self.device, &desc, ptr::null(), &mut texture) < 0 {
+      return Err("Direct3D couldn't make a shared texture that size".to_string());
+    }
+    // the handle other processes open the texture with comes from its DXGI interface
+    let query: unsafe extern "system" fn(*mut c_void, *const Guid, *mut *mut c_void) -> i32 = method(texture, QUERY_INTERFACE);
+    let mut resource = ptr::null_mut();
+    if query(texture, &IID_DXGI_RESOURCE, &mut resource) < 0 {
+      release(texture);
+      return Err("the shared texture has no DXGI resource".to_string());
+    }
+    let get_shared_handle: unsafe extern "system" fn(*mut c_void, *mut Handle) -> i32 = method(resource, GET_SHARED_HANDLE);
+    let mut handle = 0;
+    let shared = get_shared_handle(resource, &mut handle);
+    release(resource);
+    if shared < 0 {
+      release(texture);
+      return Err("the shared texture has no handle".to_string());
+    }
+    self.texture = texture;
+    self.size = (width, height);
+    // shared handles always fit in 32 bits, which is all the room Spout has for them
+    let info = TextureInfo { share_handle: handle as u32, width, height, format: FORMAT_BGRA, usage: 0, description: [0; 256], partner_id: 0 };
+    if let Some(memory) = &mut self.info {
+      if self.kernel.lock(memory.mutex) {
+        let info = std::slice::from_raw_parts(&info as *const TextureInfo as *const u8, size_of::<TextureInfo>());
+        memory.bytes()[..info.len()].copy_from_slice(info);
+        self.kernel.unlock(memory.mutex);
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(feature = "spout")]
+impl Drop for SpoutSender {
+  fn drop(&mut self) {
+    unsafe {
+      if self.kernel.lock(self.names.mutex) {
+        let bytes = self.names.bytes();
+        let names: Vec<String> = read_names(bytes).into_iter().filter(|name| *name != self.name).collect();
+        write_names(bytes, &names);
+        self.kernel.unlock(self.names.mutex);
+        // receivers move on to whichever sender is left, if there are any
+        if self.kernel.lock(self.active.mutex) {
+          let active = self.active.bytes();
+          if read_names(active).first() == Some(&self.name) {
+            write_names(active, &names[..names.len().min(1)]);
+          }
+          self.kernel.unlock(self.active.mutex);
+        }
+      }
+      if let Some(info) = &self.info {
+        info.close(&self.kernel);
+      }
+      self.active.close(&self.kernel);
+      self.names.close(&self.kernel);
+      if self.access != 0 {
+        (self.kernel.close_handle)(self.access);
+      }
+      if !self.texture.is_null() {
+        release(self.texture);
+      }
+      release(self.context);
+      release(self.device);
+    }
+  }
+}
+
+// the method at `index` of the vtable of the COM interface `object`
+#[cfg(feature = "spout")]
+unsafe fn method<F: Copy>(object: *mut c_void, index: usize) -> F {
+  let vtable = *(object as *const *const usize);
+  std::mem::transmute_copy(&*vtable.add(index))
+}
+
+#[cfg(feature = "spout")]
+unsafe fn release(object: *mut c_void) {
+  let release: unsafe extern "system" fn(*mut c_void) -> u32 = method(object, RELEASE);
+  release(object);
+}
+
+// the names in a list of senders, which ends at the first empty one
+#[cfg(feature = "spout")]
+fn read_names(bytes: &[u8]) -> Vec<String> {
+  bytes.chunks_exact(NAME_LENGTH).map_while(|slot| {
+    let length = slot.iter().position(|&byte| byte == 0).unwrap_or(NAME_LENGTH);
+    (length > 0).then(|| String::from_utf8_lossy(&slot[..length]).into_owned())
+  }).collect()
+}
+
+#[cfg(feature = "spout")]
+fn write_names(bytes: &mut [u8], names: &[String]) {
+  bytes.fill(0);
+  for (slot, name) in bytes.chunks_exact_mut(NAME_LENGTH).zip(names) {
+    slot[..name.len()].copy_from_slice(name.as_bytes());
+  }
+}
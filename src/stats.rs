@@ -0,0 +1,60 @@
+// how fast frames are being drawn, averaged over about a second so the number is steady enough to read
+
+use std::{collections::VecDeque, time::{Duration, Instant}};
+
+const PERIOD: Duration = Duration::from_secs(1);
+
+// how many frame times the stats overlay graphs
+pub const HISTORY: usize = 240;
+
+pub struct FrameRate {
+  // when the current period started and how many frames have been drawn since
+  start: Instant,
+  frames: u32,
+  // the average over the last period, until there's been a whole one
+  fps: Option<f32>,
+  last_frame: Instant,
+  // the seconds between each of the most recent frames, oldest first
+  times: VecDeque<f32>,
+}
+
+impl Default for FrameRate {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl FrameRate {
+  pub fn new() -> Self {
+    FrameRate { start: Instant::now(), frames: 0, fps: None, last_frame: Instant::now(), times: VecDeque::with_capacity(HISTORY) }
+  }
+
+  // count a frame, returning the average frame rate whenever another period has passed
+  pub fn tick(&mut self) -> Option<f32> {
+    let now = Instant::now();
+    if self.times.len() == HISTORY {
+      self.times.pop_front();
+    }
+    self.times.push_back((now - self.last_frame).as_secs_f32());
+    self.last_frame = now;
+
+    self.frames += 1;
+    let elapsed = now - self.start;
+    if elapsed < PERIOD {
+      return None;
+    }
+    let fps = self.frames as f32 / elapsed.as_secs_f32();
+    self.start = now;
+    self.frames = 0;
+    self.fps = Some(fps);
+    self.fps
+  }
+
+  pub fn fps(&self) -> Option<f32> {
+    self.fps
+  }
+
+  pub fn times(&self) -> &VecDeque<f32> {
+    &self.times
+  }
+}
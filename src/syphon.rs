@@ -0,0 +1,230 @@
+// with --syphon, every frame is published as a Syphon server that Resolume, OBS, MadMapper and other Mac programs can receive
+//
+// Syphon shares an IOSurface between processes, which its framework fills from a Metal texture. wgpu can't hand out its own textures,
+// so each frame is read back the same as it is for recordings and written into a Metal texture of Horus' own, which Syphon copies into
+// the surface that receivers read without it leaving the GPU. Syphon.framework isn't part of macOS, so it's loaded when Horus starts
+// publishing rather than linked, from the app bundle Horus is in, next to Horus, or the Frameworks folders it's installed to
+
+#[cfg(feature = "syphon")]
+use std::{env, ffi::{c_char, c_void, CString}, path::PathBuf, ptr};
+#[cfg(feature = "syphon")]
+use libloading::Library;
+use image::RgbaImage;
+
+#[cfg(feature = "syphon")]
+type Id = *mut c_void;
+#[cfg(feature = "syphon")]
+type Selector = *const c_void;
+
+#[cfg(feature = "syphon")]
+const OBJC: &str = "/usr/lib/libobjc.A.dylib";
+// Foundation has NSString, and Metal has the GPU frames are uploaded to
+#[cfg(feature = "syphon")]
+const FOUNDATION: &str = "/System/Library/Frameworks/Foundation.framework/Foundation";
+#[cfg(feature = "syphon")]
+const METAL: &str = "/System/Library/Frameworks/Metal.framework/Metal";
+#[cfg(feature = "syphon")]
+const SYPHON: &str = "Syphon.framework/Syphon";
+// MTLPixelFormatRGBA8Unorm, which is how frames are read back
+#[cfg(feature = "syphon")]
+const PIXEL_FORMAT_RGBA8: u64 = 70;
+
+#[cfg(feature = "syphon")]
+#[repr(C)]
+struct Region {
+  x: u64,
+  y: u64,
+  z: u64,
+  width: u64,
+  height: u64,
+  depth: u64,
+}
+
+#[cfg(feature = "syphon")]
+#[repr(C)]
+struct Rect {
+  x: f64,
+  y: f64,
+  width: f64,
+  height: f64,
+}
+
+// the parts of the Objective-C runtime that Syphon and Metal are called through
+#[cfg(feature = "syphon")]
+struct Runtime {
+  get_class: unsafe extern "C" fn(*const c_char) -> Id,
+  register_selector: unsafe extern "C" fn(*const c_char) -> Selector,
+  message: *const c_void,
+  push_pool: unsafe extern "C" fn() -> *mut c_void,
+  pop_pool: unsafe extern "C" fn(*mut c_void),
+  create_device: unsafe extern "C" fn() -> Id,
+  // the functions above live in libobjc and Metal, and the classes that are used live in all of them
+  _libraries: Vec<Library>,
+}
+
+#[cfg(feature = "syphon")]
+impl Runtime {
+  unsafe fn load() -> Result<Self, String> {
+    let objc = Library::new(OBJC).map_err(|error| error.to_string())?;
+    let foundation = Library::new(FOUNDATION).map_err(|error| error.to_string())?;
+    let metal = Library::new(METAL).map_err(|error| error.to_string())?;
+    Ok(Runtime {
+      get_class: *objc.get(b"objc_getClass\0").map_err(|error| error.to_string())?,
+      register_selector: *objc.get(b"sel_registerName\0").map_err(|error| error.to_string())?,
+      message: *objc.get::<*const c_void>(b"objc_msgSend\0").map_err(|error| error.to_string())?,
+      push_pool: *objc.get(b"objc_autoreleasePoolPush\0").map_err(|error| error.to_string())?,
+      pop_pool: *objc.get(b"objc_autoreleasePoolPop\0").map_err(|error| error.to_string())?,
+      create_device: *metal.get(b"MTLCreateSystemDefaultDevice\0").map_err(|error| error.to_string())?,
+      _libraries: vec![objc, foundation, metal, load_syphon()?],
+    })
+  }
+
+  // the class called `name`, or an error if none of the libraries that were loaded have it
+  unsafe fn class(&self, name: &str) -> Result<Id, String> {
+    let c_name = CString::new(name).map_err(|error| error.to_string())?;
+    let class = (self.get_class)(c_name.as_ptr());
+    match class.is_null() {
+      true => Err(format!("{} isn't available, which needs a version of Syphon.framework with Metal support", name)),
+      false => Ok(class),
+    }
+  }
+
+  unsafe fn selector(&self, name: &str) -> Selector {
+    let name = CString::new(name).unwrap();
+    (self.register_selector)(name.as_ptr())
+  }
+
+  // objc_msgSend as the function `F`, which has to match the signature of the method it's going to call
+  unsafe fn send<F: Copy>(&self) -> F {
+    std::mem::transmute_copy(&self.message)
+  }
+
+  // send `selector` to `object` when it takes nothing and returns an object, or nothing
+  unsafe fn call(&self, object: Id, selector: &str) -> Id {
+    let call: unsafe extern "C" fn(Id, Selector) -> Id = self.send();
+    call(object, self.selector(selector))
+  }
+}
+
+pub struct SyphonServer {
+  #[cfg(feature = "syphon")]
+  server: Id,
+  #[cfg(feature = "syphon")]
+  device: Id,
+  #[cfg(feature = "syphon")]
+  queue: Id,
+  // the texture frames are uploaded to, which is made again whenever they change size
+  #[cfg(feature = "syphon")]
+  texture: Id,
+  #[cfg(feature = "syphon")]
+  size: (u32, u32),
+  // the objects above are all called through the runtime, so it's dropped last
+  #[cfg(feature = "syphon")]
+  runtime: Runtime,
+}
+
+impl SyphonServer {
+  // start publishing a Syphon server called `name`, which receivers list along with the name of the app that publishes it
+  #[cfg_attr(not(feature = "syphon"), allow(unused_variables))]
+  pub fn new(name: &str) -> Result<Self, String> {
+    #[cfg(not(feature = "syphon"))]
+    return Err("Horus was built without the syphon feature".to_string());
+    #[cfg(feature = "syphon")]
+    {
+      if !cfg!(target_os = "macos") {
+        return Err("Syphon only works on macOS".to_string());
+      }
+      let name = CString::new(name).map_err(|error| error.to_string())?;
+      unsafe {
+        let runtime = Runtime::load()?;
+        let server_class = runtime.class("SyphonMetalServer")?;
+        let string_class = runtime.class("NSString")?;
+        let device = (runtime.create_device)();
+        if device.is_null() {
+          return Err("Metal isn't available on this GPU".to_string());
+        }
+        let pool = (runtime.push_pool)();
+        let string: unsafe extern "C" fn(Id, Selector, *const c_char) -> Id = runtime.send();
+        let name = string(string_class, runtime.selector("stringWithUTF8String:"), name.as_ptr());
+        let init: unsafe extern "C" fn(Id, Selector, Id, Id, Id) -> Id = runtime.send();
+        let server = init(runtime.call(server_class, "alloc"), runtime.selector("initWithName:device:options:"), name, device, ptr::null_mut());
+        (runtime.pop_pool)(pool);
+        if server.is_null() {
+          runtime.call(device, "release");
+          return Err("Syphon couldn't start a server".to_string());
+        }
+        let queue = runtime.call(device, "newCommandQueue");
+        Ok(SyphonServer { server, device, queue, texture: ptr::null_mut(), size: (0, 0), runtime })
+      }
+    }
+  }
+
+  // publish `frame` to every receiver
+  #[cfg_attr(not(feature = "syphon"), allow(unused_variables))]
+  pub fn send(&mut self, frame: &RgbaImage) {
+    #[cfg(feature = "syphon")]
+    unsafe {
+      let (width, height) = frame.dimensions();
+      let runtime = &self.runtime;
+      let pool = (runtime.push_pool)();
+      if self.size != (width, height) {
+        if !self.texture.is_null() {
+          runtime.call(self.texture, "release");
+        }
+        let descriptor: unsafe extern "C" fn(Id, Selector, u64, u64, u64, bool) -> Id = runtime.send();
+        let descriptor = descriptor(runtime.class("MTLTextureDescriptor").unwrap_or(ptr::null_mut()), runtime.selector("texture2DDescriptorWithPixelFormat:width:height:mipmapped:"), PIXEL_FORMAT_RGBA8, width as u64, height as u64, false);
+        let texture: unsafe extern "C" fn(Id, Selector, Id) -> Id = runtime.send();
+        self.texture = match descriptor.is_null() {
+          true => ptr::null_mut(),
+          false => texture(self.device, runtime.selector("newTextureWithDescriptor:"), descriptor),
+        };
+        self.size = match self.texture.is_null() {
+          true => (0, 0),
+          false => (width, height),
+        };
+      }
+      if self.texture.is_null() {
+        println!("[Horus] Unable to publish a {}x{} frame over Syphon: Metal couldn't make a texture that size", width, height);
+        (runtime.pop_pool)(pool);
+        return;
+      }
+      let region = Region { x: 0, y: 0, z: 0, width: width as u64, height: height as u64, depth: 1 };
+      let replace: unsafe extern "C" fn(Id, Selector, Region, u64, *const c_void, u64) = runtime.send();
+      replace(self.texture, runtime.selector("replaceRegion:mipmapLevel:withBytes:bytesPerRow:"), region, 0, frame.as_raw().as_ptr() as *const c_void, width as u64 * 4);
+      // Syphon copies the texture into its surface on a command buffer of ours, which it doesn't wait for
+      let commands = runtime.call(self.queue, "commandBuffer");
+      let publish: unsafe extern "C" fn(Id, Selector, Id, Id, Rect, bool) = runtime.send();
+      let rect = Rect { x: 0.0, y: 0.0, width: width as f64, height: height as f64 };
+      publish(self.server, runtime.selector("publishFrameTexture:onCommandBuffer:imageRegion:flipped:"), self.texture, commands, rect, false);
+      runtime.call(commands, "commit");
+      (runtime.pop_pool)(pool);
+    }
+  }
+}
+
+#[cfg(feature = "syphon")]
+impl Drop for SyphonServer {
+  fn drop(&mut self) {
+    unsafe {
+      let runtime = &self.runtime;
+      runtime.call(self.server, "stop");
+      runtime.call(self.server, "release");
+      if !self.texture.is_null() {
+        runtime.call(self.texture, "release");
+      }
+      runtime.call(self.queue, "release");
+      runtime.call(self.device, "release");
+    }
+  }
+}
+
+#[cfg(feature = "syphon")]
+fn load_syphon() -> Result<Library, String> {
+  let executable = env::current_exe().ok().and_then(|path| path.parent().map(PathBuf::from));
+  // an app bundle keeps its frameworks in Contents/Frameworks, next to the Contents/MacOS folder Horus is in
+  let bundled = executable.iter().flat_map(|directory| [directory.join("../Frameworks").join(SYPHON), directory.join(SYPHON)]);
+  let installed = [PathBuf::from("/Library/Frameworks").join(SYPHON)];
+  let home = env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Frameworks").join(SYPHON));
+  bundled.chain(installed).chain(home).find_map(|path| unsafe { Library::new(path).ok() })
+    .ok_or_else(|| "Syphon.framework isn't installed, which comes from https://github.com/Syphon/Syphon-Framework".to_string())
+}
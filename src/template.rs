@@ -0,0 +1,52 @@
+// starter shaders that `horus new --template` can begin from, each one commented to explain a different technique
+
+use clap::ValueEnum;
+
+use crate::{live, pass::BUFFER_NAMES, FRAGMENT_SOURCE};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Template {
+  #[default]
+  #[value(help = "A gradient across the screen, which is also what runs when Horus isn't given a shader")]
+  Gradient,
+  #[value(help = "A 3D scene drawn by stepping rays through a distance function")]
+  Raymarch,
+  #[value(help = "2D shapes drawn and blended with signed distance functions")]
+  Sdf2d,
+  #[value(help = "Colors cycling through a few sine waves")]
+  Plasma,
+  #[value(help = "Trails left in a buffer that reads what it rendered last frame")]
+  Feedback,
+  #[value(help = "The microphone's spectrum and waveform")]
+  Audio,
+}
+
+impl Template {
+  pub fn shader(self) -> &'static str {
+    match self {
+      Template::Gradient => FRAGMENT_SOURCE,
+      Template::Raymarch => include_str!("templates/raymarch.wgsl"),
+      Template::Sdf2d => include_str!("templates/sdf2d.wgsl"),
+      Template::Plasma => include_str!("templates/plasma.wgsl"),
+      Template::Feedback => include_str!("templates/feedback.wgsl"),
+      Template::Audio => include_str!("templates/audio.wgsl"),
+    }
+  }
+
+  // the source of bufferA, for templates that need one
+  pub fn buffer(self) -> Option<&'static str> {
+    match self {
+      Template::Feedback => Some(include_str!("templates/feedback_buffer.wgsl")),
+      _ => None,
+    }
+  }
+
+  // what the shader reads from channel 0, if anything
+  pub fn channel0(self) -> Option<&'static str> {
+    match self {
+      Template::Feedback => Some(BUFFER_NAMES[0]),
+      Template::Audio => Some(live::MICROPHONE),
+      _ => None,
+    }
+  }
+}
@@ -0,0 +1,50 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Uniforms {
+    mouse: vec2<f32>,
+    resolution: vec2<f32>,
+    time: f32,
+    time_delta: f32,
+    frame: i32,
+    sample_rate: f32,
+    mouse_click: vec4<f32>,
+    date: vec4<f32>,
+    offset: vec2<f32>,
+    beat: f32,
+    bpm: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// a 512x2 texture of the microphone, which horus.toml binds to channel 0
+// row 0 is the spectrum from low to high frequencies and row 1 is the waveform, both between 0 and 1
+// binding "audio" instead plays the file given to `audio` in horus.toml through the same texture
+@group(1) @binding(0)
+var channel0: texture_2d<f32>;
+@group(1) @binding(1)
+var channel0_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = in.position.xy / uniforms.resolution;
+
+    // sampling halfway down each row keeps the two rows from blending into each other
+    let spectrum = textureSample(channel0, channel0_sampler, vec2<f32>(uv.x, 0.25)).x;
+    let wave = textureSample(channel0, channel0_sampler, vec2<f32>(uv.x, 0.75)).x;
+
+    // bars rising from the bottom as loud as each frequency, colored from red for the bass to blue for the treble
+    let height = 1. - uv.y;
+    // the background flashes on every beat
+    var color = vec3<f32>(0.05, 0.05, 0.1) + vec3<f32>(0.15, 0.1, 0.25) * uniforms.beat;
+    if (height < spectrum) {
+        color = mix(vec3<f32>(1., 0.3, 0.2), vec3<f32>(0.2, 0.5, 1.), uv.x) * (0.5 + height);
+    }
+
+    // the waveform as a bright line through the middle, where 0.5 is silence
+    let gap = abs(uv.y - (1. - wave)) * uniforms.resolution.y;
+    color += vec3<f32>(1.) * (1. - smoothstep(1., 3., gap));
+    return vec4<f32>(color, 1.);
+}
@@ -0,0 +1,32 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Uniforms {
+    mouse: vec2<f32>,
+    resolution: vec2<f32>,
+    time: f32,
+    time_delta: f32,
+    frame: i32,
+    sample_rate: f32,
+    mouse_click: vec4<f32>,
+    date: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// bufferA's latest frame, which horus.toml binds to channel 0
+@group(1) @binding(0)
+var channel0: texture_2d<f32>;
+@group(1) @binding(1)
+var channel0_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // all of the work happens in bufferA, so the image only has to show what it drew
+    let uv = in.position.xy / uniforms.resolution;
+    let trail = textureSample(channel0, channel0_sampler, uv).rgb;
+    // buffers can hold values above 1, so bright spots are squeezed back down
+    return vec4<f32>(trail / (1. + trail), 1.);
+}
@@ -0,0 +1,46 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Uniforms {
+    mouse: vec2<f32>,
+    resolution: vec2<f32>,
+    time: f32,
+    time_delta: f32,
+    frame: i32,
+    sample_rate: f32,
+    mouse_click: vec4<f32>,
+    date: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// reading its own channel gives bufferA what it rendered last frame, which is how trails and simulations remember things
+@group(1) @binding(0)
+var channel0: texture_2d<f32>;
+@group(1) @binding(1)
+var channel0_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = in.position.xy / uniforms.resolution;
+
+    // last frame, pulled slightly towards the center so the trails drift inwards, and faded so they don't last forever
+    let previous = textureSample(channel0, channel0_sampler, mix(uv, vec2<f32>(0.5), 0.004)).rgb * 0.97;
+
+    // a glowing dot that follows the mouse while the left button is held, and circles the screen otherwise
+    let t = uniforms.time;
+    var brush = uniforms.resolution * (0.5 + vec2<f32>(cos(t), sin(t * 1.3)) * 0.3);
+    if (uniforms.mouse_click.z > 0.) {
+        brush = uniforms.mouse_click.xy;
+    }
+    let glow = 20. / (1. + length(in.position.xy - brush));
+    let color = vec3<f32>(0.5 + 0.5 * cos(t + vec3<f32>(0., 2., 4.))) * glow * glow * 0.05;
+
+    // the buffers are cleared whenever Horus restarts or the window is resized, and frame counts up from 0 again
+    if (uniforms.frame == 0) {
+        return vec4<f32>(0.);
+    }
+    return vec4<f32>(previous + color, 1.);
+}
@@ -0,0 +1,43 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Uniforms {
+    mouse: vec2<f32>,
+    resolution: vec2<f32>,
+    time: f32,
+    time_delta: f32,
+    frame: i32,
+    sample_rate: f32,
+    mouse_click: vec4<f32>,
+    date: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// a cosine palette from iquilezles.org/articles/palettes, which cycles smoothly through colors as `t` goes from 0 to 1
+fn palette(t: f32) -> vec3<f32> {
+    let a = vec3<f32>(0.5, 0.5, 0.5);
+    let b = vec3<f32>(0.5, 0.5, 0.5);
+    let c = vec3<f32>(1., 1., 1.);
+    let d = vec3<f32>(0., 0.33, 0.67);
+    return a + b * cos(6.28318 * (c * t + d));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = in.position.xy / min(uniforms.resolution.x, uniforms.resolution.y) * 4.;
+    let t = uniforms.time;
+
+    // a plasma is a few sine waves added together, each moving in a different direction over time
+    var v = sin(uv.x + t);
+    v += sin((uv.y + t) * 0.5);
+    v += sin((uv.x + uv.y + t) * 0.5);
+    // this one ripples out from a point that drifts around the screen
+    let center = uv + vec2<f32>(sin(t * 0.3), cos(t * 0.5)) * 2.;
+    v += sin(length(center) + t);
+
+    // the four waves add up to somewhere between -4 and 4
+    return vec4<f32>(palette(v / 8. + 0.5), 1.);
+}
@@ -0,0 +1,79 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Uniforms {
+    mouse: vec2<f32>,
+    resolution: vec2<f32>,
+    time: f32,
+    time_delta: f32,
+    frame: i32,
+    sample_rate: f32,
+    mouse_click: vec4<f32>,
+    date: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// the distance from `p` to the closest surface in the scene, which is negative inside of it
+// a sphere is every point `radius` away from its center, and the floor is every point at a height of -1
+fn scene(p: vec3<f32>) -> f32 {
+    let bob = sin(uniforms.time) * 0.25;
+    let sphere = length(p - vec3<f32>(0., bob, 0.)) - 1.;
+    let floor = p.y + 1.;
+    return min(sphere, floor);
+}
+
+// the direction the distance grows fastest in, which points straight out of the surface
+fn normal(p: vec3<f32>) -> vec3<f32> {
+    let e = vec2<f32>(0.001, 0.);
+    return normalize(vec3<f32>(
+        scene(p + e.xyy) - scene(p - e.xyy),
+        scene(p + e.yxy) - scene(p - e.yxy),
+        scene(p + e.yyx) - scene(p - e.yyx),
+    ));
+}
+
+// step along the ray by the distance to the closest surface, which can never overshoot it,
+// until it's close enough to count as a hit or has gone too far to hit anything
+fn march(origin: vec3<f32>, direction: vec3<f32>) -> f32 {
+    var t = 0.;
+    for (var i = 0; i < 128; i++) {
+        let d = scene(origin + direction * t);
+        if (d < 0.001 || t > 50.) {
+            break;
+        }
+        t += d;
+    }
+    return t;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // pixel coordinates centered on the screen, with y going up and the shorter side going from -1 to 1
+    let uv = (in.position.xy * 2. - uniforms.resolution) / min(uniforms.resolution.x, uniforms.resolution.y) * vec2<f32>(1., -1.);
+
+    // a camera circling the scene, looking at its center
+    let angle = uniforms.time * 0.3;
+    let origin = vec3<f32>(sin(angle) * 4., 1., cos(angle) * 4.);
+    let forward = normalize(-origin);
+    let right = normalize(cross(vec3<f32>(0., 1., 0.), forward));
+    let up = cross(forward, right);
+    let direction = normalize(forward * 1.5 + right * uv.x + up * uv.y);
+
+    let t = march(origin, direction);
+    var color = vec3<f32>(0.6, 0.7, 0.9) - uv.y * 0.2;
+    if (t < 50.) {
+        let p = origin + direction * t;
+        let n = normal(p);
+        let light = normalize(vec3<f32>(1., 2., 1.));
+        // march towards the light to find out whether anything is in the way
+        let shadow = select(1., 0.3, march(p + n * 0.01, light) < 50.);
+        let diffuse = max(dot(n, light), 0.) * shadow;
+        color = vec3<f32>(0.9, 0.5, 0.3) * (diffuse + 0.1);
+        // fade into the sky with distance
+        color = mix(color, vec3<f32>(0.6, 0.7, 0.9), 1. - exp(-0.02 * t * t));
+    }
+    return vec4<f32>(color, 1.);
+}
@@ -0,0 +1,57 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Uniforms {
+    mouse: vec2<f32>,
+    resolution: vec2<f32>,
+    time: f32,
+    time_delta: f32,
+    frame: i32,
+    sample_rate: f32,
+    mouse_click: vec4<f32>,
+    date: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// signed distance functions return how far a point is from the edge of a shape, which is negative inside of it
+fn circle(p: vec2<f32>, radius: f32) -> f32 {
+    return length(p) - radius;
+}
+
+fn box(p: vec2<f32>, size: vec2<f32>) -> f32 {
+    let d = abs(p) - size;
+    return length(max(d, vec2<f32>(0.))) + min(max(d.x, d.y), 0.);
+}
+
+// blends two shapes together within `k` of where they meet, where `min` would leave a sharp corner
+fn smooth_union(a: f32, b: f32, k: f32) -> f32 {
+    let h = clamp(0.5 + 0.5 * (b - a) / k, 0., 1.);
+    return mix(b, a, h) - k * h * (1. - h);
+}
+
+// pixel coordinates centered on the screen, with y going up and the shorter side going from -1 to 1
+fn to_uv(position: vec2<f32>) -> vec2<f32> {
+    return (position * 2. - uniforms.resolution) / min(uniforms.resolution.x, uniforms.resolution.y) * vec2<f32>(1., -1.);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = to_uv(in.position.xy);
+    let pixel = 2. / min(uniforms.resolution.x, uniforms.resolution.y);
+
+    // a box spinning in place, merging with a circle that follows the mouse
+    let angle = uniforms.time * 0.5;
+    let rotated = mat2x2<f32>(cos(angle), -sin(angle), sin(angle), cos(angle)) * uv;
+    var d = box(rotated, vec2<f32>(0.4, 0.25));
+    d = smooth_union(d, circle(uv - to_uv(uniforms.mouse), 0.2), 0.2);
+
+    // orange inside, blue outside, with rings showing the distance and a white line on the edge
+    var color = select(vec3<f32>(0.4, 0.7, 1.), vec3<f32>(1., 0.6, 0.3), d < 0.);
+    color *= 1. - exp(-4. * abs(d));
+    color *= 0.8 + 0.2 * cos(120. * d);
+    color = mix(color, vec3<f32>(1.), 1. - smoothstep(0., pixel * 2., abs(d)));
+    return vec4<f32>(color, 1.);
+}
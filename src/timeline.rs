@@ -0,0 +1,196 @@
+// keyframes for the custom uniforms, read from a toml or json file and evaluated at the shader's time on every frame, so animations
+// and camera moves can be choreographed without hardcoding them in the shader
+//
+// loop = 8.0
+//
+// [[track]]
+// uniform = "camera"
+// keys = [
+//   { time = 0.0, value = [0.0, 1.0, -5.0] },
+//   { time = 4.0, value = [2.0, 1.0, -3.0], easing = "ease-in-out" },
+// ]
+
+use std::{fs::read_to_string, path::{Path, PathBuf}};
+use serde::Deserialize;
+
+use crate::params::Params;
+
+// how a key is reached from the one before it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Easing {
+  // holds the previous key's value until this one's time
+  Step,
+  #[default]
+  Linear,
+  EaseIn,
+  EaseOut,
+  EaseInOut,
+}
+
+impl Easing {
+  // how far along the way to the key `progress` of the time between them is
+  fn apply(self, progress: f32) -> f32 {
+    match self {
+      Easing::Step => if progress < 1. { 0. } else { 1. },
+      Easing::Linear => progress,
+      Easing::EaseIn => progress * progress * progress,
+      Easing::EaseOut => 1. - (1. - progress).powi(3),
+      Easing::EaseInOut => match progress < 0.5 {
+        true => 4. * progress * progress * progress,
+        false => 1. - (2. - 2. * progress).powi(3) / 2.,
+      },
+    }
+  }
+}
+
+// a single number is the same for every component
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum Components {
+  One(f32),
+  Each(Vec<f32>),
+}
+
+impl Components {
+  fn to_vec(&self) -> Vec<f32> {
+    match self {
+      Components::One(value) => vec![*value],
+      Components::Each(values) => values.clone(),
+    }
+  }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Key {
+  time: f32,
+  value: Components,
+  #[serde(default)]
+  easing: Easing,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Track {
+  uniform: String,
+  keys: Vec<Key>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Keyframes {
+  // how many seconds in the timeline starts over
+  #[serde(rename = "loop")]
+  repeat: Option<f32>,
+  #[serde(default)]
+  track: Vec<Track>,
+}
+
+pub struct Timeline {
+  pub path: PathBuf,
+  tracks: Vec<Track>,
+  repeat: Option<f32>,
+  // why the file last failed to load
+  pub error: Option<String>,
+  // uniforms that have already been complained about, since the timeline is evaluated every frame
+  unknown: Vec<String>,
+}
+
+impl Timeline {
+  // the keyframes in the file at `path`, if there is one
+  pub fn new(path: PathBuf) -> Self {
+    let mut timeline = Timeline { path, tracks: Vec::new(), repeat: None, error: None, unknown: Vec::new() };
+    timeline.reload();
+    timeline
+  }
+
+  // read the file again, keeping the keyframes it last had if it can't be read
+  pub fn reload(&mut self) {
+    match load(&self.path) {
+      Ok(keyframes) => {
+        if !keyframes.track.is_empty() {
+          println!("[Horus] Animating {} uniforms from {}", keyframes.track.len(), self.path.display());
+        }
+        self.tracks = keyframes.track;
+        self.repeat = keyframes.repeat;
+        self.error = None;
+        self.unknown.clear();
+      }
+      Err(error) => {
+        let error = format!("error: {}", error);
+        println!("[Horus] Unable to load {}\n{}", self.path.display(), error);
+        self.error = Some(error);
+      }
+    }
+  }
+
+  // set every uniform that has a track to where its keys put it at `time`
+  pub fn apply(&mut self, params: &mut Params, time: f32) {
+    let time = match self.repeat {
+      Some(repeat) => time.rem_euclid(repeat),
+      None => time,
+    };
+    for track in &self.tracks {
+      let Some(index) = params.index_of(&track.uniform) else {
+        if !self.unknown.contains(&track.uniform) {
+          println!("[Horus] Ignoring the keys for {} in {} since there's no uniform called {}", track.uniform, self.path.display(), track.uniform);
+          self.unknown.push(track.uniform.clone());
+        }
+        continue;
+      };
+      let value = track.value_at(time);
+      // a single number sets every component
+      let value = match value[..] {
+        [value] => vec![value; params.params[index].kind.components()],
+        _ => value,
+      };
+      params.set(index, &value);
+    }
+  }
+}
+
+impl Track {
+  // the value between the keys on either side of `time`, which holds still before the first key and after the last
+  fn value_at(&self, time: f32) -> Vec<f32> {
+    let next = self.keys.partition_point(|key| key.time <= time);
+    let (Some(from), Some(to)) = (next.checked_sub(1).map(|index| &self.keys[index]), self.keys.get(next)) else {
+      return self.keys[next.saturating_sub(1)].value.to_vec();
+    };
+    let progress = to.easing.apply((time - from.time) / (to.time - from.time));
+    let (from, to) = (from.value.to_vec(), to.value.to_vec());
+    // keys of a single number are spread over however many components the other key has
+    let components = from.len().max(to.len());
+    (0..components).map(|component| {
+      let (from, to) = (from[component.min(from.len() - 1)], to[component.min(to.len() - 1)]);
+      from + (to - from) * progress
+    }).collect()
+  }
+}
+
+// the keyframes at `path`, read as json if that's its extension and as toml otherwise
+fn load(path: &Path) -> Result<Keyframes, String> {
+  let source = read_to_string(path).map_err(|error| error.to_string())?;
+  let mut keyframes: Keyframes = match path.extension().is_some_and(|extension| extension == "json") {
+    true => serde_json::from_str(&source).map_err(|error| error.to_string())?,
+    false => toml::from_str(&source).map_err(|error| error.to_string())?,
+  };
+  if keyframes.repeat.is_some_and(|repeat| repeat <= 0. || !repeat.is_finite()) {
+    return Err("the timeline has to loop after more than 0 seconds".to_string());
+  }
+  for track in &mut keyframes.track {
+    if track.keys.is_empty() {
+      return Err(format!("the track for {} doesn't have any keys", track.uniform));
+    }
+    if let Some(key) = track.keys.iter().find(|key| matches!(&key.value, Components::Each(values) if values.is_empty())) {
+      return Err(format!("the key at {}s for {} doesn't have a value", key.time, track.uniform));
+    }
+    let lengths: Vec<usize> = track.keys.iter().map(|key| key.value.to_vec().len()).filter(|length| *length > 1).collect();
+    if lengths.windows(2).any(|pair| pair[0] != pair[1]) {
+      return Err(format!("the keys for {} don't all have the same number of components", track.uniform));
+    }
+    // keys at the same time jump straight from one to the other
+    track.keys.sort_by(|first, second| first.time.total_cmp(&second.time));
+  }
+  Ok(keyframes)
+}
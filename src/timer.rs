@@ -0,0 +1,91 @@
+// measures how long the GPU spends drawing each frame with timestamp queries, separately from the CPU and presenting
+
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use wgpu::{Buffer, BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoder, Device, Features, Maintain, MapMode, QuerySet, QuerySetDescriptor, QueryType, Queue, QUERY_SIZE};
+
+// one timestamp before the frame is drawn and one after
+const QUERIES: u32 = 2;
+
+pub struct GpuTimer {
+  queries: QuerySet,
+  // where the timestamps are resolved to and read back from
+  readback: Buffer,
+  // nanoseconds per tick of a timestamp
+  period: f32,
+  // set while the readback buffer is on its way back from the GPU, which skips timing frames until it arrives
+  mapping: Option<Receiver<Result<(), BufferAsyncError>>>,
+  // whether the frame being encoded is being timed
+  timing: bool,
+  // how many milliseconds the last measured frame took
+  milliseconds: Option<f32>,
+}
+
+impl GpuTimer {
+  // the features a device needs for timestamp queries, if the adapter supports them
+  pub fn features(supported: Features) -> Features {
+    supported & Features::TIMESTAMP_QUERY
+  }
+
+  // a timer, if `device` was created with timestamp queries
+  pub fn new(device: &Device, queue: &Queue) -> Option<Self> {
+    if !device.features().contains(Features::TIMESTAMP_QUERY) {
+      return None;
+    }
+    let queries = device.create_query_set(&QuerySetDescriptor { label: Some("timestamps"), ty: QueryType::Timestamp, count: QUERIES });
+    let readback = device.create_buffer(&BufferDescriptor { label: Some("timestamps"), size: (QUERIES * QUERY_SIZE) as u64, usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ, mapped_at_creation: false });
+    Some(GpuTimer { queries, readback, period: queue.get_timestamp_period(), mapping: None, timing: false, milliseconds: None })
+  }
+
+  // write the first timestamp, before anything else in `encoder`
+  pub fn start(&mut self, encoder: &mut CommandEncoder) {
+    self.timing = self.mapping.is_none();
+    if self.timing {
+      encoder.write_timestamp(&self.queries, 0);
+    }
+  }
+
+  // write the second timestamp and resolve both of them somewhere they can be read
+  pub fn end(&mut self, encoder: &mut CommandEncoder) {
+    if !self.timing {
+      return;
+    }
+    encoder.write_timestamp(&self.queries, 1);
+    encoder.resolve_query_set(&self.queries, 0..QUERIES, &self.readback, 0);
+  }
+
+  // once the timed frame has been submitted, start reading its timestamps back without waiting for them
+  pub fn submitted(&mut self) {
+    if !self.timing {
+      return;
+    }
+    self.timing = false;
+    let (sender, receiver) = channel();
+    self.readback.slice(..).map_async(MapMode::Read, move |result| {
+      let _ = sender.send(result);
+    });
+    self.mapping = Some(receiver);
+  }
+
+  // the GPU time of the most recent frame whose timestamps have arrived
+  pub fn milliseconds(&mut self, device: &Device) -> Option<f32> {
+    let Some(receiver) = &self.mapping else {
+      return self.milliseconds;
+    };
+    device.poll(Maintain::Poll);
+    match receiver.try_recv() {
+      Ok(Ok(())) => {
+        let mapped = self.readback.slice(..).get_mapped_range();
+        let timestamps: &[u64] = bytemuck::cast_slice(&mapped);
+        let ticks = timestamps[1].wrapping_sub(timestamps[0]);
+        self.milliseconds = Some(ticks as f32 * self.period / 1_000_000.);
+        drop(mapped);
+        self.readback.unmap();
+        self.mapping = None;
+      }
+      Err(TryRecvError::Empty) => {}
+      // the frame wasn't timed after all, so try again with the next one
+      Ok(Err(_)) | Err(TryRecvError::Disconnected) => self.mapping = None,
+    }
+    self.milliseconds
+  }
+}
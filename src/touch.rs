@@ -0,0 +1,75 @@
+// fingers on a touchscreen move the mouse, and the first few of them are also handed to shaders in a uniform block of their own,
+// so they can respond to pinches, twists and other gestures that take more than one finger
+//
+// the first finger to touch an empty screen is the mouse, which is held down until that finger lifts, whatever the others do
+
+use winit::event::{Touch, TouchPhase};
+
+// how many fingers shaders can see at once, past which any more are left out until one lifts
+pub const MAX_TOUCHES: usize = 4;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Touches {
+  // like shadertoy's iMouse, xy is where each finger is and zw is where it touched down, in pixels from the top left corner
+  // and in the order they touched down, with every point past `count` left at 0
+  pub points: [[f32; 4]; MAX_TOUCHES],
+  pub count: i32,
+  // shaders round the block up to a multiple of 16 bytes, since `points` are vec4s
+  pub padding: [i32; 3],
+}
+
+// what a finger did to the mouse
+pub enum MouseTouch {
+  Pressed([f32; 2]),
+  Moved([f32; 2]),
+  Released,
+}
+
+#[derive(Default)]
+pub struct TouchScreen {
+  // the id of each finger that's down along with where it is and where it touched down
+  fingers: Vec<(u64, [f32; 4])>,
+  // the finger that's the mouse
+  mouse: Option<u64>,
+}
+
+impl TouchScreen {
+  // follow `touch`, which is at `position` in the pixels the shader renders, returning what it did to the mouse if it's the finger that's the mouse
+  pub fn input(&mut self, touch: &Touch, position: [f32; 2]) -> Option<MouseTouch> {
+    let [x, y] = position;
+    match touch.phase {
+      TouchPhase::Started => {
+        self.fingers.push((touch.id, [x, y, x, y]));
+        if self.mouse.is_some() {
+          return None;
+        }
+        self.mouse = Some(touch.id);
+        Some(MouseTouch::Pressed(position))
+      }
+      TouchPhase::Moved => {
+        if let Some((_, point)) = self.fingers.iter_mut().find(|(id, _)| *id == touch.id) {
+          point[..2].copy_from_slice(&position);
+        }
+        (self.mouse == Some(touch.id)).then_some(MouseTouch::Moved(position))
+      }
+      TouchPhase::Ended | TouchPhase::Cancelled => {
+        self.fingers.retain(|(id, _)| *id != touch.id);
+        if self.mouse != Some(touch.id) {
+          return None;
+        }
+        self.mouse = None;
+        Some(MouseTouch::Released)
+      }
+    }
+  }
+
+  pub fn touches(&self) -> Touches {
+    let mut touches: Touches = bytemuck::Zeroable::zeroed();
+    for (point, (_, finger)) in touches.points.iter_mut().zip(&self.fingers) {
+      *point = *finger;
+    }
+    touches.count = self.fingers.len().min(MAX_TOUCHES) as i32;
+    touches
+  }
+}
@@ -0,0 +1,145 @@
+// volumes are bound as a `texture_3d<f32>`, so shaders can raymarch through real clouds, smoke or scans instead of noise
+//
+// they're loaded from shadertoy's .bin volumes, which are also read as .vol, raw voxels with their size and type in their name like
+// `foot_256x256x256_uint8.raw`, or a directory of images that are each a slice, stacked in the order of their names
+
+use std::{fs, path::Path};
+use half::f16;
+use wgpu::TextureFormat;
+
+use crate::channel::ColorSpace;
+
+// shadertoy's volumes start with a signature, their size, the number of channels, how they're laid out and the format of each voxel
+const HEADER_SIZE: usize = 20;
+// the formats shadertoy's volumes can have, which are 8-bit integers or 32-bit floats
+const FORMAT_BYTE: u16 = 0;
+const FORMAT_FLOAT: u16 = 10;
+
+pub struct Volume {
+  pub width: u32,
+  pub height: u32,
+  pub depth: u32,
+  pub format: TextureFormat,
+  pub voxels: Vec<u8>,
+}
+
+// whether `path` is loaded as a volume, which every directory that isn't a cubemap is
+pub fn is_volume(path: &Path) -> bool {
+  path.is_dir() || path.extension().and_then(|extension| extension.to_str()).is_some_and(|extension| ["bin", "vol", "raw"].contains(&extension.to_lowercase().as_str()))
+}
+
+// load the volume at `path`, flipping each slice upside down for shaders whose origin is in the bottom left
+pub fn load(path: &Path, flip: bool, color_space: Option<ColorSpace>) -> Result<Volume, String> {
+  if path.is_dir() {
+    return from_slices(path, flip, color_space);
+  }
+  let bytes = fs::read(path).map_err(|error| error.to_string())?;
+  match path.extension().and_then(|extension| extension.to_str()).map(str::to_lowercase).as_deref() {
+    Some("raw") => from_raw(path, &bytes),
+    _ => from_shadertoy(&bytes),
+  }
+}
+
+fn from_shadertoy(bytes: &[u8]) -> Result<Volume, String> {
+  let Some(header) = bytes.get(..HEADER_SIZE) else {
+    return Err("it's too short to be a volume".to_string());
+  };
+  let word = |offset: usize| u32::from_le_bytes([header[offset], header[offset + 1], header[offset + 2], header[offset + 3]]);
+  let (width, height, depth, channels) = (word(4), word(8), word(12), header[16] as usize);
+  let format = u16::from_le_bytes([header[18], header[19]]);
+  if !(1..=4).contains(&channels) {
+    return Err(format!("it has {} channels instead of one to four", channels));
+  }
+  let voxels = &bytes[HEADER_SIZE..];
+  let count = width as usize * height as usize * depth as usize * channels;
+  let values: Vec<f32> = match format {
+    FORMAT_BYTE if voxels.len() >= count => return Ok(from_bytes(width, height, depth, channels, &voxels[..count])),
+    FORMAT_FLOAT if voxels.len() >= count * 4 => voxels[..count * 4].chunks_exact(4).map(|value| f32::from_le_bytes([value[0], value[1], value[2], value[3]])).collect(),
+    FORMAT_BYTE | FORMAT_FLOAT => return Err(format!("it's shorter than a {}x{}x{} volume with {} channels", width, height, depth, channels)),
+    _ => return Err(format!("its voxels have a format Horus doesn't know of ({})", format)),
+  };
+  Ok(from_floats(width, height, depth, channels, &values))
+}
+
+// the size and type of raw voxels come from their name, since there's nothing else in the file
+fn from_raw(path: &Path, bytes: &[u8]) -> Result<Volume, String> {
+  let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default().to_lowercase();
+  let parts: Vec<&str> = stem.split(['_', '-', '.']).collect();
+  let size = parts.iter().find_map(|part| match part.split('x').map(|size| size.parse::<u32>()).collect::<Result<Vec<_>, _>>() {
+    Ok(size) if size.len() == 3 => Some(size),
+    _ => None,
+  });
+  let Some(&[width, height, depth]) = size.as_deref() else {
+    return Err("raw volumes need their size in their name, like foot_256x256x256_uint8.raw".to_string());
+  };
+  let count = width as usize * height as usize * depth as usize;
+  let kind = parts.iter().copied().find(|part| ["uint8", "uint16", "float32"].contains(part)).unwrap_or("uint8");
+  let size = match kind {
+    "uint16" => 2,
+    "float32" => 4,
+    _ => 1,
+  };
+  if bytes.len() < count * size {
+    return Err(format!("it's shorter than {}x{}x{} voxels of {}", width, height, depth, kind));
+  }
+  let values: Vec<f32> = match kind {
+    "uint16" => bytes[..count * 2].chunks_exact(2).map(|value| u16::from_le_bytes([value[0], value[1]]) as f32 / u16::MAX as f32).collect(),
+    "float32" => bytes[..count * 4].chunks_exact(4).map(|value| f32::from_le_bytes([value[0], value[1], value[2], value[3]])).collect(),
+    _ => return Ok(from_bytes(width, height, depth, 1, &bytes[..count])),
+  };
+  Ok(from_floats(width, height, depth, 1, &values))
+}
+
+// every image in `directory` is a slice, in the order of their names, and they all have to be the same size
+fn from_slices(directory: &Path, flip: bool, color_space: Option<ColorSpace>) -> Result<Volume, String> {
+  let mut paths: Vec<_> = directory.read_dir().map_err(|error| error.to_string())?.filter_map(|entry| Some(entry.ok()?.path()))
+    .filter(|path| path.is_file() && image::ImageFormat::from_path(path).is_ok())
+    .collect();
+  paths.sort();
+  let Some(first) = paths.first() else {
+    return Err(format!("there are no images in {}", directory.display()));
+  };
+  let first = image::open(first).map_err(|error| error.to_string())?;
+  let (width, height) = (first.width(), first.height());
+  // the same as images, color slices are almost always sRGB while grayscale ones hold data like densities
+  let color_space = color_space.unwrap_or(if first.color().has_color() { ColorSpace::Srgb } else { ColorSpace::Linear });
+  let mut voxels = Vec::with_capacity(width as usize * height as usize * paths.len() * 4);
+  for path in &paths {
+    let mut slice = image::open(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    if slice.width() != width || slice.height() != height {
+      return Err(format!("{} isn't {}x{} like the other slices", path.display(), width, height));
+    }
+    if flip {
+      slice = slice.flipv();
+    }
+    voxels.extend_from_slice(&slice.to_rgba8());
+  }
+  let format = match color_space {
+    ColorSpace::Srgb => TextureFormat::Rgba8UnormSrgb,
+    ColorSpace::Linear => TextureFormat::Rgba8Unorm,
+  };
+  Ok(Volume { width, height, depth: paths.len() as u32, format, voxels })
+}
+
+// 8-bit voxels with one, two or four channels are uploaded as they are, and three get an opaque alpha since there's no RGB format
+fn from_bytes(width: u32, height: u32, depth: u32, channels: usize, bytes: &[u8]) -> Volume {
+  let (format, voxels) = match channels {
+    1 => (TextureFormat::R8Unorm, bytes.to_vec()),
+    2 => (TextureFormat::Rg8Unorm, bytes.to_vec()),
+    3 => (TextureFormat::Rgba8Unorm, bytes.chunks_exact(3).flat_map(|voxel| [voxel[0], voxel[1], voxel[2], u8::MAX]).collect()),
+    _ => (TextureFormat::Rgba8Unorm, bytes.to_vec()),
+  };
+  Volume { width, height, depth, format, voxels }
+}
+
+// unlike 32-bit floats, half floats can be filtered on every device
+fn from_floats(width: u32, height: u32, depth: u32, channels: usize, values: &[f32]) -> Volume {
+  let (format, values): (_, Vec<f32>) = match channels {
+    1 => (TextureFormat::R16Float, values.to_vec()),
+    2 => (TextureFormat::Rg16Float, values.to_vec()),
+    3 => (TextureFormat::Rgba16Float, values.chunks_exact(3).flat_map(|voxel| [voxel[0], voxel[1], voxel[2], 1.]).collect()),
+    _ => (TextureFormat::Rgba16Float, values.to_vec()),
+  };
+  let halves: Vec<f16> = values.iter().map(|&value| f16::from_f32(value)).collect();
+  Volume { width, height, depth, format, voxels: bytemuck::cast_slice(&halves).to_vec() }
+}
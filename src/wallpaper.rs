@@ -0,0 +1,90 @@
+// with --wallpaper, the window covers a monitor behind the desktop's icons, so the shader runs as a live animated wallpaper
+//
+// on Windows it's moved into the WorkerW window that Explorer draws the wallpaper into, and on X11 it's made a desktop window, which
+// window managers keep below everything else. Wayland compositors only put surfaces behind windows through wlroots' layer-shell protocol,
+// which the version of winit Horus is built on has no way to make yet
+
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+#[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+use winit::platform::unix::{WindowBuilderExtUnix, XWindowType};
+use winit::window::{Window, WindowBuilder};
+
+// a borderless window that X11 window managers put behind everything else, which is left to be sized over a monitor
+pub fn prepare(builder: WindowBuilder) -> WindowBuilder {
+  #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+  let builder = builder.with_x11_window_type(vec![XWindowType::Desktop]);
+  builder.with_decorations(false)
+}
+
+// put `window` behind the desktop's icons, where it stays until it's closed
+pub fn attach(window: &Window) -> Result<(), String> {
+  match window.raw_window_handle() {
+    #[cfg(target_os = "windows")]
+    RawWindowHandle::Win32(handle) => {
+      let position = window.outer_position().map_err(|error| error.to_string())?;
+      workerw::attach(handle.hwnd as isize)?;
+      // a child window is positioned from the top left corner of its parent, which is the top left of every monitor
+      let origin = window.available_monitors().map(|monitor| monitor.position()).fold((0, 0), |(x, y), position| (x.min(position.x), y.min(position.y)));
+      window.set_outer_position(winit::dpi::PhysicalPosition::new(position.x - origin.0, position.y - origin.1));
+      Ok(())
+    }
+    // the window was already made a desktop window when it was created
+    RawWindowHandle::Xlib(_) | RawWindowHandle::Xcb(_) => Ok(()),
+    RawWindowHandle::Wayland(_) => Err("Wayland needs layer-shell to put windows behind the desktop, which winit can't make yet, so run Horus under XWayland with WINIT_UNIX_BACKEND=x11".to_string()),
+    _ => Err("this platform has no way to put windows behind the desktop that Horus knows of".to_string()),
+  }
+}
+
+#[cfg(target_os = "windows")]
+mod workerw {
+  use std::ptr;
+
+  type Hwnd = isize;
+
+  // asks Explorer to make the WorkerW window behind the icons, which it otherwise only does while the wallpaper changes
+  const SPAWN_WORKERW: u32 = 0x052C;
+  const SMTO_NORMAL: u32 = 0;
+
+  #[link(name = "user32")]
+  extern "system" {
+    fn FindWindowW(class: *const u16, name: *const u16) -> Hwnd;
+    fn FindWindowExW(parent: Hwnd, after: Hwnd, class: *const u16, name: *const u16) -> Hwnd;
+    fn SendMessageTimeoutW(window: Hwnd, message: u32, wparam: usize, lparam: isize, flags: u32, timeout: u32, result: *mut usize) -> isize;
+    fn EnumWindows(callback: unsafe extern "system" fn(Hwnd, isize) -> i32, lparam: isize) -> i32;
+    fn SetParent(child: Hwnd, parent: Hwnd) -> Hwnd;
+  }
+
+  fn wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain(Some(0)).collect()
+  }
+
+  // the WorkerW is the window after the one holding the icons
+  unsafe extern "system" fn find_workerw(window: Hwnd, found: isize) -> i32 {
+    if FindWindowExW(window, 0, wide("SHELLDLL_DefView").as_ptr(), ptr::null()) != 0 {
+      *(found as *mut Hwnd) = FindWindowExW(0, window, wide("WorkerW").as_ptr(), ptr::null());
+    }
+    1
+  }
+
+  pub fn attach(window: Hwnd) -> Result<(), String> {
+    unsafe {
+      let progman = FindWindowW(wide("Progman").as_ptr(), ptr::null());
+      if progman == 0 {
+        return Err("Explorer isn't running".to_string());
+      }
+      let mut result = 0;
+      SendMessageTimeoutW(progman, SPAWN_WORKERW, 0, 0, SMTO_NORMAL, 1000, &mut result);
+      let mut workerw: Hwnd = 0;
+      EnumWindows(find_workerw, &mut workerw as *mut Hwnd as isize);
+      // newer versions of Windows 11 keep it inside Progman instead
+      if workerw == 0 {
+        workerw = FindWindowExW(progman, 0, wide("WorkerW").as_ptr(), ptr::null());
+      }
+      if workerw == 0 {
+        return Err("Explorer didn't make a window behind the desktop icons".to_string());
+      }
+      SetParent(window, workerw);
+    }
+    Ok(())
+  }
+}
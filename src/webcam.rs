@@ -0,0 +1,111 @@
+// the default camera's live feed, captured on its own thread so waiting for frames never holds up rendering
+
+use std::{sync::mpsc::{self, Receiver}, thread};
+use nokhwa::{Camera, pixel_format::RgbAFormat, utils::{CameraIndex, RequestedFormat, RequestedFormatType}};
+use wgpu::{Device, Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d, Queue, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages};
+
+use crate::channel::Channel;
+
+pub struct Webcam {
+  texture: Texture,
+  format: TextureFormat,
+  width: u32,
+  height: u32,
+  // the newest frame as RGBA, the camera thread drops frames while one is still waiting to be uploaded
+  frames: Receiver<Vec<u8>>,
+  // shaders whose origin is in the bottom left see the feed upside down unless it's flipped
+  flip: bool,
+}
+
+impl Webcam {
+  pub fn start(device: &Device) -> Result<Self, String> {
+    let (ready_sender, ready) = mpsc::channel();
+    let (frame_sender, frames) = mpsc::sync_channel(1);
+    thread::spawn(move || {
+      let format = RequestedFormat::new::<RgbAFormat>(RequestedFormatType::AbsoluteHighestFrameRate);
+      let camera = Camera::new(CameraIndex::Index(0), format).and_then(|mut camera| camera.open_stream().map(|_| camera));
+      let mut camera = match camera {
+        Ok(camera) => camera,
+        Err(error) => {
+          let _ = ready_sender.send(Err(error.to_string()));
+          return;
+        }
+      };
+      let resolution = camera.resolution();
+      if ready_sender.send(Ok((resolution.width(), resolution.height()))).is_err() {
+        return;
+      }
+      loop {
+        match camera.frame().and_then(|frame| frame.decode_image::<RgbAFormat>()) {
+          Ok(image) => {
+            // stop once the window has closed and nothing is listening anymore
+            if let Err(mpsc::TrySendError::Disconnected(_)) = frame_sender.try_send(image.into_raw()) {
+              return;
+            }
+          }
+          Err(error) => {
+            println!("[Horus] Webcam error: {}", error);
+            return;
+          }
+        }
+      }
+    });
+
+    let (width, height) = ready.recv().map_err(|error| error.to_string())??;
+    let format = TextureFormat::Rgba8UnormSrgb;
+    Ok(Webcam { texture: create_texture(device, width, height, format), format, width, height, frames, flip: false })
+  }
+
+  // shadertoy flips the feed like any other image and reads its bytes without any sRGB conversion
+  pub fn channel(&mut self, device: &Device, shadertoy: bool) -> Channel {
+    self.flip = shadertoy;
+    let format = if shadertoy { TextureFormat::Rgba8Unorm } else { TextureFormat::Rgba8UnormSrgb };
+    if self.format != format {
+      self.texture = create_texture(device, self.width, self.height, format);
+      self.format = format;
+    }
+    Channel::from_texture(device, &self.texture)
+  }
+
+  // upload the newest frame if the camera has captured one since the last time
+  pub fn update(&mut self, queue: &Queue) {
+    let Ok(mut pixels) = self.frames.try_recv() else {
+      return;
+    };
+    let row = (self.width * 4) as usize;
+    if pixels.len() != row * self.height as usize {
+      return;
+    }
+    if self.flip {
+      let rows: Vec<&[u8]> = pixels.chunks(row).rev().collect();
+      pixels = rows.concat();
+    }
+    queue.write_texture(
+      ImageCopyTexture {
+        texture: &self.texture,
+        mip_level: 0,
+        origin: Origin3d::ZERO,
+        aspect: TextureAspect::All,
+      },
+      &pixels,
+      ImageDataLayout {
+        offset: 0,
+        bytes_per_row: std::num::NonZeroU32::new(self.width * 4),
+        rows_per_image: None,
+      },
+      Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+    );
+  }
+}
+
+fn create_texture(device: &Device, width: u32, height: u32, format: TextureFormat) -> Texture {
+  device.create_texture(&TextureDescriptor {
+    label: Some("webcam"),
+    size: Extent3d { width, height, depth_or_array_layers: 1 },
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: TextureDimension::D2,
+    format,
+    usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+  })
+}
@@ -0,0 +1,89 @@
+// HorusRenderer drawing offscreen, which needs a GPU to run on, or a software one like lavapipe or llvmpipe
+
+use horus::{cli::Options, HorusRenderer, NoWindow, SurfaceTarget};
+
+const RED: &str = "
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(1., 0., 0., 1.);
+}
+";
+
+// counts frames in the red channel, so each frame can be told apart from the one before it
+const FRAMES: &str = "
+struct Uniforms {
+    mouse: vec2<f32>,
+    resolution: vec2<f32>,
+    time: f32,
+    time_delta: f32,
+    frame: i32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(f32(uniforms.frame) / 255., 0., 0., 1.);
+}
+";
+
+// a renderer that's the same every run, or `None` when there's no GPU here to render with
+fn renderer(source: &str, width: u32, height: u32) -> Option<HorusRenderer<NoWindow>> {
+  // linear keeps the values the shader writes, so they can be compared exactly
+  let options = Options { deterministic: true, linear: true, ..Default::default() };
+  match pollster::block_on(HorusRenderer::new(SurfaceTarget::offscreen(width, height), source, &options)) {
+    Ok(renderer) => Some(renderer),
+    Err(error) => {
+      println!("Skipping, since there's no GPU to render with: {}", error);
+      None
+    }
+  }
+}
+
+#[test]
+fn renders_offscreen() {
+  let Some(mut renderer) = renderer(RED, 8, 4) else {
+    return;
+  };
+  assert!(renderer.window().is_none());
+  renderer.update();
+  renderer.render().unwrap();
+  let frame = renderer.read_frame().unwrap();
+  assert_eq!(frame.dimensions(), (8, 4));
+  assert!(frame.pixels().all(|pixel| pixel.0 == [255, 0, 0, 255]));
+}
+
+#[test]
+fn counts_frames() {
+  let Some(mut renderer) = renderer(FRAMES, 2, 2) else {
+    return;
+  };
+  for frame in 0..3 {
+    renderer.update();
+    renderer.render().unwrap();
+    assert_eq!(renderer.read_frame().unwrap().get_pixel(0, 0).0[0], frame);
+  }
+}
+
+#[test]
+fn keeps_the_last_shader_that_compiled() {
+  let Some(mut renderer) = renderer(RED, 2, 2) else {
+    return;
+  };
+  assert!(renderer.set_source("fn fs_main(").is_err());
+  renderer.update();
+  renderer.render().unwrap();
+  assert_eq!(renderer.read_frame().unwrap().get_pixel(1, 1).0, [255, 0, 0, 255]);
+}
+
+#[test]
+fn resizes() {
+  let Some(mut renderer) = renderer(RED, 2, 2) else {
+    return;
+  };
+  renderer.resize(5, 3);
+  renderer.update();
+  renderer.render().unwrap();
+  assert_eq!(renderer.read_frame().unwrap().dimensions(), (5, 3));
+}